@@ -16,6 +16,15 @@ type FormField = {
 }
 
 const FORM: FormField[] = [
+    {
+        name: "name",
+        description: "Lobby name (optional).",
+        type: "text",
+        validate: input => (
+            input.value.trim().length <= 64
+            || "Lobby name must be at most 64 characters."
+        )
+    },
     {
         name: "list",
         description: "List of cards to include in packs.",
@@ -25,6 +34,17 @@ const FORM: FormField[] = [
             input.files?.length == 1 || "Please select a set list file."
         )
     },
+    {
+        name: "webhook_url",
+        description: "URL to send draft events to (optional).",
+        type: "url",
+    },
+    {
+        name: "exclude",
+        description: "List of cards to exclude from the list (optional).",
+        type: "file",
+        accept: ".txt",
+    },
     {
         name: "card_database",
         description: "Card database for custom cards (Cockatrice XML)",
@@ -53,12 +73,235 @@ const FORM: FormField[] = [
                 || "Number of cards per pack must be a positive integer.";
         }
     },
+    {
+        name: "pack_sizes",
+        description: "Cards in each pack by round, e.g. 15, 15, 10 (optional).",
+        type: "text",
+        validate: input => {
+            if (input.value.trim() == "") {
+                return true;
+            }
+
+            let sizes = input.value.split(",").map(s => Number(s.trim()));
+            if (!sizes.every(n => Number.isInteger(n) && n > 0)) {
+                return "Pack sizes must be positive integers separated by commas.";
+            }
+            return sizes.length == get_value("packs")
+                || "Must give a pack size for each pack in the draft.";
+        }
+    },
+    {
+        name: "min_players",
+        description: "Minimum number of players.",
+        type: "number",
+        value: 1,
+        validate: input => {
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val > 0)
+                || "Minimum number of players must be a positive integer.";
+        }
+    },
+    {
+        name: "max_players",
+        description: "Maximum number of players (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val > 0)
+                || "Maximum number of players must be a positive integer.";
+        }
+    },
+    {
+        name: "bots",
+        description: "Number of bots to add to the table.",
+        type: "number",
+        value: 0,
+        validate: input => {
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val >= 0)
+                || "Number of bots must be a non-negative integer.";
+        }
+    },
+    {
+        name: "pick_timer",
+        description: "Seconds to pick from each pack before a card is picked automatically (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val > 0)
+                || "Pick timer must be a positive whole number of seconds.";
+        }
+    },
+    {
+        name: "min_pick_delay",
+        description: "Minimum time before a pick is accepted, in milliseconds (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val >= 0)
+                || "Minimum pick delay must be a whole number of milliseconds.";
+        }
+    },
+    {
+        name: "reconnect_grace",
+        description: "Time a disconnected player keeps their lobby seat, in milliseconds (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val >= 0)
+                || "Reconnection grace period must be a whole number of milliseconds.";
+        }
+    },
+    {
+        name: "deck_size",
+        description: "Deck size, to build decks after the draft (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val > 0)
+                || "Deck size must be a positive integer.";
+        }
+    },
+    {
+        name: "deck_deadline",
+        description: "Seconds to submit a deck before one is built automatically (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val > 0)
+                || "Deck deadline must be a positive whole number of seconds.";
+        }
+    },
+    {
+        name: "mode",
+        description: "Draft format: booster, winston for two or three players, grid for two players, or rochester with one pack open at a time (optional).",
+        type: "text",
+        validate: input => {
+            let val = input.value.trim();
+            return ["", "booster", "winston", "grid", "rochester"].includes(val)
+                || "Must be one of booster, winston, grid or rochester.";
+        }
+    },
+    {
+        name: "image_quality",
+        description: "Card images to show: display, print for full resolution or thumbnail (optional).",
+        type: "text",
+        validate: input => {
+            let val = input.value.trim();
+            return ["", "display", "print", "thumbnail"].includes(val)
+                || "Must be one of display, print or thumbnail.";
+        }
+    },
+    {
+        name: "pass_model",
+        description: "Passing direction between rounds: snake, linear, random or random_seat to pass each pack to a random player (optional).",
+        type: "text",
+        validate: input => {
+            let val = input.value.trim();
+            return ["", "snake", "linear", "random", "random_seat"].includes(val)
+                || "Must be one of snake, linear, random or random_seat.";
+        }
+    },
+    {
+        name: "first_direction",
+        description: "Direction packs are passed in the first round: left or right (optional).",
+        type: "text",
+        validate: input => {
+            let val = input.value.trim();
+            return ["", "left", "right"].includes(val) || "Must be left or right.";
+        }
+    },
+    {
+        name: "seed",
+        description: "Seed for generating packs and random passing, so that a draft can be reproduced (optional).",
+        type: "number",
+        validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val >= 0)
+                || "Seed must be a non-negative integer.";
+        }
+    },
     {
         name: "unique_cards",
         description: "Cards are unique (cube mode).",
         type: "checkbox",
         checked: true,
     },
+    {
+        name: "shuffle_pack_order",
+        description: "Shuffle the cards in each pack, so rares aren't always first.",
+        type: "checkbox",
+        checked: true,
+    },
+    {
+        name: "cover_colours",
+        description: "Ensure each player's packs contain every colour, as in sealed.",
+        type: "checkbox",
+    },
+    {
+        name: "keep_lobby",
+        description: "Return to the lobby after the draft, to draft again.",
+        type: "checkbox",
+    },
+    {
+        name: "strict",
+        description: "Check the pool is complete for the maximum number of players, without substituting rarities.",
+        type: "checkbox",
+    },
+    {
+        name: "reveal_bombs",
+        description: "Reveal the best card in each pack as it is opened.",
+        type: "checkbox",
+    },
+    {
+        name: "include_basics",
+        description: "Add basic lands to each pool once the draft is complete.",
+        type: "checkbox",
+    },
+    {
+        name: "basics_per_colour",
+        description: "Number of each basic land to add to each pool, if adding basic lands.",
+        type: "number",
+        value: 10,
+        validate: input => {
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val > 0 && val <= 40)
+                || "Number of basic lands must be an integer from 1 to 40.";
+        }
+    },
+    {
+        name: "log_messages",
+        description: "Log protocol messages on the server, for debugging.",
+        type: "checkbox",
+    },
     {
         name: "use_rarities",
         description: "Specify the number of each rarity in a pack.",
@@ -66,30 +309,83 @@ const FORM: FormField[] = [
         checked: true,
         oninput: input => {
             set_field_visible("mythic_incidence", input.checked);
+            set_field_visible("identical_rarities", input.checked);
+            set_field_visible("pool_ratio", input.checked);
+            set_field_visible("mythics", input.checked);
             set_field_visible("rares", input.checked);
             set_field_visible("uncommons", input.checked);
             set_field_visible("commons", input.checked);
         }
     },
+    {
+        name: "rarity_labels",
+        description: "Names for mythic, rare, uncommon and common, e.g. for other games (optional).",
+        type: "text",
+        validate: input => {
+            if (input.value.trim() == "") {
+                return true;
+            }
+
+            let labels = input.value.split(",").map(s => s.trim());
+            return (labels.length == 4 && labels.every(l => l.length > 0 && l.length <= 32))
+                || "Must give four names separated by commas.";
+        }
+    },
     {
         name: "mythic_incidence",
-        description: "Rate at which mythics replace rares in packs.",
-        type: "number",
+        description: "Rate at which mythics replace rares in packs, as a fraction or a percentage.",
+        type: "text",
         value: 0.125,
+        validate: input => {
+            let text = input.value.trim();
+            let percent = text.endsWith("%");
+            let val = Number(percent ? text.slice(0, -1).trim() : text);
+            if (percent || val > 1.0) {
+                val /= 100;
+            }
+            return (text != "" && !Number.isNaN(val) && val >= 0.0 && val <= 1.0)
+                || !get_value("use_rarities")
+                || "Must be a fraction in [0.0, 1.0] or a percentage up to 100%."
+        }
+    },
+    {
+        name: "pool_ratio",
+        description: "Warn if a rarity has fewer than this many times the cards needed (optional).",
+        type: "number",
         step: "any",
         validate: input => {
+            if (input.value == "") {
+                return true;
+            }
+
             let val = parseFloat(input.value);
-            return (!Number.isNaN(val) && val >= 0.0 && val <= 1.0)
-                || !get_value("use_rarities")
-                || "Must be a probability in [0.0, 1.0]."
+            return (!Number.isNaN(val) && val >= 0.0)
+                || "Pool ratio must be a non-negative number.";
         }
     },
+    {
+        name: "identical_rarities",
+        description: "Give every pack the same number of mythics.",
+        type: "checkbox",
+    },
+    {
+        name: "mythics",
+        description: "Number of dedicated mythic slots in each pack (optional).",
+        type: "number",
+        oninput: () => {
+            revalidate("rares");
+            revalidate("uncommons");
+            revalidate("commons");
+        },
+        validate: input => input.value == "" || validate_rarity(input),
+    },
     {
         name: "rares",
         description: "Number of rares in each pack.",
         type: "number",
         value: 1,
         oninput: () => {
+            revalidate("mythics");
             revalidate("uncommons");
             revalidate("commons");
         },
@@ -101,6 +397,7 @@ const FORM: FormField[] = [
         type: "number",
         value: 3,
         oninput: () => {
+            revalidate("mythics");
             revalidate("rares");
             revalidate("commons");
         },
@@ -112,11 +409,47 @@ const FORM: FormField[] = [
         type: "number",
         value: 11,
         oninput: () => {
+            revalidate("mythics");
             revalidate("rares");
             revalidate("uncommons");
         },
         validate: validate_rarity,
     },
+    {
+        name: "slot_distribution",
+        description: "Extra slots of random rarity, e.g. \"0,5,25,70\" for a slot which is a rare 5% of the time. Give mythic, rare, uncommon and common weights for each slot, separating slots with semicolons (optional).",
+        type: "text",
+        oninput: () => {
+            revalidate("mythics");
+            revalidate("rares");
+            revalidate("uncommons");
+            revalidate("commons");
+        },
+        validate: input => {
+            if (input.value.trim() == "") {
+                return true;
+            }
+
+            let valid = input.value.split(";").every(slot => {
+                let weights = slot.split(",").map(s => s.trim());
+                return weights.length == 4
+                    && weights.every(w => w != "" && Number.isFinite(+w) && +w >= 0)
+                    && weights.some(w => +w > 0);
+            });
+            return valid || "Must give four weights separated by commas for each slot.";
+        }
+    },
+    {
+        name: "foils",
+        description: "Number of extra foil cards of any rarity in each pack.",
+        type: "number",
+        value: 0,
+        validate: input => {
+            let val = parseInt(input.value);
+            return (Number.isInteger(val) && val >= 0)
+                || "Number of foils must be a non-negative integer.";
+        }
+    },
 ];
 
 function validate_rarity(input: FormInput) {
@@ -130,16 +463,25 @@ function validate_rarity(input: FormInput) {
     }
 
     let total = +get_value("cards_per_pack");
+    let mythics = +get_value("mythics") || 0; // Optional, empty is NaN.
     let rares = +get_value("rares");
     let uncommons = +get_value("uncommons");
     let commons = +get_value("commons");
-    if (rares + uncommons + commons != total) {
-        return "Rares + uncommons + commons must equal cards per pack.";
+    let slots = slot_count(get_value("slot_distribution"));
+    if (mythics + rares + uncommons + commons + slots != total) {
+        return "Mythics + rares + uncommons + commons + random slots must equal cards per pack.";
     }
 
     return true;
 }
 
+function slot_count(distribution: string | number | boolean) {
+    if (typeof distribution != "string" || distribution.trim() == "") {
+        return 0;
+    }
+    return distribution.split(";").length;
+}
+
 function revalidate(name: string) {
     get_input(name)?.validate?.();
 }
@@ -226,6 +568,31 @@ function build_form_input(field: FormField): FormInput {
     return input;
 }
 
+type LaunchResponse = {
+    message: string,
+    success: boolean,
+    lobby?: string,
+    warnings?: string[],
+};
+
+async function launch(form: HTMLFormElement) {
+    let resp: LaunchResponse = await fetch(
+        form.action, { method: "POST", body: new FormData(form) }
+    ).then(r => r.json());
+
+    if (!resp.success || !resp.lobby) {
+        alert(resp.message);
+        return;
+    }
+
+    if (resp.warnings?.length) {
+        alert("Warning:\n" + resp.warnings.join("\n"));
+    }
+
+    // Join the lobby straight away, as it is closed if no one joins.
+    location.href = `/lobby/${resp.lobby}`;
+}
+
 function build_form() {
     const form = document.createElement("form");
     form.id = "config";
@@ -253,10 +620,11 @@ function build_form() {
     row.classList.add("row");
     let button = document.createElement("button");
     button.innerText = "Submit";
+    button.type = "button"; // Launched with fetch, not by submitting the form.
     button.onclick = () => {
         if (inputs.every(input => !input.validate || input.validate())) {
             // TODO save field values in localstorage.
-            form.submit();
+            launch(form);
         }
     };
     row.appendChild(button);
@@ -264,6 +632,17 @@ function build_form() {
     document.body.appendChild(form);
 }
 
+// Identifies this browser to the server, which limits how many lobbies each
+// user may have open at once.
+function ensure_user_token() {
+    const COOKIE = "drafttool_token";
+    if (!document.cookie.split("; ").some(c => c.startsWith(COOKIE + "="))) {
+        let token = crypto.randomUUID();
+        document.cookie = `${COOKIE}=${token}; path=/; max-age=31536000; samesite=strict`;
+    }
+}
+
 window.onload = () => {
+    ensure_user_token();
     build_form();
 };
@@ -1,20 +1,47 @@
 enum Css {
     Card = "card",
+    CardGrid = "card-grid",
     Center = "center",
+    EmptySlot = "empty-slot",
+    Foil = "foil",
     Hide = "hide",
     Label = "label",
     Selected = "selected",
 }
 
+type Colour = "W" | "U" | "B" | "R" | "G";
+
+type CardImages = {
+    png?: string,
+    large?: string,
+    normal?: string,
+    small?: string,
+    border_crop?: string,
+    art_crop?: string,
+};
+
 type Card = {
     name: string,
     image: string,
+    images?: CardImages,
     set: string,
     rarity: "Mythic" | "Rare" | "Uncommon" | "Common" | "Special" | "Bonus",
-    text: string
+    rarity_label?: string,
+    text: string,
+    colours: Colour[],
+    power?: string,
+    toughness?: string,
+    loyalty?: string,
+    foil?: boolean,
 };
 
-type Status = "Ok" | "Warning" | "Error";
+type Pack = {
+    id: number,
+    cards: Card[],
+    origin: string,
+};
+
+type Status = "Ok" | "Warning" | "Error" | "Left";
 
 type PlayerDetails = {
     seat: string,
@@ -25,18 +52,48 @@ type PlayerDetails = {
 
 type PlayerList = PlayerDetails[];
 
+type PoolSummary = {
+    colours: { [colour in Colour]?: number },
+    colourless: number,
+};
+
+type Reveal = {
+    seat: string,
+    card: Card,
+};
+
+type DraftError = {
+    kind: string,
+    message: string,
+};
+
+type WinstonState = {
+    active: string | null,
+    pile: number,
+    pile_sizes: number[],
+    deck_size: number,
+};
+
 type ServerMessage =
     { type: "Started" }
+    | { type: "LobbyFull" }
     | { type: "Ended" }
-    | { type: "FatalError", value: string }
-    | { type: "Pack", "value": Card[] }
+    | { type: "FatalError", value: DraftError }
+    | { type: "Pack", "value": { pack: Pack, time_left: number | null } }
+    | { type: "PeekPack", "value": Pack }
     | { type: "PickSuccessful", "value": Card }
+    | { type: "PickUndone", value: Card[] }
+    | { type: "UndoRejected", value: string }
+    | { type: "PackWithdrawn" }
+    | { type: "DraftComplete" }
     | { type: "Finished", value: Card[] }
+    | { type: "PoolSummary", value: PoolSummary }
     | {
         type: "Connected",
         value: {
             draft: string,
             seat: string,
+            name?: string,
         }
     }
     | {
@@ -44,9 +101,10 @@ type ServerMessage =
         value: {
             draft: string,
             seat: string,
+            name?: string,
             in_progress: boolean,
             pool: Card[],
-            pack?: Card[],
+            pack?: Pack,
         }
     } | { type: "Refresh" }
     | {
@@ -55,14 +113,57 @@ type ServerMessage =
     } | {
         type: "PlayerList",
         value: PlayerList
-    } | { type: "QueueSize", value: { seat: string, count: number } };
+    } | {
+        type: "Starting",
+        value: {
+            players: PlayerList,
+            summary: {
+                rounds: number,
+                pack_sizes: number[],
+                deck_size?: number,
+            },
+        }
+    } | { type: "Revealed", value: Reveal[] }
+    | { type: "QueueSize", value: { seat: string, count: number } }
+    | { type: "LobbyName", value: string }
+    | { type: "DeckBuilding", value: number }
+    | { type: "DeckSubmitted", value: Card[] }
+    | { type: "DeckRejected", value: string }
+    | { type: "DeckDeadline", value: number }
+    | { type: "DeckAutoSubmitted", value: Card[] }
+    | { type: "Decks", value: { [seat: string]: Card[] } }
+    | { type: "Kicked" }
+    | { type: "ReturnedToLobby" }
+    | { type: "Winston", value: WinstonState }
+    | { type: "WinstonPile", value: Card[] }
+    | { type: "WinstonTaken", value: Card[] }
+    | { type: "WinstonRejected", value: string }
+    | { type: "Grid", value: (Card | null)[] }
+    | { type: "GridTurn" }
+    | { type: "GridTaken", value: Card[] }
+    | { type: "GridRejected", value: string }
+    | { type: "ReclaimCode", value: string }
+    | { type: "ReclaimRejected", value: string }
+    | { type: "YourTurn" }
+    | { type: "WaitingFor", value: string };
 
 type ClientMessage =
     { type: "HeartBeat" }
     | { type: "ReadyState", value: boolean }
     | { type: "Disconnected" }
+    | { type: "Leave" }
     | { type: "SetName", value: string }
-    | { type: "Pick", value: number };
+    | { type: "Pick", value: { pack: number, index: number } }
+    | { type: "UndoPick" }
+    | { type: "PeekNext" }
+    | { type: "SetLobbyName", value: string }
+    | { type: "SubmitDeck", value: number[] }
+    | { type: "EndBuilding" }
+    | { type: "Kick", value: string }
+    | { type: "Winston", value: "Take" | "Pass" }
+    | { type: "Grid", value: { Row: number } | { Column: number } }
+    | { type: "RequestReclaimCode" }
+    | { type: "Reclaim", value: string };
 
 enum Phase {
     Connecting,
@@ -78,21 +179,35 @@ type UiState =
         phase: Phase.Lobby,
         updatePlayerList: (players: PlayerList) => void,
         updatePlayerDetails: (details: PlayerDetails) => void,
+        showReclaimStatus: (status: string) => void,
     }
     | {
         phase: Phase.Draft,
-        receivePack: (pack: Card[]) => void,
+        receivePack: (pack: Pack | null) => void,
+        startPickTimer: (seconds: number | null) => void,
         pickSuccessful: (picked: Card) => void,
         updatePlayerList: (players: PlayerList) => void,
         updatePlayerDetails: (details: PlayerDetails) => void,
         updatePool: (pool: Card[]) => void,
         updateQueuedCount: (seat: string, count: number) => void,
+        showRevealed: (reveals: Reveal[]) => void,
+        showWinston: (winston: WinstonState) => void,
+        showWinstonPile: (cards: Card[]) => void,
+        winstonTaken: (cards: Card[]) => void,
+        showGrid: (slots: (Card | null)[], turn: boolean) => void,
+        gridTurn: () => void,
+        showTurn: (seat: string | null) => void,
     }
     | {
         phase: Phase.Finished,
         updatePlayerList: (players: PlayerList) => void,
         updatePlayerDetails: (details: PlayerDetails) => void,
         updatePool: (pool: Card[]) => void,
+        showPoolSummary: (summary: PoolSummary) => void,
+        startDeckBuilding: (size: number) => void,
+        updateDeckStatus: (message: string) => void,
+        startDeckDeadline: (seconds: number) => void,
+        showDecks: (decks: { [seat: string]: Card[] }) => void,
     }
     | {
         phase: Phase.Terminated,
@@ -269,11 +384,12 @@ function statusIndicator(parent: HTMLElement, status: Status): HTMLElement {
 }
 
 function updateStatusIndicator(element: HTMLElement, status: Status) {
-    element.classList.remove("ok", "warn", "err");
+    element.classList.remove("ok", "warn", "err", "left");
     element.classList.add(
         status == "Warning" ? "warn"
             : status == "Error" ? "err"
-                : "ok"
+                : status == "Left" ? "left"
+                    : "ok"
     );
 }
 
@@ -433,17 +549,50 @@ function setUpLobby(root: HTMLElement): UiState {
     const updatePlayerDetails =
         (details: PlayerDetails) => updatePlayerListEntry(details, lobbyState);
 
+    // A reclaim code lets the player reclaim their seat if they lose its ID,
+    // for example by clearing their browser storage.
+    let reclaim = classes(el("div", float), "padhalf");
+    let requestCode = text(el("button", reclaim), "Get reclaim code");
+    requestCode.onclick = () => sendMessage({ type: "RequestReclaimCode" });
+    text(el("span", reclaim), " Reclaim code: ");
+    let code = input("text", reclaim);
+    let reclaimSeat = text(el("button", reclaim), "Reclaim seat");
+    reclaimSeat.onclick = () => sendMessage(
+        { type: "Reclaim", value: code.value }
+    );
+    let reclaimStatus = el("div", reclaim);
+
+    const showReclaimStatus = (status: string) => text(reclaimStatus, status);
+
     return {
         phase: Phase.Lobby,
         updatePlayerList,
         updatePlayerDetails,
+        showReclaimStatus,
     };
 }
 
+function cardStats(card: Card): string {
+    if (card.power !== undefined && card.toughness !== undefined) {
+        return ` ${card.power}/${card.toughness}`;
+    } else if (card.loyalty !== undefined) {
+        return ` [${card.loyalty}]`;
+    }
+    return "";
+}
+
 function renderCard(root: HTMLElement, card: Card): HTMLElement {
     let img = el("img", root);
     attr(img, "src", card.image);
+    attr(
+        img,
+        "title",
+        `${card.name} (${card.rarity_label ?? card.rarity})${cardStats(card)}`
+    );
     classes(img, Css.Card);
+    if (card.foil) {
+        classes(img, Css.Foil);
+    }
     return img;
 }
 
@@ -456,15 +605,16 @@ function renderCardList(root: HTMLElement, cards: Card[]) {
     ));
 }
 
-function populatePack(root: HTMLElement, cards: Card[]) {
+function populatePack(root: HTMLElement, pack: Pack | null) {
     root.innerHTML = "";
-    if (cards.length == 0) {
+    if (pack == null || pack.cards.length == 0) {
         heading(root, "Waiting for pack");
         return;
     }
 
     heading(root, "Current pack");
-    renderCardList(root, cards);
+    renderCardList(root, pack.cards);
+    const packId = pack.id;
     forEachEl(`.${Css.Card}`, img => img.onclick = e => {
         if (img.classList.contains(Css.Selected)) {
             if (img.dataset.index === undefined) {
@@ -473,7 +623,7 @@ function populatePack(root: HTMLElement, cards: Card[]) {
             }
             sendMessage({
                 type: "Pick",
-                value: parseInt(img.dataset.index)
+                value: { pack: packId, index: parseInt(img.dataset.index) }
             });
         } else {
             forEachEl(
@@ -548,6 +698,8 @@ function setUpDraft(root: HTMLElement): UiState {
     let header = classes(el("div", float), "container", "simple-border");
     heading(header, "Draft in progress");
     let pack = classes(el("div", float), "container", "simple-border");
+    let revealed = classes(el("div", float), "container", "simple-border");
+    setVisible(revealed, false);
     let pool = classes(el("div", float), "container", "simple-border");
     heading(pool, "Picked cards");
 
@@ -567,12 +719,35 @@ function setUpDraft(root: HTMLElement): UiState {
         );
     };
 
-    const receivePack = (cards: Card[]) => {
-        populatePack(pack, cards);
+    const receivePack = (received: Pack | null) => {
+        populatePack(pack, received);
         updateCardWidths();
     };
 
+    let pickTimer = el("span", classes(el("span", headerControls), "padhalf"));
+    let pickInterval: number | undefined;
+
+    const startPickTimer = (seconds: number | null) => {
+        window.clearInterval(pickInterval);
+        text(pickTimer, "");
+        if (seconds === null) {
+            return;
+        }
+
+        const end = Date.now() + seconds * 1000;
+        const tick = () => {
+            let left = Math.max(0, Math.ceil((end - Date.now()) / 1000));
+            text(pickTimer, `${left}s left to pick.`);
+        };
+        pickInterval = window.setInterval(tick, 1000);
+        tick();
+    };
+
+    let undo = text(el("button", classes(el("span", headerControls), "padhalf")), "Undo pick");
+    undo.onclick = () => sendMessage({ type: "UndoPick" });
+
     const pickSuccessful = (card: Card) => {
+        startPickTimer(null);
         pack.innerHTML = "";
         heading(pack, "Waiting for pack");
         renderCard(pool, card);
@@ -586,14 +761,121 @@ function setUpDraft(root: HTMLElement): UiState {
         updateCardWidths();
     };
 
+    const showRevealed = (reveals: Reveal[]) => {
+        revealed.innerHTML = "";
+        heading(revealed, "Opened this round");
+        reveals.forEach(reveal => {
+            let name = state.playerDetails.get(reveal.seat)?.name ?? "";
+            renderCard(revealed, reveal.card).title = name;
+        });
+        setVisible(revealed, true);
+        updateCardWidths();
+    };
+
+    let winstonStatus = el("span", classes(el("span", headerControls), "padhalf"));
+
+    const showWinston = (winston: WinstonState) => {
+        if (winston.active == null) {
+            text(winstonStatus, "");
+            return;
+        }
+
+        let name = state.playerDetails.get(winston.active)?.name ?? winston.active;
+        text(
+            winstonStatus,
+            `${name} is looking at pile ${winston.pile + 1}. `
+            + `Piles: ${winston.pile_sizes.join(", ")}. Deck: ${winston.deck_size}.`
+        );
+        if (winston.active != state.seat) {
+            pack.innerHTML = "";
+            heading(pack, "Waiting for your turn");
+        }
+    };
+
+    const showWinstonPile = (cards: Card[]) => {
+        pack.innerHTML = "";
+        heading(pack, "Current pile");
+        let controls = classes(el("div", pack), "padhalf");
+        text(el("button", controls), "Take pile").onclick = () => {
+            sendMessage({ type: "Winston", value: "Take" });
+        };
+        text(el("button", controls), "Pass").onclick = () => {
+            sendMessage({ type: "Winston", value: "Pass" });
+        };
+        renderCardList(pack, cards);
+        updateCardWidths();
+    };
+
+    const winstonTaken = (cards: Card[]) => {
+        cards.forEach(card => renderCard(pool, card));
+        updateCardWidths();
+    };
+
+    let grid: (Card | null)[] = [];
+
+    const showGrid = (slots: (Card | null)[], turn: boolean) => {
+        grid = slots;
+        pack.innerHTML = "";
+        heading(pack, turn ? "Take a row or column" : "Waiting for your turn");
+        if (turn) {
+            let controls = classes(el("div", pack), "padhalf");
+            for (let i = 0; i < 3; i++) {
+                text(el("button", controls), `Row ${i + 1}`).onclick = () => {
+                    sendMessage({ type: "Grid", value: { Row: i } });
+                };
+            }
+            for (let i = 0; i < 3; i++) {
+                text(el("button", controls), `Column ${i + 1}`).onclick = () => {
+                    sendMessage({ type: "Grid", value: { Column: i } });
+                };
+            }
+        }
+
+        let cards = classes(el("div", pack), Css.CardGrid);
+        slots.forEach(card => {
+            if (card) {
+                renderCard(cards, card);
+            } else {
+                classes(el("div", cards), Css.Card, Css.EmptySlot);
+            }
+        });
+        updateCardWidths();
+    };
+
+    const gridTurn = () => showGrid(grid, true);
+
+    // In a rochester draft, the open pack is shown to the whole table, headed
+    // with whose turn it is to pick.
+    const showTurn = (seat: string | null) => {
+        let title = pack.querySelector(".container-heading");
+        if (title == null) {
+            return;
+        }
+
+        if (seat == null) {
+            text(title as HTMLElement, "Your turn to pick");
+        } else {
+            let name = state.playerDetails.get(seat)?.name ?? seat;
+            text(title as HTMLElement, `Waiting for ${name} to pick`);
+        }
+    };
+
     return {
         phase: Phase.Draft,
         receivePack,
+        startPickTimer,
         pickSuccessful,
         updatePlayerList,
         updatePlayerDetails,
         updatePool,
         updateQueuedCount,
+        showRevealed,
+        showWinston,
+        showWinstonPile,
+        winstonTaken,
+        showGrid,
+        gridTurn,
+        showTurn,
     };
 }
 
@@ -635,11 +917,104 @@ function setUpFinished(root: HTMLElement): UiState {
         updateCardWidths();
     };
 
+    const updatePlayerList = (players: PlayerList) => {
+        state.players = players.map(details => details.seat);
+        state.playerDetails.clear();
+        players.forEach(details => updatePlayerDetails(details));
+    };
+
+    const updatePlayerDetails = (details: PlayerDetails) => {
+        state.playerDetails.set(details.seat, details);
+    };
+
+    let poolSummary = el("span", classes(el("span", headerControls), "padhalf"));
+
+    const showPoolSummary = (summary: PoolSummary) => {
+        const colours: Colour[] = ["W", "U", "B", "R", "G"];
+        let counts = colours
+            .filter(colour => summary.colours[colour])
+            .map(colour => `${colour} ${summary.colours[colour]}`);
+        if (summary.colourless) {
+            counts.push(`Colourless ${summary.colourless}`);
+        }
+        text(poolSummary, counts.join(", "));
+    };
+
+    let deckStatus = el("span", classes(el("span", headerControls), "padhalf"));
+
+    const updateDeckStatus = (message: string) => text(deckStatus, message);
+
+    let deckDeadline = el("span", classes(el("span", headerControls), "padhalf"));
+    let deadlineTimer: number | undefined;
+
+    const stopDeckDeadline = () => {
+        window.clearInterval(deadlineTimer);
+        text(deckDeadline, "");
+    };
+
+    const startDeckDeadline = (seconds: number) => {
+        const end = Date.now() + seconds * 1000;
+        const tick = () => {
+            let left = Math.max(0, Math.ceil((end - Date.now()) / 1000));
+            text(deckDeadline, `${left}s left to submit.`);
+        };
+
+        window.clearInterval(deadlineTimer);
+        deadlineTimer = window.setInterval(tick, 1000);
+        tick();
+    };
+
+    const startDeckBuilding = (size: number) => {
+        const selected = () => pool.querySelectorAll(`.${Css.Card}.${Css.Selected}`);
+        const showSelected = () => updateDeckStatus(
+            `${selected().length}/${size} cards selected.`
+        );
+
+        pool.onclick = e => {
+            let card = e.target as HTMLElement;
+            if (card.classList.contains(Css.Card)) {
+                card.classList.toggle(Css.Selected);
+                showSelected();
+            }
+        };
+
+        let controls = classes(el("span", headerControls), "padhalf");
+        text(el("button", controls), "Submit deck").onclick = () => {
+            let indices: number[] = [];
+            selected().forEach(card => {
+                let index = (card as HTMLElement).dataset.index;
+                if (index !== undefined) {
+                    indices.push(parseInt(index));
+                }
+            });
+            sendMessage({ type: "SubmitDeck", value: indices });
+        };
+        showSelected();
+    };
+
+    const showDecks = (decks: { [seat: string]: Card[] }) => {
+        pool.onclick = null;
+        stopDeckDeadline();
+        updateDeckStatus("Deckbuilding complete.");
+        for (let seat in decks) {
+            let deck = classes(el("div", float), "container", "simple-border");
+            let name = state.playerDetails.get(seat)?.name ?? seat;
+            heading(deck, `${name}'s deck`);
+            renderCardList(deck, decks[seat]);
+        }
+        updateCardWidths();
+    };
+
     return {
         phase: Phase.Finished,
-        updatePlayerList: null!, // TODO
-        updatePlayerDetails: null!, // TODO
+        updatePlayerList,
+        updatePlayerDetails,
         updatePool,
+        showPoolSummary,
+        startDeckBuilding,
+        updateDeckStatus,
+        startDeckDeadline,
+        showDecks,
     };
 }
 
@@ -701,7 +1076,7 @@ function terminate(message: string) {
     displayErrorMessage(message);
 }
 
-function receivedPack(pack: Card[]) {
+function receivedPack(pack: Pack | null) {
     if (state.ui.phase == Phase.Draft) {
         state.ui.receivePack(pack);
     } else {
@@ -750,6 +1125,10 @@ function updatePlayerList(playerList: PlayerList) {
     }
 }
 
+function updateLobbyName(name?: string) {
+    document.title = name ? name : "Draft";
+}
+
 function updateDraftSeat(draft: string, seat: string) {
     state.draft = draft;
     state.seat = seat;
@@ -759,35 +1138,132 @@ function updateDraftSeat(draft: string, seat: string) {
 function handleMessage(message: ServerMessage) {
     switch (message.type) {
         case "Started":
-            terminate("Failed to join draft. Draft has already started.");
+            let code = window.prompt(
+                "Draft has already started. Enter your reclaim code to rejoin."
+            );
+            if (code) {
+                sendMessage({ type: "Reclaim", value: code });
+            } else {
+                terminate("Failed to join draft. Draft has already started.");
+            }
+            break;
+        case "ReclaimCode":
+            if (state.ui.phase == Phase.Lobby) {
+                state.ui.showReclaimStatus(
+                    `Your reclaim code is ${message.value}. Keep it safe.`
+                );
+            }
+            break;
+        case "ReclaimRejected":
+            if (state.ui.phase == Phase.Lobby) {
+                state.ui.showReclaimStatus(message.value);
+            } else {
+                terminate("Failed to join draft. " + message.value);
+            }
+            break;
+        case "LobbyFull":
+            terminate("Failed to join draft. Lobby is full.");
             break;
         case "Ended":
             terminate("Failed to join draft. Draft already complete.");
             break;
         case "FatalError":
-            terminate("Server error: " + message.value);
+            terminate("Server error: " + message.value.message);
             break;
         case "Pack":
             moveToPhase(Phase.Draft);
-            receivedPack(message.value);
+            receivedPack(message.value.pack);
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.startPickTimer(message.value.time_left);
+            }
             break;
         case "PickSuccessful":
             pickSuccessful(message.value);
             break;
+        case "PickUndone":
+            updatePool(message.value);
+            break;
+        case "UndoRejected":
+            console.warn("Undo rejected:", message.value);
+            break;
+        case "PackWithdrawn":
+            receivedPack(null);
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.startPickTimer(null);
+            }
+            break;
+        case "Winston":
+            moveToPhase(Phase.Draft);
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.showWinston(message.value);
+            }
+            break;
+        case "WinstonPile":
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.showWinstonPile(message.value);
+            }
+            break;
+        case "WinstonTaken":
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.winstonTaken(message.value);
+            }
+            break;
+        case "WinstonRejected":
+            console.warn("Winston action rejected:", message.value);
+            break;
+        case "Grid":
+            moveToPhase(Phase.Draft);
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.showGrid(message.value, false);
+            }
+            break;
+        case "GridTurn":
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.gridTurn();
+            }
+            break;
+        case "GridTaken":
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.winstonTaken(message.value);
+            }
+            break;
+        case "GridRejected":
+            console.warn("Grid selection rejected:", message.value);
+            break;
+        case "YourTurn":
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.showTurn(null);
+            }
+            break;
+        case "WaitingFor":
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.showTurn(message.value);
+            }
+            break;
+        case "DraftComplete":
+            moveToPhase(Phase.Finished);
+            break;
         case "Finished":
             moveToPhase(Phase.Finished);
             updatePool(message.value);
             break;
+        case "PoolSummary":
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.showPoolSummary(message.value);
+            }
+            break;
         case "Connected":
             moveToPhase(Phase.Lobby);
             updateDraftSeat(message.value.draft, message.value.seat);
+            updateLobbyName(message.value.name);
             break;
         case "Reconnected":
             let draft_in_progress = message.value.in_progress;
             moveToPhase(draft_in_progress ? Phase.Draft : Phase.Finished);
             updateDraftSeat(message.value.draft, message.value.seat);
+            updateLobbyName(message.value.name);
             updatePool(message.value.pool);
-            receivedPack(message.value.pack ? message.value.pack : []);
+            receivedPack(message.value.pack ? message.value.pack : null);
             break;
         case "Refresh":
             location.href = location.href;
@@ -798,6 +1274,16 @@ function handleMessage(message: ServerMessage) {
         case "PlayerList":
             updatePlayerList(message.value);
             break;
+        case "Starting":
+            // Show the final table before the first packs arrive.
+            updatePlayerList(message.value.players);
+            break;
+        case "Revealed":
+            moveToPhase(Phase.Draft);
+            if (state.ui.phase == Phase.Draft) {
+                state.ui.showRevealed(message.value);
+            }
+            break;
         case "QueueSize":
             if (state.ui.phase == Phase.Draft) {
                 state.ui.updateQueuedCount(
@@ -806,6 +1292,51 @@ function handleMessage(message: ServerMessage) {
                 );
             }
             break;
+        case "LobbyName":
+            updateLobbyName(message.value);
+            break;
+        case "DeckBuilding":
+            moveToPhase(Phase.Finished);
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.startDeckBuilding(message.value);
+            }
+            break;
+        case "DeckSubmitted":
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.updateDeckStatus(
+                    `Deck of ${message.value.length} cards submitted.`
+                );
+            }
+            break;
+        case "DeckDeadline":
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.startDeckDeadline(message.value);
+            }
+            break;
+        case "DeckAutoSubmitted":
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.updateDeckStatus(
+                    `Out of time, deck of ${message.value.length} cards submitted automatically.`
+                );
+            }
+            break;
+        case "DeckRejected":
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.updateDeckStatus(message.value);
+            }
+            break;
+        case "Decks":
+            if (state.ui.phase == Phase.Finished) {
+                state.ui.showDecks(message.value);
+            }
+            break;
+        case "Kicked":
+            terminate("Removed from the draft by the host.");
+            break;
+        case "ReturnedToLobby":
+            // The draft is over; ready up to draft again.
+            moveToPhase(Phase.Lobby);
+            break;
     }
 }
 
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 use quick_xml::DeError;
 
-use crate::cards::{Card, Rarity};
+use crate::cards::{secure_image_url, Card, Colour, Rarity};
 
 #[allow(unused)]
 #[derive(serde::Deserialize)]
@@ -23,26 +23,17 @@ struct XmlSetEntry {
     #[serde(rename = "@rarity")]
     rarity: String,
 
-    #[serde(rename = "@picURL")]
+    #[serde(default, rename = "@picURL")]
     image: String,
 
     #[serde(rename = "$text")]
     name: String,
 }
 
-#[derive(serde::Deserialize, PartialEq, Debug)]
-enum XmlColour {
-    W,
-    U,
-    B,
-    R,
-    G,
-}
-
 #[derive(serde::Deserialize, PartialEq, Debug)]
 struct XmlColourHolder {
     #[serde(rename = "$text")]
-    inner: XmlColour,
+    inner: Colour,
 }
 
 #[allow(unused)]
@@ -54,13 +45,17 @@ struct XmlCard {
     #[serde(default, rename = "color")]
     colour: Vec<XmlColourHolder>,
 
-    manacost: String,
-    cmc: u32,
+    // Cards from partial databases may be missing any of the rest.
+    manacost: Option<String>,
+    cmc: Option<u32>,
 
     #[serde(rename = "type")]
-    ty: String,
+    ty: Option<String>,
 
     pt: Option<String>,
+    loyalty: Option<String>,
+
+    #[serde(default)]
     text: String,
 }
 
@@ -68,7 +63,7 @@ impl XmlCard {
     fn rarity(&self) -> Option<Rarity> {
         let rarity_str = self.set.rarity.replace(" Rare", "");
         match rarity_str.as_str() {
-            "Mythic" => Some(Rarity::Rare),
+            "Mythic" => Some(Rarity::Mythic),
             "Rare" => Some(Rarity::Rare),
             "Uncommon" => Some(Rarity::Uncommon),
             "Common" => Some(Rarity::Common),
@@ -77,6 +72,18 @@ impl XmlCard {
     }
 }
 
+/// Split a power/toughness string, e.g. "0/1" or "*/1+*", into power and
+/// toughness.
+fn parse_pt(pt: &str) -> (Option<String>, Option<String>) {
+    match pt.split_once('/') {
+        Some((power, toughness)) => (
+            Some(power.trim().to_string()),
+            Some(toughness.trim().to_string()),
+        ),
+        None => (None, None),
+    }
+}
+
 #[derive(serde::Deserialize)]
 struct XmlCardList {
     #[serde(default, rename = "card")]
@@ -92,29 +99,124 @@ struct XmlCardDb {
     cards: XmlCardList,
 }
 
+/// Cards decoded from a Cockatrice card database.
+pub struct DecodedCards {
+    /// Map from lowercased card name to card.
+    pub cards: HashMap<String, Card>,
+
+    /// Names of cards left out because their rarity wasn't recognised.
+    pub skipped: Vec<String>,
+}
+
 /// Decode the provided cockatrice card database XML into a map from lowercased
 /// card name to card object. This ensures that all cards in the database are
 /// unique and handles name case normalisation for building the card list.
-pub fn decode_xml_cards(data: bytes::Bytes) -> Result<HashMap<String, Card>, DeError> {
+/// Cards with unrecognised rarities are skipped, and listed in the result.
+pub fn decode_xml_cards(data: bytes::Bytes) -> Result<DecodedCards, DeError> {
     let mut map = HashMap::new();
+    let mut skipped = Vec::new();
     let xml: XmlCardDb = quick_xml::de::from_reader(&*data)?;
 
     for card in xml.cards.list {
-        if let Some(rarity) = card.rarity() {
-            map.insert(
-                card.name.to_lowercase(),
-                Card::new(card.name, card.set.image, card.set.name, card.text, rarity),
-            );
+        let Some(rarity) = card.rarity() else {
+            skipped.push(card.name);
+            continue;
+        };
+
+        let (power, toughness) = card.pt.as_deref().map(parse_pt).unwrap_or_default();
+        let mut entry = Card::new(
+            card.name,
+            secure_image_url(card.set.image),
+            card.set.name,
+            card.text,
+            rarity,
+            card.colour.into_iter().map(|c| c.inner).collect(),
+        );
+        entry.set_stats(power, toughness, card.loyalty);
+        map.insert(entry.name().to_lowercase(), entry);
+    }
+
+    Ok(DecodedCards {
+        cards: map,
+        skipped,
+    })
+}
+
+#[derive(serde::Serialize)]
+struct XmlDeckCard {
+    #[serde(rename = "@number")]
+    number: usize,
+
+    #[serde(rename = "@name")]
+    name: String,
+}
+
+#[derive(serde::Serialize)]
+struct XmlZone {
+    #[serde(rename = "@name")]
+    name: &'static str,
+    card: Vec<XmlDeckCard>,
+}
+
+#[derive(serde::Serialize)]
+#[serde(rename = "cockatrice_deck")]
+struct XmlDeck {
+    #[serde(rename = "@version")]
+    version: u32,
+    deckname: String,
+    zone: XmlZone,
+}
+
+/// Encode the cards as a Cockatrice deck file, with all of the cards in the
+/// main deck. Copies of a card are listed once, with the number of copies.
+pub fn encode_deck(name: &str, cards: &[Card]) -> Result<String, DeError> {
+    let mut entries: Vec<XmlDeckCard> = Vec::new();
+    for card in cards {
+        match entries.iter_mut().find(|entry| entry.name == card.name()) {
+            Some(entry) => entry.number += 1,
+            None => entries.push(XmlDeckCard {
+                number: 1,
+                name: card.name().to_string(),
+            }),
         }
     }
 
-    Ok(map)
+    let deck = XmlDeck {
+        version: 1,
+        deckname: name.to_string(),
+        zone: XmlZone {
+            name: "main",
+            card: entries,
+        },
+    };
+    let xml = quick_xml::se::to_string(&deck)?;
+    Ok(format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n{xml}"))
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_encode_deck() {
+        let bolt = Card::sample(Rarity::Common);
+        let other = Card::sample(Rarity::Rare);
+        let xml = encode_deck("Pool & Co", &[bolt.clone(), other.clone(), bolt.clone()]).unwrap();
+        assert_eq!(
+            xml,
+            format!(
+                concat!(
+                    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+                    "<cockatrice_deck version=\"1\"><deckname>Pool &amp; Co</deckname>",
+                    "<zone name=\"main\"><card number=\"2\" name=\"{}\"/>",
+                    "<card number=\"1\" name=\"{}\"/></zone></cockatrice_deck>"
+                ),
+                bolt.name(),
+                other.name()
+            )
+        );
+    }
+
     #[test]
     fn test_decode() {
         const DATA: &str = r#"
@@ -158,21 +260,115 @@ Pay 1 life: Regenerate Nibbles. </text>
         assert_eq!(
             card.colour,
             vec![
-                XmlColourHolder {
-                    inner: XmlColour::B
-                },
-                XmlColourHolder {
-                    inner: XmlColour::G
-                }
+                XmlColourHolder { inner: Colour::B },
+                XmlColourHolder { inner: Colour::G }
             ]
         );
-        assert_eq!(card.manacost, "G/B");
-        assert_eq!(card.cmc, 1);
-        assert_eq!(card.ty, "Legendary Creature — Zombie Squirrel");
+        assert_eq!(card.manacost.as_deref(), Some("G/B"));
+        assert_eq!(card.cmc, Some(1));
+        assert_eq!(
+            card.ty.as_deref(),
+            Some("Legendary Creature — Zombie Squirrel")
+        );
         assert_eq!(card.pt.as_ref().unwrap(), "0/1");
         assert!(card.text.starts_with("Each other Zombie"));
     }
 
+    #[test]
+    fn test_decode_stats() {
+        const DATA: &str = r#"
+<cockatrice_carddatabase version="3">
+  <cards>
+    <card>
+      <name>Nibbles, Corpse Companion</name>
+      <set rarity="Uncommon" picURL="https://mtg.design/i/vjre15.jpg">KR2</set>
+      <manacost>G/B</manacost>
+      <cmc>1</cmc>
+      <type>Legendary Creature — Zombie Squirrel</type>
+      <pt>0/1</pt>
+      <text>Pay 1 life: Regenerate Nibbles.</text>
+    </card>
+    <card>
+      <name>Squirrel Sage</name>
+      <set rarity="Mythic Rare" picURL="https://mtg.design/i/abc123.jpg">KR2</set>
+      <manacost>2G</manacost>
+      <cmc>3</cmc>
+      <type>Legendary Planeswalker — Squirrel</type>
+      <loyalty>4</loyalty>
+      <text>+1: Create a 1/1 green Squirrel creature token.</text>
+    </card>
+  </cards>
+</cockatrice_carddatabase>
+      "#;
+
+        let cards = decode_xml_cards(bytes::Bytes::from(DATA)).unwrap().cards;
+
+        let nibbles = &cards["nibbles, corpse companion"];
+        assert_eq!(nibbles.power.as_deref(), Some("0"));
+        assert_eq!(nibbles.toughness.as_deref(), Some("1"));
+        assert_eq!(nibbles.loyalty.as_deref(), None);
+
+        let sage = &cards["squirrel sage"];
+        assert_eq!(sage.rarity, Rarity::Mythic);
+        assert_eq!(sage.power.as_deref(), None);
+        assert_eq!(sage.toughness.as_deref(), None);
+        assert_eq!(sage.loyalty.as_deref(), Some("4"));
+    }
+
+    #[test]
+    fn test_decode_minimal() {
+        const DATA: &str = r#"
+<cockatrice_carddatabase version="3">
+  <cards>
+    <card>
+      <name>Squirrel Token Maker</name>
+      <set rarity="Common">KR2</set>
+    </card>
+  </cards>
+</cockatrice_carddatabase>
+      "#;
+
+        let cards = decode_xml_cards(bytes::Bytes::from(DATA)).unwrap().cards;
+        let card = &cards["squirrel token maker"];
+        assert_eq!(card.name(), "Squirrel Token Maker");
+        assert_eq!(card.rarity, Rarity::Common);
+        assert_eq!(card.power.as_deref(), None);
+    }
+
+    #[test]
+    fn test_decode_skipped() {
+        const DATA: &str = r#"
+<cockatrice_carddatabase version="3">
+  <cards>
+    <card>
+      <name>Squirrel Sage</name>
+      <set rarity="Rare">KR2</set>
+    </card>
+    <card>
+      <name>Squirrel Token</name>
+      <set rarity="Token">KR2</set>
+    </card>
+    <card>
+      <name>Nibbles</name>
+      <set rarity="Uncomon">KR2</set>
+    </card>
+  </cards>
+</cockatrice_carddatabase>
+      "#;
+
+        let decoded = decode_xml_cards(bytes::Bytes::from(DATA)).unwrap();
+        assert_eq!(decoded.cards.len(), 1);
+        assert!(decoded.cards.contains_key("squirrel sage"));
+        assert_eq!(decoded.skipped, ["Squirrel Token", "Nibbles"]);
+    }
+
+    #[test]
+    fn test_parse_pt() {
+        assert_eq!(parse_pt("0/1"), (Some("0".into()), Some("1".into())));
+        assert_eq!(parse_pt("*/1+*"), (Some("*".into()), Some("1+*".into())));
+        assert_eq!(parse_pt("3"), (None, None));
+    }
+
     #[test]
     fn test_reject() {
         assert!(quick_xml::de::from_str::<XmlCardDb>("<root></root>").is_err());
@@ -0,0 +1,111 @@
+use std::{collections::HashMap, path::Path};
+
+use crate::cards::{card_name, Card, CardDatabase};
+
+/// Named card lists registered by the server operator, which can be drafted
+/// without uploading a list.
+pub type Cubes = HashMap<String, Vec<Card>>;
+
+/// Read a cube from a file. Text files list a card name on each line, which
+/// are looked up in the card database. XML files are Cockatrice card
+/// databases, and every card in the database is part of the cube.
+async fn load_cube(path: &Path, carddb: &CardDatabase) -> Result<Vec<Card>, String> {
+    let raw = tokio::fs::read(path).await.map_err(|e| e.to_string())?;
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("xml") => crate::cards::cockatrice::decode_xml_cards(bytes::Bytes::from(raw))
+            .map(|decoded| decoded.cards.into_values().collect())
+            .map_err(|e| e.to_string()),
+        Some("txt") => {
+            let list = String::from_utf8(raw).map_err(|e| e.to_string())?;
+            list.lines()
+                .map(card_name)
+                .filter(|line| !line.is_empty())
+                .map(|line| {
+                    carddb
+                        .get(line)
+                        .cloned()
+                        .ok_or_else(|| format!("Card not found in database: {line}"))
+                })
+                .collect()
+        }
+        _ => Err("Unsupported file type.".to_string()),
+    }
+}
+
+/// Load every cube in the given directory, keyed by file name without the
+/// extension. Files which fail to load are skipped. If the directory doesn't
+/// exist there are no cubes.
+pub async fn load_cubes(dir: &Path, carddb: &CardDatabase) -> Cubes {
+    let mut cubes = Cubes::new();
+
+    let Ok(mut entries) = tokio::fs::read_dir(dir).await else {
+        tracing::debug!("No cube directory found at {}", dir.display());
+        return cubes;
+    };
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        let path = entry.path();
+        let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) else {
+            continue;
+        };
+
+        match load_cube(&path, carddb).await {
+            Ok(cards) => {
+                tracing::debug!("Loaded cube {name} with {} cards.", cards.len());
+                cubes.insert(name.to_string(), cards);
+            }
+            Err(e) => tracing::warn!("Failed to load cube from {}: {e}", path.display()),
+        }
+    }
+
+    cubes
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cards::{Card, CardDatabase, Rarity};
+
+    use super::load_cubes;
+
+    #[tokio::test]
+    async fn test_load_cubes() {
+        let dir = std::env::temp_dir().join(uuid::Uuid::new_v4().to_string());
+        std::fs::create_dir(&dir).unwrap();
+
+        let mut carddb = CardDatabase::new();
+        let card = Card::sample(Rarity::Common);
+        std::fs::write(dir.join("list.txt"), format!("{}\n\n", card.name())).unwrap();
+        std::fs::write(dir.join("missing.txt"), "Not a card\n").unwrap();
+        std::fs::write(dir.join("notes.md"), "Not a cube").unwrap();
+        std::fs::write(
+            dir.join("custom.xml"),
+            r#"
+<cockatrice_carddatabase version="3">
+  <cards>
+    <card>
+      <name>Custom Card</name>
+      <set rarity="Rare" picURL="https://example.com/card.jpg">CUS</set>
+      <manacost>1</manacost>
+      <cmc>1</cmc>
+      <type>Artifact</type>
+      <tablerow>1</tablerow>
+      <text>Draw a card.</text>
+    </card>
+  </cards>
+</cockatrice_carddatabase>
+            "#,
+        )
+        .unwrap();
+        carddb.add(card.clone());
+
+        let cubes = load_cubes(&dir, &carddb).await;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(cubes.len(), 2);
+        assert_eq!(cubes["list"].len(), 1);
+        assert_eq!(cubes["list"][0].name(), card.name());
+        assert_eq!(cubes["custom"][0].name(), "Custom Card");
+        assert!(load_cubes(&dir, &carddb).await.is_empty());
+    }
+}
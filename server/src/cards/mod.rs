@@ -1,9 +1,10 @@
 use std::collections::HashMap;
 
 pub mod cockatrice;
+pub mod cubes;
 pub mod scryfall;
 
-#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
 pub enum Rarity {
     Mythic,
     Rare,
@@ -13,31 +14,228 @@ pub enum Rarity {
     Bonus,
 }
 
-#[derive(Clone, Debug, serde::Serialize)]
+impl Rarity {
+    /// Relative value of this rarity, higher is rarer. Special and bonus cards
+    /// sit below commons as they aren't part of regular packs.
+    pub fn rank(self) -> usize {
+        match self {
+            Rarity::Mythic => 4,
+            Rarity::Rare => 3,
+            Rarity::Uncommon => 2,
+            Rarity::Common => 1,
+            Rarity::Special | Rarity::Bonus => 0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Deserialize, serde::Serialize)]
+pub enum Colour {
+    W,
+    U,
+    B,
+    R,
+    G,
+}
+
+impl Colour {
+    /// Every colour, in WUBRG order.
+    pub const ALL: [Colour; 5] = [Colour::W, Colour::U, Colour::B, Colour::R, Colour::G];
+
+    /// Name of the basic land which produces mana of this colour.
+    pub fn basic_land(self) -> &'static str {
+        match self {
+            Colour::W => "Plains",
+            Colour::U => "Island",
+            Colour::B => "Swamp",
+            Colour::R => "Mountain",
+            Colour::G => "Forest",
+        }
+    }
+}
+
+/// Every available image of a card, so that clients can choose a size to
+/// suit the display, e.g. small images in a grid.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct CardImages {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub png: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub large: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub normal: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub small: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_crop: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub art_crop: Option<String>,
+}
+
+/// Which image of a card to show by default, where there is a choice.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageQuality {
+    /// Large images, suited to showing on screen.
+    #[default]
+    Display,
+
+    /// Full resolution images, for printing proxies.
+    Print,
+
+    /// Small images, to save bandwidth.
+    Thumbnail,
+}
+
+impl CardImages {
+    fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Choose the image best suited to the quality, falling back to other
+    /// sizes, then crops, if it isn't available.
+    pub fn choose(&self, quality: ImageQuality) -> Option<&String> {
+        let order = match quality {
+            ImageQuality::Display => [
+                &self.large,
+                &self.png,
+                &self.normal,
+                &self.border_crop,
+                &self.small,
+                &self.art_crop,
+            ],
+            ImageQuality::Print => [
+                &self.png,
+                &self.large,
+                &self.normal,
+                &self.border_crop,
+                &self.small,
+                &self.art_crop,
+            ],
+            ImageQuality::Thumbnail => [
+                &self.small,
+                &self.normal,
+                &self.large,
+                &self.png,
+                &self.border_crop,
+                &self.art_crop,
+            ],
+        };
+        order.into_iter().flatten().next()
+    }
+}
+
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Card {
     name: String,
+
+    /// Image shown by default. Other sizes, where available, are in images.
     image: String,
+    #[serde(default, skip_serializing_if = "CardImages::is_empty")]
+    images: Box<CardImages>,
     set: String,
     pub rarity: Rarity,
+
+    /// Name shown to players for the card's rarity, for games with their own
+    /// rarity tiers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    rarity_label: Option<String>,
     text: String,
+    pub colours: Vec<Colour>,
+
+    /// Combat stats, for creatures. Kept as text as these may be variable,
+    /// e.g. "*" or "1+*".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    power: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    toughness: Option<String>,
+
+    /// Starting loyalty, for planeswalkers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    loyalty: Option<String>,
+
+    /// Whether the card was opened in a pack's foil slot.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub foil: bool,
 }
 
 impl Card {
-    pub fn new(name: String, image: String, set: String, text: String, rarity: Rarity) -> Self {
+    pub fn new(
+        name: String,
+        image: String,
+        set: String,
+        text: String,
+        rarity: Rarity,
+        colours: Vec<Colour>,
+    ) -> Self {
         Self {
             name,
             image,
+            images: Box::default(),
             set,
             rarity,
+            rarity_label: None,
             text,
+            colours,
+            power: None,
+            toughness: None,
+            loyalty: None,
+            foil: false,
         }
     }
 
-    #[cfg(test)]
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    pub fn set(&self) -> &str {
+        &self.set
+    }
+
+    pub fn image(&self) -> &str {
+        &self.image
+    }
+
+    /// Show the card's image of the given quality by default, if it has a
+    /// choice of images.
+    pub fn use_image_quality(&mut self, quality: ImageQuality) {
+        if let Some(image) = self.images.choose(quality) {
+            self.image = image.clone();
+        }
+    }
+
+    pub fn set_images(&mut self, images: CardImages) {
+        *self.images = images;
+    }
+
+    pub fn set_rarity_label(&mut self, label: String) {
+        self.rarity_label = Some(label);
+    }
+
+    /// Basic land of the colour, for players to build decks with. Basics
+    /// aren't drafted, so are special rather than part of any pack.
+    pub fn basic_land(colour: Colour) -> Self {
+        let name = colour.basic_land();
+        Self::new(
+            name.to_string(),
+            format!("https://api.scryfall.com/cards/named?exact={name}&format=image"),
+            String::new(),
+            String::new(),
+            Rarity::Special,
+            Vec::new(),
+        )
+    }
+
+    pub fn set_stats(
+        &mut self,
+        power: Option<String>,
+        toughness: Option<String>,
+        loyalty: Option<String>,
+    ) {
+        self.power = power;
+        self.toughness = toughness;
+        self.loyalty = loyalty;
+    }
+
     #[cfg(test)]
     pub fn sample(rarity: Rarity) -> Self {
         static ID: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(1);
@@ -46,13 +244,82 @@ impl Card {
         Self {
             name: format!("Card {id}"),
             image: format!("https://example.com/card-{id}-art.jpg"),
+            images: Box::default(),
             set: "TST".to_string(),
             rarity,
+            rarity_label: None,
             text: format!("Text for test card {id}."),
+            colours: Vec::new(),
+            power: None,
+            toughness: None,
+            loyalty: None,
+            foil: false,
         }
     }
 }
 
+/// Upgrade an http:// image URL to https://. The client is served over HTTPS,
+/// so browsers block insecure images as mixed content.
+pub fn secure_image_url(url: String) -> String {
+    match url.strip_prefix("http://") {
+        Some(rest) => format!("https://{rest}"),
+        None => url,
+    }
+}
+
+/// Whether this is a plausible set code, e.g. "2X2" or "PLST".
+fn is_set_code(s: &str) -> bool {
+    (2..=6).contains(&s.len()) && s.chars().all(|c| c.is_ascii_alphanumeric())
+}
+
+/// Whether this is a plausible collector number, e.g. "117", "12a" or "★".
+fn is_collector_number(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_alphanumeric() || "-★†".contains(c))
+}
+
+/// Name of the card in a card list line, stripping the set code and collector
+/// number added by deckbuilder exports, e.g. "Lightning Bolt (2X2) 117", and
+/// any foil or etched marker after them. Lines without a set code are left
+/// as is, so names containing parentheses are unaffected.
+pub fn card_name(line: &str) -> &str {
+    let line = line.trim();
+    let Some((name, rest)) = line.rsplit_once(" (") else {
+        return line;
+    };
+    let Some((set, suffix)) = rest.split_once(')') else {
+        return line;
+    };
+
+    let is_marker = |s: &str| s == "*F*" || s == "*E*";
+    let mut tokens = suffix.split_whitespace();
+    let number = tokens.next();
+    let marker = tokens.next();
+    if is_set_code(set)
+        && number.is_none_or(|n| is_collector_number(n) || is_marker(n))
+        && marker.is_none_or(is_marker)
+        && tokens.next().is_none()
+    {
+        name.trim_end()
+    } else {
+        line
+    }
+}
+
+/// Number of copies and name of the card in a card list line. Deckbuilder
+/// exports give a count before each card, e.g. "4 Lightning Bolt (2X2) 117"
+/// or "4x Lightning Bolt". Lines without a count are a single copy.
+pub fn card_entry(line: &str) -> (usize, &str) {
+    let line = line.trim();
+    if let Some((count, rest)) = line.split_once(' ')
+        && let Ok(count) = count.strip_suffix(['x', 'X']).unwrap_or(count).parse()
+        && count > 0
+    {
+        (count, card_name(rest))
+    } else {
+        (1, card_name(line))
+    }
+}
+
 pub struct CardDatabase {
     /// Map from lowercased card name to card.
     name_to_card: HashMap<String, Card>,
@@ -79,3 +346,115 @@ impl CardDatabase {
         self.name_to_card.len()
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::{card_entry, card_name, secure_image_url, Card, CardImages, ImageQuality, Rarity};
+
+    #[test]
+    fn test_card_name() {
+        // Plain names, including some with parentheses.
+        assert_eq!(card_name("Lightning Bolt"), "Lightning Bolt");
+        assert_eq!(card_name("  Lightning Bolt "), "Lightning Bolt");
+        assert_eq!(
+            card_name("B.F.M. (Big Furry Monster)"),
+            "B.F.M. (Big Furry Monster)"
+        );
+        assert_eq!(
+            card_name("Erase (Not the Urza's Legacy One)"),
+            "Erase (Not the Urza's Legacy One)"
+        );
+
+        // Arena and Moxfield exports.
+        assert_eq!(card_name("Lightning Bolt (2X2) 117"), "Lightning Bolt");
+        assert_eq!(card_name("Lightning Bolt (2X2)"), "Lightning Bolt");
+        assert_eq!(card_name("Lightning Bolt (PLST) 2X2-117"), "Lightning Bolt");
+        assert_eq!(card_name("Lightning Bolt (2X2) 117 *F*"), "Lightning Bolt");
+        assert_eq!(card_name("Lightning Bolt (SLD) 12a *E*"), "Lightning Bolt");
+        assert_eq!(card_name("Fire // Ice (MH2) 290"), "Fire // Ice");
+
+        // Anything else after the set code isn't a collector number.
+        assert_eq!(
+            card_name("Lightning Bolt (2X2) 117 extra words"),
+            "Lightning Bolt (2X2) 117 extra words"
+        );
+    }
+
+    #[test]
+    fn test_card_entry() {
+        assert_eq!(card_entry("Lightning Bolt"), (1, "Lightning Bolt"));
+        assert_eq!(
+            card_entry("1 Lightning Bolt (2X2) 117"),
+            (1, "Lightning Bolt")
+        );
+        assert_eq!(card_entry("4 Lightning Bolt"), (4, "Lightning Bolt"));
+        assert_eq!(
+            card_entry("2x Fire // Ice (MH2) 290 *F*"),
+            (2, "Fire // Ice")
+        );
+        assert_eq!(
+            card_entry("  3 Lightning Bolt (2X2) "),
+            (3, "Lightning Bolt")
+        );
+
+        // Names which begin with a number are read as counted, so are looked
+        // up as written if not found.
+        assert_eq!(card_entry("0 Lightning Bolt"), (1, "0 Lightning Bolt"));
+        assert_eq!(card_entry("1996 World Champion"), (1996, "World Champion"));
+    }
+
+    #[test]
+    fn test_secure_image_url() {
+        assert_eq!(
+            secure_image_url("http://mtg.design/i/vjre15.jpg".to_string()),
+            "https://mtg.design/i/vjre15.jpg"
+        );
+        assert_eq!(
+            secure_image_url("https://cards.scryfall.io/art.jpg".to_string()),
+            "https://cards.scryfall.io/art.jpg"
+        );
+    }
+
+    #[test]
+    fn test_choose_image() {
+        let url = |s: &str| Some(s.to_string());
+
+        let images = CardImages {
+            normal: url("normal"),
+            small: url("small"),
+            ..Default::default()
+        };
+        assert_eq!(images.choose(ImageQuality::Display).unwrap(), "normal");
+        assert_eq!(images.choose(ImageQuality::Print).unwrap(), "normal");
+        assert_eq!(images.choose(ImageQuality::Thumbnail).unwrap(), "small");
+
+        let images = CardImages {
+            png: url("png"),
+            large: url("large"),
+            art_crop: url("art_crop"),
+            ..Default::default()
+        };
+        assert_eq!(images.choose(ImageQuality::Display).unwrap(), "large");
+        assert_eq!(images.choose(ImageQuality::Print).unwrap(), "png");
+        assert_eq!(images.choose(ImageQuality::Thumbnail).unwrap(), "large");
+
+        assert!(CardImages::default()
+            .choose(ImageQuality::Display)
+            .is_none());
+    }
+
+    #[test]
+    fn test_serialise_stats() {
+        let mut card = Card::sample(Rarity::Common);
+        let json = serde_json::to_value(&card).unwrap();
+        assert!(json.get("power").is_none());
+        assert!(json.get("loyalty").is_none());
+        assert!(json.get("images").is_none());
+
+        card.set_stats(Some("0".into()), Some("1".into()), None);
+        let json = serde_json::to_value(&card).unwrap();
+        assert_eq!(json["power"], "0");
+        assert_eq!(json["toughness"], "1");
+        assert!(json.get("loyalty").is_none());
+    }
+}
@@ -2,8 +2,9 @@ use std::path::Path;
 
 use bytes::Buf;
 use serde::de::DeserializeOwned;
+use tokio::io::AsyncWriteExt;
 
-use crate::cards::{Card, Rarity};
+use crate::cards::{secure_image_url, Card, CardImages, Colour, ImageQuality, Rarity};
 
 async fn get_bytes(uri: &str) -> Result<bytes::Bytes, String> {
     reqwest::get(uri)
@@ -14,24 +15,123 @@ async fn get_bytes(uri: &str) -> Result<bytes::Bytes, String> {
         .map_err(|e| e.to_string())
 }
 
+/// Interval between progress updates while downloading, in percent.
+const PROGRESS_STEP: u64 = 5;
+
+/// Percentage of the download completed, if the total size is known.
+fn progress(downloaded: u64, total: Option<u64>) -> Option<u64> {
+    total
+        .filter(|&total| total > 0)
+        .map(|total| (downloaded * 100 / total).min(100))
+}
+
+/// Stream the file at the URI to the path, logging progress along the way.
+/// The file is written alongside the path first, so that an interrupted
+/// download isn't mistaken for a complete one.
+async fn download_file(uri: &str, path: &Path) -> Result<(), String> {
+    let mut resp = reqwest::get(uri)
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|e| e.to_string())?;
+    let total = resp.content_length();
+
+    let partial = path.with_extension("part");
+    let mut file = tokio::fs::File::create(&partial)
+        .await
+        .map_err(|e| e.to_string())?;
+    let mut downloaded = 0;
+    let mut reported = 0;
+    while let Some(chunk) = resp.chunk().await.map_err(|e| e.to_string())? {
+        file.write_all(&chunk).await.map_err(|e| e.to_string())?;
+        downloaded += chunk.len() as u64;
+        if let Some(percent) = progress(downloaded, total)
+            && percent >= reported + PROGRESS_STEP
+        {
+            reported = percent - percent % PROGRESS_STEP;
+            tracing::info!(
+                "Downloading Scryfall cards: {percent}% ({downloaded} of {} bytes).",
+                total.unwrap_or_default()
+            );
+        }
+    }
+    file.flush().await.map_err(|e| e.to_string())?;
+    if total.is_none() {
+        tracing::info!("Downloaded Scryfall cards: {downloaded} bytes.");
+    }
+
+    tokio::fs::rename(&partial, path)
+        .await
+        .map_err(|e| e.to_string())
+}
+
 fn decode_json<T: DeserializeOwned>(bytes: bytes::Bytes) -> Result<T, String> {
     serde_json::de::from_reader(bytes.reader()).map_err(|e| e.to_string())
 }
 
-async fn download_list(path: &Path) -> Result<(), String> {
-    #[derive(serde::Deserialize)]
-    struct BulkDataInfo {
-        download_uri: String,
+/// Type of the bulk data containing one card object for each card.
+const ORACLE_CARDS: &str = "oracle_cards";
+
+/// A Scryfall bulk data object, describing a downloadable file.
+#[derive(serde::Deserialize)]
+struct BulkDataInfo {
+    /// Type of bulk data, e.g. oracle_cards.
+    #[serde(rename = "type")]
+    ty: Option<String>,
+
+    /// API URI of this bulk data object.
+    uri: Option<String>,
+
+    /// URI of the file itself.
+    download_uri: Option<String>,
+}
+
+/// Response from the bulk data API. Requesting a type of bulk data returns a
+/// single object, while the index returns a list of every type.
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum BulkDataResponse {
+    List { data: Vec<BulkDataInfo> },
+    Single(BulkDataInfo),
+}
+
+/// Where to find the oracle cards file, according to a bulk data response.
+#[derive(Debug, PartialEq)]
+enum BulkDataUri {
+    /// URI of the file itself.
+    Download(String),
+
+    /// URI of the bulk data object, which should include the download URI.
+    Object(String),
+}
+
+fn parse_bulk_data(bytes: bytes::Bytes) -> Result<BulkDataUri, String> {
+    let info = match decode_json(bytes)? {
+        BulkDataResponse::Single(info) => info,
+        BulkDataResponse::List { data } => data
+            .into_iter()
+            .find(|info| info.ty.as_deref() == Some(ORACLE_CARDS))
+            .ok_or_else(|| "No oracle cards in bulk data list.".to_string())?,
+    };
+
+    match (info.download_uri, info.uri) {
+        (Some(uri), _) => Ok(BulkDataUri::Download(uri)),
+        (None, Some(uri)) => Ok(BulkDataUri::Object(uri)),
+        (None, None) => Err("No URI in bulk data response.".to_string()),
     }
+}
 
-    let info: BulkDataInfo =
-        decode_json(get_bytes("https://api.scryfall.com/bulk-data/oracle-cards").await?)?;
-    let raw = get_bytes(&info.download_uri).await?;
+async fn download_list(path: &Path) -> Result<(), String> {
+    let bytes = get_bytes("https://api.scryfall.com/bulk-data/oracle-cards").await?;
+    let download_uri = match parse_bulk_data(bytes)? {
+        BulkDataUri::Download(uri) => uri,
 
-    tokio::fs::write(path, raw)
-        .await
-        .map_err(|e| e.to_string())?;
-    Ok(())
+        // Follow the link to the full bulk data object once.
+        BulkDataUri::Object(uri) => match parse_bulk_data(get_bytes(&uri).await?)? {
+            BulkDataUri::Download(uri) => uri,
+            BulkDataUri::Object(_) => return Err("No download URI in bulk data.".to_string()),
+        },
+    };
+    download_file(&download_uri, path).await
 }
 
 #[derive(serde::Deserialize, Debug)]
@@ -45,23 +145,35 @@ struct ScryfallCardImages {
 }
 
 impl ScryfallCardImages {
-    fn choose(self) -> Option<String> {
-        if self.large.is_some() {
-            self.large
-        } else if self.png.is_some() {
-            self.png
-        } else if self.normal.is_some() {
-            self.normal
-        } else if self.border_crop.is_some() {
-            self.border_crop
-        } else if self.small.is_some() {
-            self.small
-        } else {
-            self.art_crop
+    fn into_images(self) -> CardImages {
+        CardImages {
+            png: self.png.map(secure_image_url),
+            large: self.large.map(secure_image_url),
+            normal: self.normal.map(secure_image_url),
+            small: self.small.map(secure_image_url),
+            border_crop: self.border_crop.map(secure_image_url),
+            art_crop: self.art_crop.map(secure_image_url),
         }
     }
 }
 
+/// One face of a double-faced or split card.
+#[derive(serde::Deserialize, Debug)]
+struct ScryfallCardFace {
+    /// Object containing image URIs. Only present for faces of double-faced
+    /// cards, as split cards share a single image.
+    image_uris: Option<ScryfallCardImages>,
+
+    oracle_text: Option<String>,
+
+    #[serde(default)]
+    colors: Vec<Colour>,
+
+    power: Option<String>,
+    toughness: Option<String>,
+    loyalty: Option<String>,
+}
+
 #[derive(serde::Deserialize, Debug)]
 struct ScryfallCard {
     /// Card name. Includes both faces (!).
@@ -78,12 +190,27 @@ struct ScryfallCard {
 
     /// Oracle text for the card.
     oracle_text: Option<String>,
+
+    /// Colours of the card. Absent for multi-faced cards.
+    #[serde(default)]
+    colors: Vec<Colour>,
+
+    /// Power and toughness, for creatures.
+    power: Option<String>,
+    toughness: Option<String>,
+
+    /// Starting loyalty, for planeswalkers.
+    loyalty: Option<String>,
+
+    /// Faces of multi-faced cards. These hold the images, text and stats
+    /// which are absent from the card itself.
+    card_faces: Option<Vec<ScryfallCardFace>>,
 }
 
 impl ScryfallCard {
     fn into_card(self) -> Option<Card> {
         let name = if self.name.contains("//") {
-            self.name.split("//").next().unwrap().to_string()
+            self.name.split("//").next().unwrap().trim_end().to_string()
         } else {
             self.name
         };
@@ -98,13 +225,39 @@ impl ScryfallCard {
             _ => return None,
         };
 
-        Some(Card::new(
+        // Fall back to the front face for anything missing from the card.
+        let mut front = self.card_faces.and_then(|faces| faces.into_iter().next());
+        let images = match self.image_uris {
+            Some(images) => images,
+            None => front.as_mut()?.image_uris.take()?,
+        };
+        let oracle_text = match self.oracle_text {
+            Some(text) => text,
+            None => front.as_mut()?.oracle_text.take()?,
+        };
+        let colors = match front.as_mut() {
+            Some(face) if self.colors.is_empty() => std::mem::take(&mut face.colors),
+            _ => self.colors,
+        };
+        let (power, toughness, loyalty) = match front {
+            Some(face) if self.power.is_none() && self.loyalty.is_none() => {
+                (face.power, face.toughness, face.loyalty)
+            }
+            _ => (self.power, self.toughness, self.loyalty),
+        };
+
+        let images = images.into_images();
+        let mut card = Card::new(
             name,
-            self.image_uris?.choose()?,
+            images.choose(ImageQuality::default())?.clone(),
             self.set,
-            self.oracle_text?,
+            oracle_text,
             rarity,
-        ))
+            colors,
+        );
+        card.set_images(images);
+        card.set_stats(power, toughness, loyalty);
+        Some(card)
     }
 }
 
@@ -131,3 +284,222 @@ pub async fn load_cards(data: &Path) -> Result<Vec<Card>, String> {
         .filter_map(ScryfallCard::into_card)
         .collect())
 }
+
+#[cfg(test)]
+mod test {
+    use super::{
+        decode_json, parse_bulk_data, progress, BulkDataUri, Colour, Rarity, ScryfallCard,
+    };
+
+    const DOWNLOAD_URI: &str =
+        "https://data.scryfall.io/oracle-cards/oracle-cards-20240501090136.json";
+
+    #[test]
+    fn test_parse_bulk_data() {
+        const SINGLE: &str = r#"{
+            "object": "bulk_data",
+            "id": "27bf3214-1271-490b-bdfe-c0be6c23d02e",
+            "type": "oracle_cards",
+            "updated_at": "2024-05-01T09:01:36.434+00:00",
+            "uri": "https://api.scryfall.com/bulk-data/27bf3214-1271-490b-bdfe-c0be6c23d02e",
+            "name": "Oracle Cards",
+            "description": "A JSON file containing one Scryfall card object for each Oracle ID on Scryfall.",
+            "size": 152594010,
+            "download_uri": "https://data.scryfall.io/oracle-cards/oracle-cards-20240501090136.json",
+            "content_type": "application/json",
+            "content_encoding": "gzip"
+        }"#;
+        assert_eq!(
+            parse_bulk_data(bytes::Bytes::from(SINGLE)),
+            Ok(BulkDataUri::Download(DOWNLOAD_URI.to_string()))
+        );
+
+        const LIST: &str = r#"{
+            "object": "list",
+            "has_more": false,
+            "data": [
+                {
+                    "object": "bulk_data",
+                    "type": "unique_artwork",
+                    "uri": "https://api.scryfall.com/bulk-data/6bbcf976",
+                    "download_uri": "https://data.scryfall.io/unique-artwork/unique-artwork.json"
+                },
+                {
+                    "object": "bulk_data",
+                    "type": "oracle_cards",
+                    "uri": "https://api.scryfall.com/bulk-data/27bf3214",
+                    "download_uri": "https://data.scryfall.io/oracle-cards/oracle-cards-20240501090136.json"
+                }
+            ]
+        }"#;
+        assert_eq!(
+            parse_bulk_data(bytes::Bytes::from(LIST)),
+            Ok(BulkDataUri::Download(DOWNLOAD_URI.to_string()))
+        );
+
+        const OBJECT: &str =
+            r#"{"object": "bulk_data", "uri": "https://api.scryfall.com/bulk-data/27bf3214"}"#;
+        assert_eq!(
+            parse_bulk_data(bytes::Bytes::from(OBJECT)),
+            Ok(BulkDataUri::Object(
+                "https://api.scryfall.com/bulk-data/27bf3214".to_string()
+            ))
+        );
+
+        assert!(parse_bulk_data(bytes::Bytes::from(r#"{"object": "error"}"#)).is_err());
+    }
+
+    #[test]
+    fn test_card_stats() {
+        const CARDS: &str = r#"[
+            {
+                "name": "Grizzly Bears",
+                "set": "lea",
+                "image_uris": {"large": "https://cards.scryfall.io/large/bears.jpg"},
+                "rarity": "common",
+                "oracle_text": "",
+                "colors": ["G"],
+                "power": "2",
+                "toughness": "2"
+            },
+            {
+                "name": "Jace Beleren",
+                "set": "lrw",
+                "image_uris": {"large": "https://cards.scryfall.io/large/jace.jpg"},
+                "rarity": "rare",
+                "oracle_text": "+2: Each player draws a card.",
+                "colors": ["U"],
+                "loyalty": "3"
+            }
+        ]"#;
+        let cards: Vec<ScryfallCard> = decode_json(bytes::Bytes::from(CARDS)).unwrap();
+        let cards: Vec<_> = cards
+            .into_iter()
+            .filter_map(ScryfallCard::into_card)
+            .collect();
+
+        assert_eq!(cards[0].power.as_deref(), Some("2"));
+        assert_eq!(cards[0].toughness.as_deref(), Some("2"));
+        assert_eq!(cards[0].loyalty.as_deref(), None);
+
+        assert_eq!(cards[1].power.as_deref(), None);
+        assert_eq!(cards[1].loyalty.as_deref(), Some("3"));
+    }
+
+    #[test]
+    fn test_mythic_rarity() {
+        const CARDS: &str = r#"[
+            {
+                "name": "Jace, the Mind Sculptor",
+                "set": "wwk",
+                "image_uris": {"large": "https://cards.scryfall.io/large/jace.jpg"},
+                "rarity": "mythic",
+                "oracle_text": "+2: Look at the top card of target player's library.",
+                "colors": ["U"],
+                "loyalty": "3"
+            }
+        ]"#;
+        let cards: Vec<ScryfallCard> = decode_json(bytes::Bytes::from(CARDS)).unwrap();
+        let card = cards.into_iter().next().unwrap().into_card().unwrap();
+        assert_eq!(card.rarity, Rarity::Mythic);
+    }
+
+    #[test]
+    fn test_card_faces() {
+        const CARDS: &str = r#"[
+            {
+                "name": "Delver of Secrets // Insectile Aberration",
+                "set": "isd",
+                "rarity": "common",
+                "card_faces": [
+                    {
+                        "name": "Delver of Secrets",
+                        "oracle_text": "At the beginning of your upkeep, look at the top card of your library.",
+                        "colors": ["U"],
+                        "power": "1",
+                        "toughness": "1",
+                        "image_uris": {"large": "http://cards.scryfall.io/large/front/delver.jpg"}
+                    },
+                    {
+                        "name": "Insectile Aberration",
+                        "oracle_text": "Flying",
+                        "colors": ["U"],
+                        "power": "3",
+                        "toughness": "2",
+                        "image_uris": {"large": "http://cards.scryfall.io/large/back/delver.jpg"}
+                    }
+                ]
+            },
+            {
+                "name": "Fire // Ice",
+                "set": "mh2",
+                "image_uris": {"large": "https://cards.scryfall.io/large/fire-ice.jpg"},
+                "rarity": "uncommon",
+                "colors": ["U", "R"],
+                "card_faces": [
+                    {"name": "Fire", "oracle_text": "Fire deals 2 damage divided as you choose."},
+                    {"name": "Ice", "oracle_text": "Tap target permanent."}
+                ]
+            }
+        ]"#;
+        let cards: Vec<ScryfallCard> = decode_json(bytes::Bytes::from(CARDS)).unwrap();
+        let cards: Vec<_> = cards
+            .into_iter()
+            .filter_map(ScryfallCard::into_card)
+            .collect();
+        assert_eq!(cards.len(), 2);
+
+        let delver = &cards[0];
+        assert_eq!(delver.name(), "Delver of Secrets");
+        assert_eq!(
+            delver.image(),
+            "https://cards.scryfall.io/large/front/delver.jpg"
+        );
+        assert_eq!(delver.colours, vec![Colour::U]);
+        assert_eq!(delver.power.as_deref(), Some("1"));
+
+        let fire = &cards[1];
+        assert_eq!(fire.name(), "Fire");
+        assert_eq!(fire.image(), "https://cards.scryfall.io/large/fire-ice.jpg");
+    }
+
+    #[test]
+    fn test_progress() {
+        assert_eq!(progress(0, Some(200)), Some(0));
+        assert_eq!(progress(50, Some(200)), Some(25));
+        assert_eq!(progress(200, Some(200)), Some(100));
+        assert_eq!(progress(250, Some(200)), Some(100));
+        assert_eq!(progress(50, Some(0)), None);
+        assert_eq!(progress(50, None), None);
+    }
+
+    #[test]
+    fn test_card_images() {
+        const CARDS: &str = r#"[{
+            "name": "Grizzly Bears",
+            "set": "lea",
+            "image_uris": {
+                "small": "https://cards.scryfall.io/small/bears.jpg",
+                "normal": "https://cards.scryfall.io/normal/bears.jpg",
+                "large": "http://cards.scryfall.io/large/bears.jpg",
+                "art_crop": "https://cards.scryfall.io/art_crop/bears.jpg"
+            },
+            "rarity": "common",
+            "oracle_text": ""
+        }]"#;
+        let cards: Vec<ScryfallCard> = decode_json(bytes::Bytes::from(CARDS)).unwrap();
+        let card = cards.into_iter().next().unwrap().into_card().unwrap();
+        let json = serde_json::to_value(&card).unwrap();
+
+        assert_eq!(json["image"], "https://cards.scryfall.io/large/bears.jpg");
+        assert_eq!(
+            json["images"],
+            serde_json::json!({
+                "large": "https://cards.scryfall.io/large/bears.jpg",
+                "normal": "https://cards.scryfall.io/normal/bears.jpg",
+                "small": "https://cards.scryfall.io/small/bears.jpg",
+                "art_crop": "https://cards.scryfall.io/art_crop/bears.jpg"
+            })
+        );
+    }
+}
@@ -1,60 +1,200 @@
 use std::collections::{HashMap, VecDeque};
 
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use uuid::Uuid;
 
-use crate::{cards::Card, err, Res};
+use crate::{
+    cards::{Card, Rarity},
+    err,
+    error::DraftError,
+    Res,
+};
 
-use super::packs::Pack;
+use super::{packs::Pack, AutoPick, PassDirection, PassModel};
 
-#[derive(Clone, Copy)]
-enum PassDirection {
-    Left,
-    Right,
+/// A pack as it is passed around the table. The ID is unique within the draft,
+/// so that a pick can be checked against the pack the player was looking at.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct DraftPack {
+    pub id: usize,
+    pub cards: Pack,
+
+    /// Seat which opened this pack.
+    pub origin: Uuid,
 }
 
-impl PassDirection {
-    fn reverse(self) -> Self {
-        match self {
-            PassDirection::Left => PassDirection::Right,
-            PassDirection::Right => PassDirection::Left,
-        }
-    }
+pub type NewPacks = Vec<(Uuid, DraftPack)>;
+
+/// A pack as it was when a seat opened it, kept for auditing the draft.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct OpenedPack {
+    pub round: usize,
+    pub seat: Uuid,
+    pub pack: DraftPack,
 }
 
-pub type NewPacks = Vec<(Uuid, Pack)>;
+/// Number of cards of each rarity in each part of the draft.
+#[derive(Debug, Default, serde::Serialize)]
+pub struct RarityCounts {
+    /// In packs which haven't been opened yet.
+    pub unopened: HashMap<Rarity, usize>,
+
+    /// In packs being passed around the table.
+    pub in_packs: HashMap<Rarity, usize>,
 
+    /// Already picked by players.
+    pub picked: HashMap<Rarity, usize>,
+}
+
+fn count_rarities<'a, I: Iterator<Item = &'a Card>>(cards: I) -> HashMap<Rarity, usize> {
+    let mut counts = HashMap::new();
+    for card in cards {
+        *counts.entry(card.rarity).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// State of a draft in progress. This can be serialised and restored at any
+/// point, to resume the draft exactly where it left off.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
 pub struct Draft {
     players: Vec<Uuid>,
     pools: HashMap<Uuid, Vec<Card>>,
     direction: PassDirection,
+    pass_model: PassModel,
+
+    /// Direction packs are passed in the first round.
+    #[serde(default)]
+    first_direction: PassDirection,
+
+    /// Seed for the random passing direction, combined with the round number
+    /// so that each round's direction doesn't depend on earlier rounds.
+    seed: u64,
     rounds: usize,
     current_round: usize,
     generated_packs: Vec<Pack>,
-    packs_being_drafted: HashMap<Uuid, VecDeque<Pack>>,
+    packs_being_drafted: HashMap<Uuid, VecDeque<DraftPack>>,
+
+    /// Every pack opened so far, in the order they were opened.
+    opened_packs: Vec<OpenedPack>,
+
+    /// Size of the packs opened this round, and the number of picks made by
+    /// each player this round. Used to share picks out evenly when packs are
+    /// passed to random seats.
+    #[serde(default)]
+    round_pack_size: usize,
+    #[serde(default)]
+    round_picks: HashMap<Uuid, usize>,
+
+    /// Every pick made by each player, in order.
+    #[serde(default)]
+    pick_log: HashMap<Uuid, Vec<PickRecord>>,
+
+    /// Where each player passed the pack they last picked from, so that the
+    /// pick can be undone until the pack is picked from again.
+    #[serde(default)]
+    last_passed: HashMap<Uuid, PassedPack>,
+
+    /// In a rochester draft, packs are opened one at a time, face up, and
+    /// players take turns to pick from them, snaking around the table from
+    /// the seat which opened the pack. Picks made from the open pack so far.
+    #[serde(default)]
+    rochester: bool,
+    #[serde(default)]
+    rochester_picks: usize,
+}
+
+/// A card picked by a player, with the rest of the pack it was picked from.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PickRecord {
+    picked: Card,
+    alternatives: Vec<Card>,
+
+    /// Round of the draft and number of the pick in the round, from one.
+    pack_number: usize,
+    pick_number: usize,
+}
+
+/// A pack passed on after a pick, with the index the card was picked from
+/// and the number of cards left in it.
+#[derive(Clone, Copy, Debug, serde::Deserialize, serde::Serialize)]
+struct PassedPack {
+    pack: usize,
+    index: usize,
+    remaining: usize,
+    to: Uuid,
 }
 
 impl Draft {
-    pub fn new(players: Vec<Uuid>, rounds: usize, packs: Vec<Pack>) -> Self {
+    /// Create a draft for the given players. The packs should be in the order
+    /// they are to be opened, one for each player in each round. Packs in
+    /// different rounds may differ in size. The seed is used for random
+    /// passing directions.
+    pub fn new(
+        players: Vec<Uuid>,
+        rounds: usize,
+        mut packs: Vec<Pack>,
+        pass_model: PassModel,
+        first_direction: PassDirection,
+        seed: u64,
+    ) -> Self {
         debug_assert!(packs.len() == players.len() * rounds);
 
+        // Packs are popped off the end as each round starts.
+        packs.reverse();
+
         Self {
             players,
             pools: HashMap::new(),
 
-            // Reversed to left at beginning of first round.
-            direction: PassDirection::Right,
+            // Set at the beginning of each round.
+            direction: first_direction,
+            pass_model,
+            first_direction,
+            seed,
             current_round: 0,
             rounds,
             generated_packs: packs,
             packs_being_drafted: HashMap::new(),
+            opened_packs: Vec::new(),
+            round_pack_size: 0,
+            round_picks: HashMap::new(),
+            pick_log: HashMap::new(),
+            last_passed: HashMap::new(),
+            rochester: false,
+            rochester_picks: 0,
+        }
+    }
+
+    /// Make this a rochester draft, with a single pack open at a time which
+    /// players pick from in turn.
+    pub fn rochester(mut self) -> Self {
+        self.rochester = true;
+        self
+    }
+
+    /// Whether this is a rochester draft.
+    pub fn is_rochester(&self) -> bool {
+        self.rochester
+    }
+
+    /// Player whose turn it is to pick from the open pack in a rochester
+    /// draft. None for other drafts, in which everyone picks at once.
+    pub fn rochester_turn(&self) -> Option<Uuid> {
+        if !self.rochester {
+            return None;
         }
+        self.players
+            .iter()
+            .find(|player| self.queue_size(**player) > 0)
+            .copied()
     }
 
     /// Start the draft, choosing a pack for each player. Returns a vector of
     /// (player, pack) pairs for each player to make their pick from. This may
     /// only be called once to begin the draft. Future rounds will begin when
     /// the previous round finishes.
-    pub fn begin(&mut self) -> Vec<(Uuid, Vec<Card>)> {
+    pub fn begin(&mut self) -> NewPacks {
         debug_assert!(self.current_round == 0);
 
         self.start_round()
@@ -68,14 +208,44 @@ impl Draft {
     /// one will be produced for the player next in the draft after the picking
     /// player if the pack the picking player is passing is not empty.
     pub fn handle_pick(&mut self, player: Uuid, index: usize) -> Res<(Card, NewPacks)> {
+        if self.rochester && self.players.contains(&player) && self.rochester_turn() != Some(player)
+        {
+            return err("It's not your turn to pick.");
+        }
+
         let (card, pack) = self.pick_card(player, index)?;
         self.pool_for(player).push(card.clone());
+        let picks = self.round_picks.entry(player).or_default();
+        *picks += 1;
+        let record = PickRecord {
+            picked: card.clone(),
+            alternatives: pack.cards.clone(),
+            pack_number: self.current_round,
+            pick_number: *picks,
+        };
+        self.pick_log.entry(player).or_default().push(record);
+        if self.rochester {
+            return Ok((card, self.pass_rochester_pack(pack)));
+        }
 
         let mut newly_available_packs = Vec::new();
-        let next = self.next_player(player);
-        if !pack.is_empty()
+        let next = match self.pass_model {
+            PassModel::RandomSeat => self.random_seat(player, &pack),
+            _ => self.next_player(player),
+        };
+        self.last_passed.remove(&player);
+        if !pack.cards.is_empty()
             && let Some(next_player) = next
         {
+            self.last_passed.insert(
+                player,
+                PassedPack {
+                    pack: pack.id,
+                    index,
+                    remaining: pack.cards.len(),
+                    to: next_player,
+                },
+            );
             self.stack_for(next_player).push_back(pack);
             if let Some(next_player_stack) = self.packs_being_drafted.get(&next_player)
                 && next_player_stack.len() == 1
@@ -94,21 +264,86 @@ impl Draft {
         }
 
         // If this was the last pick in the round, begin the next.
-        if newly_available_packs.is_empty() && self.round_finished() && !self.draft_complete() {
+        if newly_available_packs.is_empty()
+            && self.round_finished()
+            && !self.draft_complete()
+            && self.generated_packs.len() >= self.players.len()
+        {
             Ok((card, self.start_round()))
         } else {
             Ok((card, newly_available_packs))
         }
     }
 
+    /// Player the pack from this player's last pick was passed to, while the
+    /// pick can still be undone.
+    pub fn last_passed_to(&self, player: Uuid) -> Option<Uuid> {
+        self.last_passed.get(&player).map(|passed| passed.to)
+    }
+
+    /// Undo the player's last pick, taking back the pack they passed. This is
+    /// only possible until the next player picks from the pack, and before
+    /// the round ends. On success, the card is returned to the pack, which
+    /// becomes the player's current pack again. Returns the card and the
+    /// player the pack was taken back from.
+    pub fn undo_pick(&mut self, player: Uuid) -> Res<(Card, Uuid)> {
+        if self.rochester {
+            return err("Picks can't be undone in a rochester draft.");
+        }
+
+        let Some(passed) = self.last_passed.get(&player).copied() else {
+            return err("No pick to undo.");
+        };
+        let Some(stack) = self.packs_being_drafted.get_mut(&passed.to) else {
+            return Err(DraftError::PlayerNotInDraft);
+        };
+        let Some(position) = stack
+            .iter()
+            .position(|pack| pack.id == passed.pack && pack.cards.len() == passed.remaining)
+        else {
+            return err("Too late to undo, the pack has been picked from.");
+        };
+
+        self.last_passed.remove(&player);
+        let mut pack = stack.remove(position).unwrap();
+        let Some(card) = self.pool_for(player).pop() else {
+            return Err(DraftError::PlayerNotInDraft);
+        };
+        pack.cards
+            .insert(passed.index.min(pack.cards.len()), card.clone());
+        self.stack_for(player).push_front(pack);
+        if let Some(picks) = self.round_picks.get_mut(&player) {
+            *picks = picks.saturating_sub(1);
+        }
+        if let Some(log) = self.pick_log.get_mut(&player) {
+            log.pop();
+        }
+        Ok((card, passed.to))
+    }
+
     /// Get the pack currently being drafted by this player, if any.
-    pub fn current_pack(&self, player: Uuid) -> Option<Vec<Card>> {
+    pub fn current_pack(&self, player: Uuid) -> Option<DraftPack> {
         self.packs_being_drafted
             .get(&player)
             .and_then(|stack| stack.front())
             .cloned()
     }
 
+    /// Get the pack queued behind this player's current pack, if any. This
+    /// can be looked at, but not picked from until it reaches the front.
+    pub fn next_pack(&self, player: Uuid) -> Option<DraftPack> {
+        self.packs_being_drafted
+            .get(&player)
+            .and_then(|stack| stack.get(1))
+            .cloned()
+    }
+
+    /// Number of the current round, starting from one. Zero before the draft
+    /// begins.
+    pub fn round(&self) -> usize {
+        self.current_round
+    }
+
     /// Get the pool of cards drafted by this player, if any.
     pub fn drafted_cards(&self, player: Uuid) -> Option<&Vec<Card>> {
         self.pools.get(&player)
@@ -120,11 +355,43 @@ impl Draft {
         self.current_round == self.rounds && self.round_finished()
     }
 
+    /// Check if this draft has stalled, with no player holding a pack but
+    /// the draft not complete. This should never happen, but would otherwise
+    /// leave the draft hanging.
+    pub fn stalled(&self) -> bool {
+        self.round_finished() && !self.draft_complete()
+    }
+
+    /// Every pack opened so far, as it was when opened, in order.
+    pub fn opened_packs(&self) -> &[OpenedPack] {
+        &self.opened_packs
+    }
+
+    /// Every pick made by each player, in order.
+    pub fn pick_log(&self) -> &HashMap<Uuid, Vec<PickRecord>> {
+        &self.pick_log
+    }
+
     /// Map from player ID to pool of picked cards.
     pub fn pools(&self) -> &HashMap<Uuid, Vec<Card>> {
         &self.pools
     }
 
+    /// Count the cards of each rarity yet to be drafted, either in unopened
+    /// packs or packs being passed, and those already picked.
+    pub fn remaining_by_rarity(&self) -> RarityCounts {
+        RarityCounts {
+            unopened: count_rarities(self.generated_packs.iter().flatten()),
+            in_packs: count_rarities(
+                self.packs_being_drafted
+                    .values()
+                    .flatten()
+                    .flat_map(|pack| pack.cards.iter()),
+            ),
+            picked: count_rarities(self.pools.values().flatten()),
+        }
+    }
+
     /// Get the number of queued of packs for this player.
     pub fn queue_size(&self, player: Uuid) -> usize {
         self.packs_being_drafted
@@ -155,6 +422,112 @@ impl Draft {
         }
     }
 
+    /// Choose a seat at random to pass the pack to. Seats are only chosen if
+    /// they still need picks this round, counting one for each pack already
+    /// waiting for them, so that every seat makes the same number of picks.
+    /// Other seats are preferred, but the pack stays with the player if no one
+    /// else needs it. The choice is seeded by the pack, so is reproducible.
+    fn random_seat(&self, player: Uuid, pack: &DraftPack) -> Option<Uuid> {
+        let needs_picks = |seat: &Uuid| {
+            self.round_picks.get(seat).copied().unwrap_or_default() + self.queue_size(*seat)
+                < self.round_pack_size
+        };
+        let mut candidates: Vec<Uuid> = self
+            .players
+            .iter()
+            .filter(|&&seat| seat != player && needs_picks(&seat))
+            .copied()
+            .collect();
+        if candidates.is_empty() {
+            if needs_picks(&player) {
+                return Some(player);
+            }
+            // Packs differ in size, so picks can't be shared out evenly.
+            candidates = self.players.clone();
+        }
+
+        candidates.choose(&mut self.pack_rng(pack)).copied()
+    }
+
+    /// Random number generator for a choice made about the pack, seeded by
+    /// the draft's seed and the pack, so that seeded drafts are reproducible.
+    pub fn pack_rng(&self, pack: &DraftPack) -> StdRng {
+        StdRng::seed_from_u64(self.seed ^ ((pack.id as u64) << 32) ^ pack.cards.len() as u64)
+    }
+
+    /// Seat whose turn it is to pick from the open pack in a rochester draft.
+    /// Turns start from the seat which opened the pack and go around the
+    /// table in the pass direction, with the last seat picking twice before
+    /// turns go back the other way.
+    fn rochester_seat(&self, pack: &DraftPack) -> Option<Uuid> {
+        let players = self.players.len();
+        let opener = self.players.iter().position(|p| *p == pack.origin)?;
+        let step = self.rochester_picks % (2 * players);
+        let offset = if step < players {
+            step
+        } else {
+            2 * players - 1 - step
+        };
+        let index = match self.direction {
+            PassDirection::Left => (opener + players - offset) % players,
+            PassDirection::Right => (opener + offset) % players,
+        };
+        self.players.get(index).copied()
+    }
+
+    /// Pass the open pack to the next seat in a rochester draft, or once it's
+    /// empty, open the next pack of the round, or begin the next round.
+    fn pass_rochester_pack(&mut self, pack: DraftPack) -> NewPacks {
+        self.rochester_picks += 1;
+        if !pack.cards.is_empty()
+            && let Some(next) = self.rochester_seat(&pack)
+        {
+            self.stack_for(next).push_back(pack.clone());
+            return vec![(next, pack)];
+        }
+
+        if let Some(opened) = self.open_rochester_pack() {
+            vec![opened]
+        } else if !self.draft_complete() && self.generated_packs.len() >= self.players.len() {
+            self.start_round()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Open the next pack of the round in a rochester draft, if any are left.
+    /// Each seat opens a pack in turn.
+    fn open_rochester_pack(&mut self) -> Option<(Uuid, DraftPack)> {
+        let opened = self
+            .opened_packs
+            .iter()
+            .filter(|opened| opened.round == self.current_round)
+            .count();
+        let player = *self.players.get(opened)?;
+        let cards = self.generated_packs.pop()?;
+        self.rochester_picks = 0;
+        let pack = self.open_pack(player, cards);
+        Some((player, pack))
+    }
+
+    /// Give the player a newly opened pack, recording it as opened.
+    fn open_pack(&mut self, player: Uuid, cards: Pack) -> DraftPack {
+        // Each pack has a distinct index in the generated packs.
+        let id = self.generated_packs.len();
+        let pack = DraftPack {
+            id,
+            cards,
+            origin: player,
+        };
+        self.opened_packs.push(OpenedPack {
+            round: self.current_round,
+            seat: player,
+            pack: pack.clone(),
+        });
+        self.stack_for(player).push_back(pack.clone());
+        pack
+    }
+
     /// Get a mutable reference to the pool of picked cards for the specified
     /// player, creating it if necessary.
     fn pool_for(&mut self, player: Uuid) -> &mut Vec<Card> {
@@ -165,7 +538,7 @@ impl Draft {
 
     /// Get a mutable reference to the stack of packs waiting for the specified
     /// player to draft, creating it if necessary.
-    fn stack_for(&mut self, player: Uuid) -> &mut VecDeque<Vec<Card>> {
+    fn stack_for(&mut self, player: Uuid) -> &mut VecDeque<DraftPack> {
         debug_assert!(self.players.contains(&player));
 
         self.packs_being_drafted.entry(player).or_default()
@@ -182,27 +555,56 @@ impl Draft {
     /// Begin a new round of the draft. Handles reversing the draft direction,
     /// assigning the new pack from the pool, etc. Returns a vector of pairs of
     /// player ID and new pack for that player.
-    fn start_round(&mut self) -> Vec<(Uuid, Vec<Card>)> {
+    fn start_round(&mut self) -> NewPacks {
         debug_assert!(self.round_finished());
         debug_assert!(!self.draft_complete());
         debug_assert!(self.current_round < self.rounds);
-        debug_assert!(self.generated_packs.len() >= self.players.len());
 
         self.current_round += 1;
-        self.direction = self.direction.reverse();
+        self.direction = match self.pass_model {
+            PassModel::Snake if self.current_round % 2 == 1 => self.first_direction,
+            PassModel::Snake => self.first_direction.reverse(),
+            PassModel::Linear => self.first_direction,
+            PassModel::Random
+                if StdRng::seed_from_u64(self.seed.wrapping_add(self.current_round as u64))
+                    .gen_bool(0.5) =>
+            {
+                PassDirection::Left
+            }
+            PassModel::Random => PassDirection::Right,
+
+            // Packs are passed to random seats, rather than in a direction.
+            PassModel::RandomSeat => self.first_direction,
+        };
+        self.round_picks.clear();
+        self.last_passed.clear();
+        self.round_pack_size = self
+            .generated_packs
+            .iter()
+            .rev()
+            .take(self.players.len())
+            .map(Vec::len)
+            .max()
+            .unwrap_or_default();
+
+        // In a rochester draft, packs are opened one at a time.
+        if self.rochester {
+            return self.open_rochester_pack().into_iter().collect();
+        }
 
         // Give each player a pack. Clone required so that self can be mutated
         // in the loop.
         for player in self.players.clone().into_iter() {
-            let pack = self.generated_packs.pop().unwrap();
-            self.stack_for(player).push_back(pack);
+            let Some(cards) = self.generated_packs.pop() else {
+                break;
+            };
+            self.open_pack(player, cards);
         }
         // Return a collection mapping each player to the pack they need to pick
-        // from. This unwrap is ok as we just added a pack to each players
-        // pack stack
+        // from.
         self.packs_being_drafted
             .iter()
-            .map(|(player, stack)| (*player, stack.front().cloned().unwrap()))
+            .filter_map(|(player, stack)| Some((*player, stack.front().cloned()?)))
             .collect()
     }
 
@@ -210,35 +612,426 @@ impl Draft {
     /// current pack. On success returns the picked card and the pack (now
     /// removed from the players pack stack). On failure (if the player has no
     /// active pack or the index is invalid) returns None.
-    fn pick_card(&mut self, player: Uuid, index: usize) -> Res<(Card, Pack)> {
+    fn pick_card(&mut self, player: Uuid, index: usize) -> Res<(Card, DraftPack)> {
         let Some(pack_stack) = self.packs_being_drafted.get_mut(&player) else {
-            return err("Player not in draft.");
+            return Err(DraftError::PlayerNotInDraft);
         };
         let Some(current_pack) = pack_stack.front_mut() else {
-            return err("No current pack.");
+            return Err(DraftError::NoCurrentPack);
         };
-        if index < current_pack.len() {
-            Ok((current_pack.remove(index), pack_stack.pop_front().unwrap()))
+        if index < current_pack.cards.len() {
+            Ok((
+                current_pack.cards.remove(index),
+                pack_stack.pop_front().unwrap(),
+            ))
         } else {
-            err("Invalid pick index.")
+            Err(DraftError::InvalidPickIndex)
+        }
+    }
+}
+
+/// Number of face-down piles in a Winston draft.
+pub const WINSTON_PILES: usize = 3;
+
+/// Choice made by the active player in a Winston draft, for the pile they are
+/// looking at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum WinstonAction {
+    /// Take every card in the pile, ending the turn.
+    Take,
+
+    /// Add a card from the deck to the pile and look at the next. Passing the
+    /// last pile takes the top card of the deck instead.
+    Pass,
+}
+
+/// State of a Winston draft visible to the whole table.
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+pub struct WinstonState {
+    /// Player whose turn it is, if the draft isn't complete.
+    pub active: Option<Uuid>,
+
+    /// Index of the pile the active player is looking at.
+    pub pile: usize,
+
+    pub pile_sizes: Vec<usize>,
+    pub deck_size: usize,
+}
+
+/// State of a Winston draft, in which players take turns to take or pass
+/// face-down piles fed from a shared deck, until every card is taken.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct WinstonDraft {
+    players: Vec<Uuid>,
+    pools: HashMap<Uuid, Vec<Card>>,
+
+    /// Cards yet to be added to a pile. Cards are drawn from the end.
+    deck: Vec<Card>,
+    piles: Vec<Vec<Card>>,
+
+    /// Index of the active player, and of the pile they are looking at.
+    active: usize,
+    pile: usize,
+}
+
+impl WinstonDraft {
+    /// Create a draft for the given players from the cards in the deck,
+    /// shuffled with the seed. A card from the deck starts each pile.
+    pub fn new(players: Vec<Uuid>, mut deck: Vec<Card>, seed: u64) -> Self {
+        deck.shuffle(&mut StdRng::seed_from_u64(seed));
+        let piles = (0..WINSTON_PILES)
+            .map(|_| deck.pop().into_iter().collect())
+            .collect();
+
+        Self {
+            pools: players.iter().map(|&player| (player, Vec::new())).collect(),
+            players,
+            deck,
+            piles,
+            active: 0,
+            pile: 0,
+        }
+    }
+
+    /// Player whose turn it is, if the draft isn't complete.
+    pub fn active_player(&self) -> Option<Uuid> {
+        if self.draft_complete() {
+            None
+        } else {
+            self.players.get(self.active).copied()
+        }
+    }
+
+    /// Cards in the pile the player is looking at, if it's their turn.
+    pub fn current_pile(&self, player: Uuid) -> Option<&[Card]> {
+        (self.active_player() == Some(player)).then(|| self.piles[self.pile].as_slice())
+    }
+
+    pub fn state(&self) -> WinstonState {
+        WinstonState {
+            active: self.active_player(),
+            pile: self.pile,
+            pile_sizes: self.piles.iter().map(Vec::len).collect(),
+            deck_size: self.deck.len(),
+        }
+    }
+
+    /// Take or pass the pile the player is looking at. Returns the cards the
+    /// player took, which are empty if they passed to the next pile. The last
+    /// pile can't be passed once the deck is empty, so that the draft always
+    /// ends.
+    pub fn handle_winston_action(&mut self, player: Uuid, action: WinstonAction) -> Res<Vec<Card>> {
+        if !self.players.contains(&player) {
+            return Err(DraftError::PlayerNotInDraft);
+        } else if self.active_player() != Some(player) {
+            return Err(DraftError::NoCurrentPack);
+        }
+
+        let taken = match action {
+            WinstonAction::Take => {
+                let taken = std::mem::take(&mut self.piles[self.pile]);
+                self.piles[self.pile].extend(self.deck.pop());
+                taken
+            }
+            WinstonAction::Pass => {
+                let next = self.next_pile(self.pile + 1);
+                if next.is_none() && self.deck.is_empty() {
+                    return err("The last pile must be taken once the deck is empty.");
+                }
+
+                self.piles[self.pile].extend(self.deck.pop());
+                if let Some(next) = next {
+                    self.pile = next;
+                    return Ok(Vec::new());
+                }
+                self.deck.pop().into_iter().collect()
+            }
+        };
+
+        self.pools
+            .entry(player)
+            .or_default()
+            .extend(taken.iter().cloned());
+        self.active = (self.active + 1) % self.players.len();
+        self.pile = self.next_pile(0).unwrap_or(0);
+        Ok(taken)
+    }
+
+    /// First pile from the given index which has cards in it.
+    fn next_pile(&self, from: usize) -> Option<usize> {
+        (from..WINSTON_PILES).find(|&i| !self.piles[i].is_empty())
+    }
+
+    /// Get the pool of cards drafted by this player, if any.
+    pub fn drafted_cards(&self, player: Uuid) -> Option<&Vec<Card>> {
+        self.pools.get(&player)
+    }
+
+    /// Check if this draft is completed, with every card taken.
+    pub fn draft_complete(&self) -> bool {
+        self.deck.is_empty() && self.piles.iter().all(Vec::is_empty)
+    }
+
+    /// Map from player ID to pool of taken cards.
+    pub fn pools(&self) -> &HashMap<Uuid, Vec<Card>> {
+        &self.pools
+    }
+}
+
+/// Number of cards on each side of the grid in a grid draft.
+pub const GRID_SIZE: usize = 3;
+
+/// Line of the grid taken by the active player in a grid draft, by index from
+/// the top or left.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+pub enum GridSelection {
+    Row(usize),
+    Column(usize),
+}
+
+impl GridSelection {
+    /// Indices of the grid slots in the line, if it is in the grid.
+    fn slots(self) -> Option<Vec<usize>> {
+        match self {
+            GridSelection::Row(row) if row < GRID_SIZE => {
+                Some((0..GRID_SIZE).map(|col| row * GRID_SIZE + col).collect())
+            }
+            GridSelection::Column(col) if col < GRID_SIZE => {
+                Some((0..GRID_SIZE).map(|row| row * GRID_SIZE + col).collect())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// State of a grid draft, in which cards from a shared deck are dealt into a
+/// grid and players take turns to take a row or column of it. Once each player
+/// has taken a line, the rest of the grid is discarded and a new grid dealt,
+/// with the next player taking the first line.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct GridDraft {
+    players: Vec<Uuid>,
+    pools: HashMap<Uuid, Vec<Card>>,
+
+    /// Cards yet to be dealt. Cards are drawn from the end.
+    deck: Vec<Card>,
+
+    /// Slots of the grid, row by row. Slots are empty once taken, or if the
+    /// deck ran out while dealing.
+    grid: Vec<Option<Card>>,
+
+    /// Index of the active player, of the player who took the first line of
+    /// the current grid, and the number of lines taken from it.
+    active: usize,
+    first: usize,
+    taken: usize,
+}
+
+impl GridDraft {
+    /// Create a draft for the given players from the cards in the deck,
+    /// shuffled with the seed, and deal the first grid.
+    pub fn new(players: Vec<Uuid>, mut deck: Vec<Card>, seed: u64) -> Self {
+        deck.shuffle(&mut StdRng::seed_from_u64(seed));
+        let mut draft = Self {
+            pools: players.iter().map(|&player| (player, Vec::new())).collect(),
+            players,
+            deck,
+            grid: Vec::new(),
+            active: 0,
+            first: 0,
+            taken: 0,
+        };
+        draft.deal();
+        draft
+    }
+
+    /// Replace the grid with cards from the deck.
+    fn deal(&mut self) {
+        self.grid = (0..GRID_SIZE * GRID_SIZE)
+            .map(|_| self.deck.pop())
+            .collect();
+        self.taken = 0;
+    }
+
+    /// Player whose turn it is, if the draft isn't complete.
+    pub fn active_player(&self) -> Option<Uuid> {
+        if self.draft_complete() {
+            None
+        } else {
+            self.players.get(self.active).copied()
+        }
+    }
+
+    /// Slots of the current grid, row by row.
+    pub fn grid(&self) -> &[Option<Card>] {
+        &self.grid
+    }
+
+    /// Take the row or column of the grid for the player, if it's their turn.
+    /// Returns the cards taken. Lines with no cards left can't be taken.
+    pub fn handle_grid_pick(&mut self, player: Uuid, selection: GridSelection) -> Res<Vec<Card>> {
+        if !self.players.contains(&player) {
+            return Err(DraftError::PlayerNotInDraft);
+        } else if self.active_player() != Some(player) {
+            return Err(DraftError::NoCurrentPack);
+        }
+
+        let Some(slots) = selection.slots() else {
+            return err("No such row or column in the grid.");
+        };
+        let taken: Vec<Card> = slots
+            .into_iter()
+            .filter_map(|slot| self.grid[slot].take())
+            .collect();
+        if taken.is_empty() {
+            return err("That row or column has no cards left.");
+        }
+
+        self.pools
+            .entry(player)
+            .or_default()
+            .extend(taken.iter().cloned());
+        self.taken += 1;
+        if self.taken == self.players.len() || self.grid.iter().all(Option::is_none) {
+            self.first = (self.first + 1) % self.players.len();
+            self.active = self.first;
+            self.deal();
+        } else {
+            self.active = (self.active + 1) % self.players.len();
+        }
+        Ok(taken)
+    }
+
+    /// Line with the most cards left, preferring rows, to take on behalf of a
+    /// player.
+    pub fn auto_selection(&self) -> GridSelection {
+        let lines = (0..GRID_SIZE)
+            .map(GridSelection::Row)
+            .chain((0..GRID_SIZE).map(GridSelection::Column));
+        let mut best = GridSelection::Row(0);
+        let mut most = 0;
+        for line in lines {
+            let count = line
+                .slots()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|&slot| self.grid[slot].is_some())
+                .count();
+            if count > most {
+                best = line;
+                most = count;
+            }
+        }
+        best
+    }
+
+    /// Get the pool of cards drafted by this player, if any.
+    pub fn drafted_cards(&self, player: Uuid) -> Option<&Vec<Card>> {
+        self.pools.get(&player)
+    }
+
+    /// Check if this draft is completed, with the deck and grid empty.
+    pub fn draft_complete(&self) -> bool {
+        self.deck.is_empty() && self.grid.iter().all(Option::is_none)
+    }
+
+    /// Map from player ID to pool of taken cards.
+    pub fn pools(&self) -> &HashMap<Uuid, Vec<Card>> {
+        &self.pools
+    }
+}
+
+/// Index of the card in the pack which maximises the key. Earlier cards win
+/// ties.
+fn best_by<K: Ord, F: Fn(&Card) -> K>(pack: &[Card], key: F) -> usize {
+    let mut best = 0;
+    for (i, card) in pack.iter().enumerate().skip(1) {
+        if key(card) > key(&pack[best]) {
+            best = i;
+        }
+    }
+    best
+}
+
+/// The card of the highest rarity in the pack, if the pack isn't empty.
+pub fn bomb(pack: &[Card]) -> Option<&Card> {
+    if pack.is_empty() {
+        None
+    } else {
+        Some(&pack[best_by(pack, |card| card.rarity.rank())])
+    }
+}
+
+/// Choose a card from the pack for a bot: one of the highest rarity, at random.
+pub fn bot_pick<R: Rng>(pack: &[Card], rng: &mut R) -> usize {
+    let best = pack.iter().map(|card| card.rarity.rank()).max();
+    let candidates: Vec<usize> = (0..pack.len())
+        .filter(|&i| Some(pack[i].rarity.rank()) == best)
+        .collect();
+    candidates.choose(rng).copied().unwrap_or(0)
+}
+
+/// Choose a card from the pack on behalf of a player, using the given
+/// strategy. The pool is the cards that player has picked so far. Returns the
+/// index of the chosen card in the pack.
+pub fn auto_pick<R: Rng>(pack: &[Card], pool: &[Card], strategy: AutoPick, rng: &mut R) -> usize {
+    debug_assert!(!pack.is_empty());
+
+    match strategy {
+        AutoPick::First => 0,
+        AutoPick::Random => rng.gen_range(0..pack.len()),
+        AutoPick::HighestRarity => best_by(pack, |card| card.rarity.rank()),
+        AutoPick::OnColour => {
+            let mut counts = HashMap::new();
+            for colour in pool.iter().flat_map(|card| card.colours.iter()) {
+                *counts.entry(*colour).or_insert(0) += 1;
+            }
+
+            // Prefer cards matching the colours already picked, breaking ties
+            // by rarity.
+            best_by(pack, |card| {
+                let score: usize = card
+                    .colours
+                    .iter()
+                    .map(|colour| counts.get(colour).copied().unwrap_or(0))
+                    .sum();
+                (score, card.rarity.rank())
+            })
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::assert_matches::assert_matches;
+    use std::collections::HashMap;
+
+    use rand::{rngs::StdRng, Rng, SeedableRng};
     use uuid::Uuid;
 
-    use crate::draft::{
-        game::PassDirection,
-        packs::{make_packs, DraftPool},
-        DraftConfig,
+    use crate::{
+        cards::{Card, Colour, Rarity},
+        draft::{
+            packs::{make_packs, DraftPool},
+            AutoPick, DraftConfig, PassDirection, PassModel,
+        },
+        error::DraftError,
     };
 
-    use super::Draft;
+    use super::{
+        auto_pick, bot_pick, Draft, DraftPack, GridDraft, GridSelection, WinstonAction,
+        WinstonDraft, WinstonState, GRID_SIZE,
+    };
 
     fn packless_draft(players: Vec<Uuid>) -> Draft {
-        Draft::new(players, 0, Vec::new())
+        Draft::new(
+            players,
+            0,
+            Vec::new(),
+            PassModel::Snake,
+            PassDirection::Left,
+            0,
+        )
     }
 
     #[test]
@@ -298,7 +1091,14 @@ mod test {
         let pool = DraftPool::sample(1, 1, 1, 1);
         let packs = make_packs(players.len(), &config, pool).unwrap();
 
-        let mut draft = Draft::new(players.clone(), config.rounds, packs);
+        let mut draft = Draft::new(
+            players.clone(),
+            config.rounds,
+            packs,
+            PassModel::Snake,
+            PassDirection::Left,
+            0,
+        );
 
         let player_packs = draft.begin();
 
@@ -308,15 +1108,21 @@ mod test {
             .any(|(pack_player, _)| pack_player == player)));
 
         // Pick at invalid index should fail.
-        assert!(draft.handle_pick(p1, config.cards_per_pack).is_err());
+        assert_matches!(
+            draft.handle_pick(p1, config.cards_per_pack),
+            Err(DraftError::InvalidPickIndex)
+        );
         // Pick from a player who is not in the draft should fail.
-        assert!(draft.handle_pick(Uuid::new_v4(), 0).is_err());
+        assert_matches!(
+            draft.handle_pick(Uuid::new_v4(), 0),
+            Err(DraftError::PlayerNotInDraft)
+        );
         // There are no packs waiting and the next player already has a pack.
         assert!(draft.handle_pick(p1, 0).unwrap().1.is_empty());
         assert!(draft.handle_pick(p3, 5).unwrap().1.is_empty());
 
         // No pack available, should be rejected.
-        assert!(draft.handle_pick(p1, 0).is_err());
+        assert_matches!(draft.handle_pick(p1, 0), Err(DraftError::NoCurrentPack));
 
         // When player two makes a pick, we should have 2 updates. Player 2
         // should have a pack available as player 3 has already picked and
@@ -327,7 +1133,7 @@ mod test {
         assert!(updates.iter().any(|(player, _)| *player == p2));
         assert!(updates
             .iter()
-            .all(|(_, pack)| pack.len() == config.cards_per_pack - 1));
+            .all(|(_, pack)| pack.cards.len() == config.cards_per_pack - 1));
         assert!(draft.handle_pick(p4, 14).unwrap().1.len() == 2);
 
         // Pick all but 1 of the rest of the cards.
@@ -338,10 +1144,10 @@ mod test {
         }
 
         // All players should have their final pick remaining.
-        assert!(players
-            .iter()
-            .all(|&player| draft.current_pack(player).unwrap().len() == 1
-                && draft.drafted_cards(player).unwrap().len() == 14));
+        assert!(players.iter().all(
+            |&player| draft.current_pack(player).unwrap().cards.len() == 1
+                && draft.drafted_cards(player).unwrap().len() == 14
+        ));
         assert!(draft.handle_pick(p1, 1).is_err());
         assert!(draft.handle_pick(p1, 0).is_ok());
         assert!(draft.handle_pick(p2, 0).is_ok());
@@ -399,7 +1205,7 @@ mod test {
         };
         let pool = DraftPool::sample(1, 1, 1, 1);
         let packs = make_packs(1, config, pool).unwrap();
-        let mut draft = Draft::new(vec![p], 1, packs);
+        let mut draft = Draft::new(vec![p], 1, packs, PassModel::Snake, PassDirection::Left, 0);
 
         assert!(draft.begin().len() == 1);
 
@@ -408,4 +1214,572 @@ mod test {
         assert_eq!(result.unwrap().1.len(), 1);
         assert!(!draft.draft_complete());
     }
+
+    #[test]
+    fn test_varying_pack_sizes() {
+        let players = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let config = DraftConfig {
+            rounds: 3,
+            cards_per_pack: 3,
+            pack_sizes: Some(vec![3, 3, 2]),
+            rares: 1,
+            uncommons: 1,
+            commons: 1,
+            ..Default::default()
+        };
+        let pool = DraftPool::sample(0, 6, 6, 6);
+        let packs = make_packs(players.len(), &config, pool).unwrap();
+        let mut draft = Draft::new(
+            players.clone(),
+            config.rounds,
+            packs,
+            PassModel::Snake,
+            PassDirection::Left,
+            0,
+        );
+
+        let first = draft.begin();
+        assert!(first.iter().all(|(_, pack)| pack.cards.len() == 3));
+
+        while !draft.draft_complete() {
+            for &player in &players {
+                if draft.current_pack(player).is_some() {
+                    draft.handle_pick(player, 0).unwrap();
+                }
+            }
+        }
+
+        assert!(draft.generated_packs.is_empty());
+        assert!(players
+            .iter()
+            .all(|&player| draft.drafted_cards(player).unwrap().len() == 8));
+    }
+
+    #[test]
+    fn test_remaining_by_rarity() {
+        let players = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 3,
+            rares: 1,
+            uncommons: 1,
+            commons: 1,
+            mythic_rate: 0.0,
+            ..Default::default()
+        };
+        let pool = DraftPool::sample(0, 4, 4, 4);
+        let packs = make_packs(players.len(), &config, pool).unwrap();
+        let mut draft = Draft::new(
+            players.clone(),
+            config.rounds,
+            packs,
+            PassModel::Snake,
+            PassDirection::Left,
+            0,
+        );
+        draft.begin();
+
+        // Each player takes the rare from their first pack.
+        for &player in &players {
+            let pack = draft.current_pack(player).unwrap();
+            let index = pack
+                .cards
+                .iter()
+                .position(|card| card.rarity == Rarity::Rare)
+                .unwrap();
+            draft.handle_pick(player, index).unwrap();
+        }
+
+        let counts = draft.remaining_by_rarity();
+        assert_eq!(counts.unopened[&Rarity::Rare], 2);
+        assert_eq!(counts.unopened[&Rarity::Common], 2);
+        assert!(!counts.in_packs.contains_key(&Rarity::Rare));
+        assert_eq!(counts.in_packs[&Rarity::Uncommon], 2);
+        assert_eq!(counts.picked[&Rarity::Rare], 2);
+        assert!(!counts.picked.contains_key(&Rarity::Common));
+    }
+
+    /// Directions packs are passed in each round of a three round draft.
+    fn pass_directions(
+        pass_model: PassModel,
+        first: PassDirection,
+        seed: u64,
+    ) -> Vec<PassDirection> {
+        let p = Uuid::new_v4();
+        let config = DraftConfig {
+            rounds: 3,
+            cards_per_pack: 1,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let packs = make_packs(1, &config, DraftPool::sample(0, 0, 0, 3)).unwrap();
+        let mut draft = Draft::new(vec![p], config.rounds, packs, pass_model, first, seed);
+
+        let mut directions = Vec::new();
+        draft.begin();
+        while !draft.draft_complete() {
+            directions.push(draft.direction);
+            draft.handle_pick(p, 0).unwrap();
+        }
+        directions
+    }
+
+    #[test]
+    fn test_pass_models() {
+        use PassDirection::*;
+
+        assert_eq!(
+            pass_directions(PassModel::Snake, Left, 0),
+            vec![Left, Right, Left]
+        );
+        assert_eq!(
+            pass_directions(PassModel::Linear, Left, 0),
+            vec![Left, Left, Left]
+        );
+
+        // Either model can start by passing right instead.
+        assert_eq!(
+            pass_directions(PassModel::Snake, Right, 0),
+            vec![Right, Left, Right]
+        );
+        assert_eq!(
+            pass_directions(PassModel::Linear, Right, 0),
+            vec![Right, Right, Right]
+        );
+
+        // Random directions are reproducible with the same seed.
+        let expected: Vec<PassDirection> = (1..=3)
+            .map(|round| {
+                if StdRng::seed_from_u64(42 + round).gen_bool(0.5) {
+                    Left
+                } else {
+                    Right
+                }
+            })
+            .collect();
+        assert_eq!(pass_directions(PassModel::Random, Left, 42), expected);
+    }
+
+    #[test]
+    fn test_random_seat() {
+        let players: Vec<Uuid> = (0..4).map(|_| Uuid::new_v4()).collect();
+        let config = DraftConfig {
+            rounds: 3,
+            cards_per_pack: 5,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let pool = DraftPool::sample(0, 0, 0, 60);
+        let packs = make_packs(players.len(), &config, pool).unwrap();
+        let mut draft = Draft::new(
+            players.clone(),
+            config.rounds,
+            packs,
+            PassModel::RandomSeat,
+            PassDirection::Left,
+            7,
+        );
+
+        draft.begin();
+        let mut picks = 0;
+        while !draft.draft_complete() {
+            assert!(!draft.stalled());
+            for &player in &players {
+                if draft.current_pack(player).is_some() {
+                    draft.handle_pick(player, 0).unwrap();
+                    picks += 1;
+                }
+            }
+        }
+
+        assert_eq!(picks, 60);
+        assert!(players
+            .iter()
+            .all(|player| draft.drafted_cards(*player).unwrap().len() == 15));
+    }
+
+    #[test]
+    fn test_pack_origins() {
+        let players: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let packs = make_packs(3, &config, DraftPool::sample(0, 0, 0, 9)).unwrap();
+        let mut draft = Draft::new(
+            players.clone(),
+            config.rounds,
+            packs,
+            PassModel::Snake,
+            PassDirection::Left,
+            0,
+        );
+
+        // Each pack opened by a seat keeps that origin as it is passed.
+        let mut origins: HashMap<usize, Uuid> = HashMap::new();
+        for (player, pack) in draft.begin() {
+            assert_eq!(pack.origin, player);
+            origins.insert(pack.id, player);
+        }
+        while !draft.draft_complete() {
+            for &player in &players {
+                if let Some(pack) = draft.current_pack(player) {
+                    assert_eq!(pack.origin, origins[&pack.id]);
+                    draft.handle_pick(player, 0).unwrap();
+                }
+            }
+        }
+        assert_eq!(origins.len(), 3);
+
+        // The full packs are kept for auditing.
+        let opened = draft.opened_packs();
+        assert_eq!(opened.len(), 3);
+        assert!(opened
+            .iter()
+            .all(|o| o.round == 1 && o.seat == o.pack.origin && o.pack.cards.len() == 3));
+    }
+
+    #[test]
+    fn test_resume_mid_round() {
+        let players: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 3,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let packs = make_packs(3, &config, DraftPool::sample(0, 0, 0, 18)).unwrap();
+
+        // Make picks in a fixed order until the draft is complete, saving and
+        // restoring the draft after the given number of picks.
+        let run = |restore_after: Option<usize>| {
+            let mut draft = Draft::new(
+                players.clone(),
+                config.rounds,
+                packs.clone(),
+                PassModel::Random,
+                PassDirection::Left,
+                7,
+            );
+            draft.begin();
+            let mut picks = 0;
+            while !draft.draft_complete() {
+                for &player in &players {
+                    if draft.current_pack(player).is_none() {
+                        continue;
+                    }
+
+                    draft.handle_pick(player, 0).unwrap();
+                    picks += 1;
+                    if restore_after == Some(picks) {
+                        let saved = serde_json::to_string(&draft).unwrap();
+                        draft = serde_json::from_str(&saved).unwrap();
+                    }
+                }
+            }
+            players
+                .iter()
+                .map(|p| draft.drafted_cards(*p).unwrap().clone())
+                .collect::<Vec<_>>()
+        };
+
+        let names = |pools: Vec<Vec<Card>>| -> Vec<Vec<String>> {
+            pools
+                .iter()
+                .map(|pool| pool.iter().map(|c| c.name().to_string()).collect())
+                .collect()
+        };
+
+        // Restored part way through the first round, with packs in flight.
+        let expected = names(run(None));
+        assert_eq!(names(run(Some(4))), expected);
+        assert_eq!(names(run(Some(11))), expected);
+    }
+
+    #[test]
+    fn test_stalled() {
+        let p = Uuid::new_v4();
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let packs = make_packs(1, &config, DraftPool::sample(0, 0, 0, 2)).unwrap();
+        let mut draft = Draft::new(
+            vec![p],
+            config.rounds,
+            packs,
+            PassModel::Snake,
+            PassDirection::Left,
+            0,
+        );
+        draft.begin();
+        assert!(!draft.stalled());
+
+        // Claim a second round without any packs for it.
+        draft.rounds = 2;
+        draft.handle_pick(p, 0).unwrap();
+        assert!(!draft.stalled());
+        let (_, packs) = draft.handle_pick(p, 0).unwrap();
+        assert!(packs.is_empty());
+        assert!(draft.stalled());
+        assert!(!draft.draft_complete());
+    }
+
+    #[test]
+    fn test_undo_pick() {
+        let players: Vec<Uuid> = (0..2).map(|_| Uuid::new_v4()).collect();
+        let (p1, p2) = (players[0], players[1]);
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let packs = make_packs(2, &config, DraftPool::sample(0, 0, 0, 6)).unwrap();
+        let mut draft = Draft::new(
+            players,
+            config.rounds,
+            packs,
+            PassModel::Linear,
+            PassDirection::Left,
+            0,
+        );
+        draft.begin();
+        assert_matches!(draft.undo_pick(p1), Err(DraftError::Other(_)));
+
+        // The pack is waiting behind the next player's own, so can be taken
+        // back, with the card returned to where it was.
+        let before = draft.current_pack(p1).unwrap();
+        let (card, _) = draft.handle_pick(p1, 1).unwrap();
+        assert_eq!(draft.queue_size(p2), 2);
+        let (undone, from) = draft.undo_pick(p1).unwrap();
+        assert_eq!(undone.name(), card.name());
+        assert_eq!(from, p2);
+        assert_eq!(draft.queue_size(p2), 1);
+        assert!(draft.drafted_cards(p1).unwrap().is_empty());
+        let after = draft.current_pack(p1).unwrap();
+        assert_eq!(after.id, before.id);
+        let names = |pack: &DraftPack| -> Vec<String> {
+            pack.cards.iter().map(|c| c.name().to_string()).collect()
+        };
+        assert_eq!(names(&after), names(&before));
+
+        // Once the next player has picked from the pack, it's too late.
+        draft.handle_pick(p1, 0).unwrap();
+        draft.handle_pick(p2, 0).unwrap();
+        draft.handle_pick(p2, 0).unwrap();
+        assert_matches!(draft.undo_pick(p1), Err(DraftError::Other(_)));
+        assert_eq!(draft.drafted_cards(p1).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_auto_pick() {
+        let coloured = |rarity, colours: &[Colour]| {
+            let mut card = Card::sample(rarity);
+            card.colours = colours.to_vec();
+            card
+        };
+
+        let pack = vec![
+            coloured(Rarity::Common, &[Colour::G]),
+            coloured(Rarity::Uncommon, &[Colour::U]),
+            coloured(Rarity::Rare, &[Colour::R]),
+            coloured(Rarity::Uncommon, &[Colour::W, Colour::U]),
+        ];
+        let pool = vec![
+            coloured(Rarity::Common, &[Colour::U]),
+            coloured(Rarity::Common, &[Colour::W]),
+            coloured(Rarity::Common, &[Colour::G]),
+        ];
+
+        let rng = &mut StdRng::seed_from_u64(0);
+        assert_eq!(auto_pick(&pack, &pool, AutoPick::First, rng), 0);
+        assert_eq!(auto_pick(&pack, &pool, AutoPick::HighestRarity, rng), 2);
+        assert_eq!(auto_pick(&pack, &pool, AutoPick::OnColour, rng), 3);
+        for _ in 0..10 {
+            assert!(auto_pick(&pack, &pool, AutoPick::Random, rng) < pack.len());
+        }
+
+        // With nothing picked yet, colour gives no preference so rarity wins.
+        assert_eq!(auto_pick(&pack, &[], AutoPick::OnColour, rng), 2);
+
+        // Bots take the rarest card, breaking ties at random.
+        assert_eq!(bot_pick(&pack, rng), 2);
+        let pack = [pack[0].clone(), pack[1].clone(), pack[3].clone()];
+        for _ in 0..10 {
+            assert!([1, 2].contains(&bot_pick(&pack, rng)));
+        }
+
+        // The same seed breaks ties the same way.
+        let picks = |seed| {
+            let rng = &mut StdRng::seed_from_u64(seed);
+            (0..10).map(|_| bot_pick(&pack, rng)).collect::<Vec<_>>()
+        };
+        assert_eq!(picks(7), picks(7));
+    }
+
+    #[test]
+    fn test_winston_draft() {
+        let players = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let deck: Vec<Card> = (0..30).map(|_| Card::sample(Rarity::Common)).collect();
+        let mut draft = WinstonDraft::new(players.clone(), deck, 0);
+        assert_eq!(
+            draft.state(),
+            WinstonState {
+                active: Some(players[0]),
+                pile: 0,
+                pile_sizes: vec![1, 1, 1],
+                deck_size: 27,
+            }
+        );
+
+        // Only the active player may act.
+        assert_matches!(
+            draft.handle_winston_action(players[1], WinstonAction::Take),
+            Err(DraftError::NoCurrentPack)
+        );
+
+        // Passing grows the pile and moves on to the next.
+        assert!(draft
+            .handle_winston_action(players[0], WinstonAction::Pass)
+            .unwrap()
+            .is_empty());
+        assert_eq!(draft.state().pile, 1);
+        assert_eq!(draft.state().pile_sizes, vec![2, 1, 1]);
+
+        // Passing the last pile takes a card from the deck.
+        draft
+            .handle_winston_action(players[0], WinstonAction::Pass)
+            .unwrap();
+        let taken = draft
+            .handle_winston_action(players[0], WinstonAction::Pass)
+            .unwrap();
+        assert_eq!(taken.len(), 1);
+        assert_eq!(draft.state().active, Some(players[1]));
+        assert_eq!(draft.state().pile_sizes, vec![2, 2, 2]);
+
+        // Alternate between taking and passing until every card is taken.
+        let mut rng = StdRng::seed_from_u64(0);
+        while let Some(player) = draft.active_player() {
+            let action = if rng.gen_bool(0.5) {
+                WinstonAction::Take
+            } else {
+                WinstonAction::Pass
+            };
+            if draft.handle_winston_action(player, action).is_err() {
+                draft
+                    .handle_winston_action(player, WinstonAction::Take)
+                    .unwrap();
+            }
+        }
+
+        assert!(draft.draft_complete());
+        assert_eq!(draft.state().deck_size, 0);
+        assert_eq!(draft.pools().values().map(Vec::len).sum::<usize>(), 30);
+    }
+
+    #[test]
+    fn test_rochester_draft() {
+        let players = vec![Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4()];
+        let packs = (0..6)
+            .map(|_| (0..4).map(|_| Card::sample(Rarity::Common)).collect())
+            .collect();
+        let mut draft = Draft::new(
+            players.clone(),
+            2,
+            packs,
+            PassModel::Linear,
+            PassDirection::Right,
+            0,
+        )
+        .rochester();
+
+        // A single pack is opened, by the first seat.
+        let new_packs = draft.begin();
+        assert_eq!(new_packs.len(), 1);
+        assert_eq!(new_packs[0].0, players[0]);
+        assert_eq!(draft.rochester_turn(), Some(players[0]));
+
+        // Picks out of turn are rejected.
+        assert!(draft.handle_pick(players[1], 0).is_err());
+        assert!(draft.undo_pick(players[0]).is_err());
+
+        // Turns snake around the table from the seat which opened each pack,
+        // with the last seat picking twice.
+        let expected = [0, 1, 2, 2, 1, 2, 0, 0, 2, 0, 1, 1];
+        for round in 1..=2 {
+            for &seat in &expected {
+                assert_eq!(draft.round(), round);
+                assert_eq!(draft.rochester_turn(), Some(players[seat]));
+                let (_, new_packs) = draft.handle_pick(players[seat], 0).unwrap();
+                assert!(new_packs.len() <= 1);
+                assert_eq!(
+                    new_packs.first().map(|(seat, _)| *seat),
+                    draft.rochester_turn()
+                );
+            }
+        }
+
+        assert!(draft.draft_complete());
+        assert_eq!(draft.rochester_turn(), None);
+        for player in &players {
+            assert_eq!(draft.drafted_cards(*player).unwrap().len(), 8);
+        }
+        assert_eq!(draft.opened_packs().len(), 6);
+    }
+
+    #[test]
+    fn test_grid_draft() {
+        let players = vec![Uuid::new_v4(), Uuid::new_v4()];
+        let deck: Vec<Card> = (0..36).map(|_| Card::sample(Rarity::Common)).collect();
+        let mut draft = GridDraft::new(players.clone(), deck, 0);
+        assert!(draft.grid().iter().all(Option::is_some));
+        assert_eq!(draft.active_player(), Some(players[0]));
+
+        // Only the active player may take a line, and only one in the grid.
+        assert_matches!(
+            draft.handle_grid_pick(players[1], GridSelection::Row(0)),
+            Err(DraftError::NoCurrentPack)
+        );
+        assert!(draft
+            .handle_grid_pick(players[0], GridSelection::Row(GRID_SIZE))
+            .is_err());
+
+        // Lines crossing one already taken hold fewer cards.
+        let taken = draft
+            .handle_grid_pick(players[0], GridSelection::Row(0))
+            .unwrap();
+        assert_eq!(taken.len(), 3);
+        assert!(draft.grid()[..3].iter().all(Option::is_none));
+        let taken = draft
+            .handle_grid_pick(players[1], GridSelection::Column(0))
+            .unwrap();
+        assert_eq!(taken.len(), 2);
+
+        // The grid is dealt again, and the other player takes first.
+        assert!(draft.grid().iter().all(Option::is_some));
+        assert_eq!(draft.active_player(), Some(players[1]));
+        draft
+            .handle_grid_pick(players[1], GridSelection::Row(1))
+            .unwrap();
+        assert!(draft
+            .handle_grid_pick(players[0], GridSelection::Row(1))
+            .is_err());
+
+        // Take lines which don't cross until the deck runs out.
+        while let Some(player) = draft.active_player() {
+            let row = if draft.grid()[0].is_some() { 0 } else { 2 };
+            draft
+                .handle_grid_pick(player, GridSelection::Row(row))
+                .unwrap();
+        }
+
+        assert!(draft.draft_complete());
+        assert_eq!(draft.drafted_cards(players[0]).unwrap().len(), 12);
+        assert_eq!(draft.drafted_cards(players[1]).unwrap().len(), 11);
+    }
 }
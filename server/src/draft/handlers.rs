@@ -1,30 +1,314 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
-use axum::extract::ws::{Message, WebSocket};
-use futures_util::{SinkExt, StreamExt};
+use axum::{
+    extract::ws::{Message, WebSocket},
+    http::StatusCode,
+};
+use futures_util::{SinkExt, Stream, StreamExt};
+use tokio::time::{Duration, Instant};
 use uuid::Uuid;
 
 use crate::{
-    cards::CardDatabase,
+    cards::{
+        card_entry, card_name, cockatrice::DecodedCards, Card, CardDatabase, ImageQuality, Rarity,
+    },
     draft::{
         server::{ClientMessage, ServerMessage},
-        DraftConfig,
+        AutoPick, DraftConfig, DraftMode, PassDirection, PassModel, RaritySlot,
     },
-    Resp, Servers,
+    error::{DraftError, Res},
+    Resp, ServerConfig, Servers,
 };
 
 use super::{
-    packs::DraftPool,
-    server::{DraftServerRequest, ServerHandle},
+    packs::{make_packs, pack_warnings, DraftPool, Pack},
+    server::{clean_lobby_name, ConnectRequest, DraftServerRequest, SeatPool, ServerHandle},
+    webhook,
 };
 
-pub async fn handle_launch_request(
-    carddb: Arc<CardDatabase>,
-    servers: Servers,
+/// Body of the response to a successful launch request. The client should
+/// join the lobby straight away, as it will be closed if no one joins.
+/// Warnings describe possible mistakes in the draft configuration.
+#[derive(serde::Serialize)]
+struct LaunchResp {
+    message: String,
+    success: bool,
+    lobby: Uuid,
+    warnings: Vec<String>,
+}
+
+/// Body of the response to a dry run launch request, with the packs which
+/// would be opened by the minimum number of players, or to a preview, with
+/// the packs a single player would open.
+#[derive(serde::Serialize)]
+struct DryRunResp {
+    message: String,
+    success: bool,
+    packs: Vec<Pack>,
+    warnings: Vec<String>,
+}
+
+/// Most cards which will be returned by a dry run, across all packs.
+const MAX_DRY_RUN_CARDS: usize = 10_000;
+
+/// Most names of skipped cards listed in a launch warning.
+const MAX_SKIPPED_NAMES: usize = 10;
+
+/// Maximum length of a custom rarity name, in characters.
+const MAX_RARITY_LABEL_LENGTH: usize = 32;
+
+/// Most basic lands of each colour which may be added to each pool.
+const MAX_BASICS_PER_COLOUR: usize = 40;
+
+/// Options read from a launch request form.
+struct LaunchForm {
+    config: DraftConfig,
+    name: Option<String>,
+    list: Option<String>,
+    exclude: Option<String>,
+    cards: Option<DecodedCards>,
+
+    /// Make the packs for the draft without launching it.
+    dry_run: bool,
+
+    /// Disallow rarity fallback, and reject pools which can't supply every
+    /// slot for the largest table.
+    strict: bool,
+}
+
+/// Body of a launch request sent as JSON, for API clients.
+#[derive(serde::Deserialize)]
+pub struct LaunchJson {
+    #[serde(default)]
+    config: DraftConfig,
+    name: Option<String>,
+
+    /// Names of the cards to include in packs.
+    list: Vec<String>,
+
+    /// Names of cards to remove from the list.
+    #[serde(default)]
+    exclude: Vec<String>,
+
+    /// Card database for custom cards, as Cockatrice XML.
+    card_database: Option<String>,
+
+    #[serde(default)]
+    dry_run: bool,
+
+    #[serde(default)]
+    strict: bool,
+}
+
+/// Check that a draft configuration is consistent and within the server
+/// limits. On failure, returns a description of the problem.
+fn validate_config(config: &DraftConfig, server_config: &ServerConfig) -> Result<(), String> {
+    if !(0.0..=1.0).contains(&config.mythic_rate) {
+        return Err(format!("Invalid mythic incidence: {}", config.mythic_rate));
+    }
+
+    if config.min_players == 0 {
+        return Err("Minimum number of players must be positive.".to_string());
+    }
+
+    if config
+        .max_players
+        .is_some_and(|max| max < config.min_players)
+    {
+        return Err("Maximum number of players must be at least the minimum.".to_string());
+    }
+
+    if config.mode == DraftMode::Winston
+        && (config.min_seats() > 3
+            || config
+                .max_players
+                .is_some_and(|max| max.saturating_add(config.bots) > 3))
+    {
+        return Err("Winston drafts are for two or three players.".to_string());
+    }
+
+    if config.mode == DraftMode::Grid
+        && (config.min_seats() > 2
+            || config
+                .max_players
+                .is_some_and(|max| max.saturating_add(config.bots) != 2))
+    {
+        return Err("Grid drafts are for two players.".to_string());
+    }
+
+    if config.deck_size == Some(0) {
+        return Err("Deck size must be positive.".to_string());
+    }
+
+    if config.basics_per_colour > MAX_BASICS_PER_COLOUR {
+        return Err(format!(
+            "Number of basic lands of each colour ({}) greater than the maximum allowed ({MAX_BASICS_PER_COLOUR}).",
+            config.basics_per_colour
+        ));
+    }
+
+    let weights = config.foil_rarity_weights.values();
+    if weights.clone().any(|w| !w.is_finite() || *w < 0.0)
+        || (config.foils > 0 && weights.sum::<f32>() <= 0.0)
+    {
+        return Err("Invalid foil rarity weights.".to_string());
+    }
+
+    if config.slot_distribution.iter().any(|slot| {
+        let weights = slot.weights.values();
+        weights.clone().any(|w| !w.is_finite() || *w < 0.0) || weights.sum::<f32>() <= 0.0
+    }) {
+        return Err("Invalid rarity weights for random slots.".to_string());
+    }
+
+    if config
+        .rarity_labels
+        .values()
+        .any(|l| l.is_empty() || l.chars().count() > MAX_RARITY_LABEL_LENGTH)
+    {
+        return Err("Invalid rarity labels.".to_string());
+    }
+
+    if let Some(url) = &config.webhook_url
+        && let Err(e) = webhook::validate_url(url, server_config.allow_internal_webhooks)
+    {
+        return Err(e.to_string());
+    }
+
+    if config.rounds > server_config.max_rounds {
+        return Err(format!(
+            "Number of packs ({}) greater than the maximum allowed ({}).",
+            config.rounds, server_config.max_rounds
+        ));
+    }
+
+    if config.cards_per_pack.saturating_add(config.foils) > server_config.max_cards_per_pack {
+        return Err(format!(
+            "Number of cards per pack ({}) greater than the maximum allowed ({}).",
+            config.cards_per_pack.saturating_add(config.foils),
+            server_config.max_cards_per_pack
+        ));
+    }
+
+    if let Some(sizes) = &config.pack_sizes {
+        if sizes.contains(&0) {
+            return Err("Pack sizes must be positive.".to_string());
+        }
+
+        if sizes.len() != config.rounds {
+            return Err(format!(
+                "Number of pack sizes ({}) does not match number of packs ({}).",
+                sizes.len(),
+                config.rounds
+            ));
+        }
+
+        if let Some(size) = sizes
+            .iter()
+            .find(|&&n| n > server_config.max_cards_per_pack)
+        {
+            return Err(format!(
+                "Number of cards per pack ({size}) greater than the maximum allowed ({}).",
+                server_config.max_cards_per_pack
+            ));
+        }
+    }
+
+    // Lobbies without a player limit are given one at launch, see
+    // largest_table, so only the largest table allowed is checked.
+    let seats = config
+        .max_players
+        .map_or(config.min_seats(), |max| max.saturating_add(config.bots));
+    let total_cards = config.cards_per_seat().saturating_mul(seats);
+    if total_cards > server_config.max_total_cards {
+        return Err(format!(
+            "Packs would contain {total_cards} cards in total, more than the maximum allowed ({}).",
+            server_config.max_total_cards
+        ));
+    }
+
+    let slots = config.slot_distribution.len();
+    if config.mythics + config.rares + config.uncommons + config.commons + slots
+        != config.cards_per_pack
+    {
+        return Err(format!(
+            "Count of mythics ({}) + rares ({}) + uncommons ({}) + commons ({}) + random slots ({slots}) does not match number of cards in pack ({}).",
+            config.mythics,
+            config.rares,
+            config.uncommons,
+            config.commons,
+            config.cards_per_pack
+        ));
+    }
+
+    Ok(())
+}
+
+/// Parse a rate given either as a fraction, e.g. "0.125", or a percentage,
+/// e.g. "12.5%". Values above one are taken to be percentages even without
+/// the sign, as entering a percentage is an easy mistake. Returns None unless
+/// the rate is between zero and one.
+fn parse_rate(s: &str) -> Option<f32> {
+    let s = s.trim();
+    let (value, percent) = match s.strip_suffix('%') {
+        Some(value) => (value.trim_end(), true),
+        None => (s, false),
+    };
+    let value: f32 = value.parse().ok()?;
+    let rate = if percent || value > 1.0 {
+        value / 100.0
+    } else {
+        value
+    };
+    (0.0..=1.0).contains(&rate).then_some(rate)
+}
+
+/// Parse weighted pack slots, given as semicolon-separated slots each of four
+/// comma-separated weights, for mythic, rare, uncommon and common. Returns None
+/// if any weight is invalid or a slot has no positive weight.
+fn parse_slot_distribution(s: &str) -> Option<Vec<RaritySlot>> {
+    s.split(';')
+        .map(|slot| {
+            let weights = slot
+                .split(',')
+                .map(|w| {
+                    w.trim()
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|w| w.is_finite() && *w >= 0.0)
+                })
+                .collect::<Option<Vec<f32>>>()?;
+            if weights.len() != 4 || weights.iter().all(|w| *w == 0.0) {
+                return None;
+            }
+
+            let weights = [
+                Rarity::Mythic,
+                Rarity::Rare,
+                Rarity::Uncommon,
+                Rarity::Common,
+            ]
+            .into_iter()
+            .zip(weights)
+            .collect();
+            Some(RaritySlot { weights })
+        })
+        .collect()
+}
+
+/// Read the options for a draft from a launch request form, validating the
+/// configuration against the server limits. On failure, returns the error
+/// response to send.
+async fn read_launch_form(
+    server_config: &ServerConfig,
     mut data: axum::extract::Multipart,
-) -> axum::response::Response<String> {
+) -> Result<LaunchForm, axum::response::Response<String>> {
     let mut cards = None;
     let mut list = None;
+    let mut exclude = None;
+    let mut name = None;
+    let mut dry_run = false;
+    let mut strict = false;
 
     // If booleans are omitted from the form data, it's because their
     // checkboxes are unchecked, and the associated variables should be false.
@@ -32,106 +316,1129 @@ pub async fn handle_launch_request(
     let mut config = DraftConfig {
         unique_cards: false,
         use_rarities: false,
+        shuffle_pack_order: false,
         ..DraftConfig::default()
     };
 
-    while let Ok(Some(field)) = data.next_field().await {
+    // A request without any fields is malformed, rather than just missing
+    // the card list.
+    let mut empty = true;
+    loop {
+        let field = match data.next_field().await {
+            Ok(Some(field)) => field,
+            Ok(None) => break,
+            Err(e) => {
+                return Err(Resp::basic(
+                    format!("Malformed form data: {e}"),
+                    StatusCode::BAD_REQUEST,
+                ))
+            }
+        };
+        empty = false;
+
         let field_name = field.name().unwrap_or("").to_string();
         if field_name == "card_database" {
             match field.bytes().await {
                 Ok(bytes) if bytes.is_empty() => {} // Empty card_database field is OK.
                 Ok(bytes) => match crate::cards::cockatrice::decode_xml_cards(bytes) {
                     Ok(db) => cards = Some(db),
-                    Err(e) => return Resp::e422(format!("Failed to load card database: {e}")),
+                    Err(e) => {
+                        return Err(Resp::error(
+                            DraftError::InvalidCardDatabase(e.to_string()),
+                            StatusCode::UNPROCESSABLE_ENTITY,
+                        ))
+                    }
                 },
-                Err(e) => return Resp::e500(e),
+                Err(e) => return Err(Resp::e500(e)),
             }
             continue;
         }
 
         let s = match field.text().await {
             Ok(s) => s,
-            Err(e) => return Resp::e500(e),
+            Err(e) => return Err(Resp::e500(e)),
         };
 
         match field_name.as_str() {
             "list" => list = Some(s),
+            "exclude" => exclude = Some(s),
+            "name" if s.trim().is_empty() => {} // Lobby name is optional.
+            "name" => match clean_lobby_name(&s) {
+                Ok(s) => name = Some(s),
+                Err(e) => return Err(Resp::invalid(format!("Invalid lobby name: {e}"))),
+            },
             "packs" => match s.parse::<usize>() {
                 Ok(n) => config.rounds = n,
-                Err(_) => return Resp::e422(format!("Invalid pack count: {s}")),
+                Err(_) => return Err(Resp::invalid(format!("Invalid pack count: {s}"))),
             },
             "cards_per_pack" => match s.parse::<usize>() {
                 Ok(n) => config.cards_per_pack = n,
-                Err(_) => return Resp::e422(format!("Invalid number of cards per pack: {s}")),
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid number of cards per pack: {s}"
+                    )))
+                }
+            },
+            "pack_sizes" if s.trim().is_empty() => {} // Per-round sizes are optional.
+            "pack_sizes" => match s
+                .split(',')
+                .map(|n| n.trim().parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+            {
+                Ok(sizes) if sizes.iter().all(|&n| n > 0) => config.pack_sizes = Some(sizes),
+                _ => return Err(Resp::invalid(format!("Invalid pack sizes: {s}"))),
             },
             "unique_cards" => match s.as_str() {
                 "checked" => config.unique_cards = true,
                 "unchecked" => config.unique_cards = false,
-                _ => return Resp::e422(format!("Invalid checkbox value for unique_cards: {s}")),
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for unique_cards: {s}"
+                    )))
+                }
             },
             "use_rarities" => match s.as_str() {
                 "checked" => config.use_rarities = true,
                 "unchecked" => config.use_rarities = false,
-                _ => return Resp::e422(format!("Invalid checkbox value for use_rarities: {s}")),
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for use_rarities: {s}"
+                    )))
+                }
+            },
+            "shuffle_pack_order" => match s.as_str() {
+                "checked" => config.shuffle_pack_order = true,
+                "unchecked" => config.shuffle_pack_order = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for shuffle_pack_order: {s}"
+                    )))
+                }
+            },
+            "reveal_bombs" => match s.as_str() {
+                "checked" => config.reveal_bombs = true,
+                "unchecked" => config.reveal_bombs = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for reveal_bombs: {s}"
+                    )))
+                }
+            },
+            "include_basics" => match s.as_str() {
+                "checked" => config.include_basics = true,
+                "unchecked" => config.include_basics = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for include_basics: {s}"
+                    )))
+                }
+            },
+            "basics_per_colour" if s.trim().is_empty() => {}
+            "basics_per_colour" => match s.parse::<usize>() {
+                Ok(n) if n > 0 => config.basics_per_colour = n,
+                _ => return Err(Resp::invalid(format!("Invalid number of basic lands: {s}"))),
+            },
+            "identical_rarities" => match s.as_str() {
+                "checked" => config.identical_rarities = true,
+                "unchecked" => config.identical_rarities = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for identical_rarities: {s}"
+                    )))
+                }
+            },
+            "cover_colours" => match s.as_str() {
+                "checked" => config.cover_colours = true,
+                "unchecked" => config.cover_colours = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for cover_colours: {s}"
+                    )))
+                }
+            },
+            "keep_lobby" => match s.as_str() {
+                "checked" => config.keep_lobby = true,
+                "unchecked" => config.keep_lobby = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for keep_lobby: {s}"
+                    )))
+                }
+            },
+            "dry_run" => match s.as_str() {
+                "checked" => dry_run = true,
+                "unchecked" => dry_run = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for dry_run: {s}"
+                    )))
+                }
+            },
+            "strict" => match s.as_str() {
+                "checked" => strict = true,
+                "unchecked" => strict = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for strict: {s}"
+                    )))
+                }
             },
-            "mythic_incidence" => match s.parse::<f32>() {
-                Ok(v) if (0.0..=1.0).contains(&v) => config.mythic_rate = v,
-                _ => return Resp::e422(format!("Invalid mythic incidence: {s}")),
+            "log_messages" => match s.as_str() {
+                "checked" => config.log_messages = true,
+                "unchecked" => config.log_messages = false,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid checkbox value for log_messages: {s}"
+                    )))
+                }
+            },
+            "rarity_labels" if s.trim().is_empty() => {} // Labels are optional.
+            "rarity_labels" => {
+                let labels: Vec<&str> = s.split(',').map(str::trim).collect();
+                if labels.len() != 4
+                    || labels
+                        .iter()
+                        .any(|l| l.is_empty() || l.chars().count() > MAX_RARITY_LABEL_LENGTH)
+                {
+                    return Err(Resp::invalid(format!("Invalid rarity labels: {s}")));
+                }
+                config.rarity_labels = [
+                    Rarity::Mythic,
+                    Rarity::Rare,
+                    Rarity::Uncommon,
+                    Rarity::Common,
+                ]
+                .into_iter()
+                .zip(labels.into_iter().map(String::from))
+                .collect();
+            }
+            "pool_ratio" if s.trim().is_empty() => {} // No recommendation by default.
+            "pool_ratio" => match s.trim().parse::<f32>() {
+                Ok(v) if v >= 0.0 => {
+                    config.pool_ratios = [
+                        Rarity::Mythic,
+                        Rarity::Rare,
+                        Rarity::Uncommon,
+                        Rarity::Common,
+                    ]
+                    .into_iter()
+                    .map(|rarity| (rarity, v))
+                    .collect()
+                }
+                _ => return Err(Resp::invalid(format!("Invalid pool ratio: {s}"))),
+            },
+            "mythic_incidence" => match parse_rate(&s) {
+                Some(rate) => config.mythic_rate = rate,
+                None => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid mythic incidence: {s}. Give a fraction or a percentage."
+                    )))
+                }
+            },
+            "mythics" if s.trim().is_empty() => {} // Mythics share rare slots.
+            "mythics" => match s.trim().parse::<usize>() {
+                Ok(n) => config.mythics = n,
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid number of mythics per pack: {s}"
+                    )))
+                }
             },
             "rares" => match s.parse::<usize>() {
                 Ok(n) => config.rares = n,
-                Err(_) => return Resp::e422(format!("Invalid number of rares per pack: {s}")),
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid number of rares per pack: {s}"
+                    )))
+                }
             },
             "uncommons" => match s.parse::<usize>() {
                 Ok(n) => config.uncommons = n,
-                Err(_) => return Resp::e422(format!("Invalid number of commons per pack: {s}")),
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid number of commons per pack: {s}"
+                    )))
+                }
             },
             "commons" => match s.parse::<usize>() {
                 Ok(n) => config.commons = n,
-                Err(_) => return Resp::e422(format!("Invalid number of commons per pack: {s}")),
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid number of commons per pack: {s}"
+                    )))
+                }
+            },
+            "slot_distribution" if s.trim().is_empty() => {} // No random slots by default.
+            "slot_distribution" => match parse_slot_distribution(&s) {
+                Some(slots) => config.slot_distribution = slots,
+                None => return Err(Resp::invalid(format!("Invalid random slot weights: {s}"))),
+            },
+            "foils" if s.trim().is_empty() => {}
+            "foils" => match s.parse::<usize>() {
+                Ok(n) => config.foils = n,
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid number of foils per pack: {s}"
+                    )))
+                }
+            },
+            "min_players" => match s.parse::<usize>() {
+                Ok(n) if n > 0 => config.min_players = n,
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid minimum number of players: {s}"
+                    )))
+                }
+            },
+            "bots" if s.trim().is_empty() => {}
+            "bots" => match s.parse::<usize>() {
+                Ok(n) => config.bots = n,
+                Err(_) => return Err(Resp::invalid(format!("Invalid number of bots: {s}"))),
+            },
+            "max_players" if s.trim().is_empty() => {} // No limit by default.
+            "max_players" => match s.parse::<usize>() {
+                Ok(n) if n > 0 => config.max_players = Some(n),
+                _ => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid maximum number of players: {s}"
+                    )))
+                }
+            },
+            "pick_timer" if s.trim().is_empty() => {} // No timer by default.
+            "pick_timer" => match s.parse::<u64>() {
+                Ok(secs) if secs > 0 => config.pick_timer = Some(Duration::from_secs(secs)),
+                _ => return Err(Resp::invalid(format!("Invalid pick timer: {s}"))),
+            },
+            "min_pick_delay" if s.trim().is_empty() => {} // No delay by default.
+            "min_pick_delay" => match s.parse::<u64>() {
+                Ok(ms) => config.min_pick_delay = Duration::from_millis(ms),
+                Err(_) => return Err(Resp::invalid(format!("Invalid minimum pick delay: {s}"))),
+            },
+            "reconnect_grace" if s.trim().is_empty() => {} // Use the default.
+            "reconnect_grace" => match s.parse::<u64>() {
+                Ok(ms) => config.reconnect_grace = Duration::from_millis(ms),
+                Err(_) => {
+                    return Err(Resp::invalid(format!(
+                        "Invalid reconnection grace period: {s}"
+                    )))
+                }
+            },
+            "deck_size" if s.trim().is_empty() => {} // Deckbuilding is optional.
+            "deck_size" => match s.parse::<usize>() {
+                Ok(n) if n > 0 => config.deck_size = Some(n),
+                _ => return Err(Resp::invalid(format!("Invalid deck size: {s}"))),
+            },
+            "deck_deadline" if s.trim().is_empty() => {} // No deadline.
+            "deck_deadline" => match s.parse::<u64>() {
+                Ok(secs) if secs > 0 => config.deck_deadline = Some(Duration::from_secs(secs)),
+                _ => return Err(Resp::invalid(format!("Invalid deck deadline: {s}"))),
+            },
+            "webhook_url" if s.trim().is_empty() => {} // Webhook is optional.
+            "webhook_url" => {
+                match webhook::validate_url(s.trim(), server_config.allow_internal_webhooks) {
+                    Ok(()) => config.webhook_url = Some(s.trim().to_string()),
+                    Err(e) => return Err(Resp::invalid(e)),
+                }
+            }
+            "mode" => match s.trim() {
+                "" | "booster" => config.mode = DraftMode::Booster,
+                "winston" => config.mode = DraftMode::Winston,
+                "grid" => config.mode = DraftMode::Grid,
+                "rochester" => config.mode = DraftMode::Rochester,
+                _ => return Err(Resp::invalid(format!("Invalid draft mode: {s}"))),
+            },
+            "image_quality" => match s.trim() {
+                "" | "display" => config.image_quality = ImageQuality::Display,
+                "print" => config.image_quality = ImageQuality::Print,
+                "thumbnail" => config.image_quality = ImageQuality::Thumbnail,
+                _ => return Err(Resp::invalid(format!("Invalid image quality: {s}"))),
+            },
+            "pass_model" => match s.trim() {
+                "" | "snake" => config.pass_model = PassModel::Snake,
+                "linear" => config.pass_model = PassModel::Linear,
+                "random" => config.pass_model = PassModel::Random,
+                "random_seat" => config.pass_model = PassModel::RandomSeat,
+                _ => return Err(Resp::invalid(format!("Invalid pass model: {s}"))),
+            },
+            "first_direction" => match s.trim() {
+                "" | "left" => config.first_direction = PassDirection::Left,
+                "right" => config.first_direction = PassDirection::Right,
+                _ => return Err(Resp::invalid(format!("Invalid first pass direction: {s}"))),
+            },
+            "seed" if s.trim().is_empty() => {} // Random by default.
+            "seed" => match s.trim().parse::<u64>() {
+                Ok(seed) => config.seed = Some(seed),
+                Err(_) => return Err(Resp::invalid(format!("Invalid seed: {s}"))),
+            },
+            "auto_pick" => match s.as_str() {
+                "first" => config.auto_pick = AutoPick::First,
+                "random" => config.auto_pick = AutoPick::Random,
+                "highest_rarity" => config.auto_pick = AutoPick::HighestRarity,
+                "on_colour" => config.auto_pick = AutoPick::OnColour,
+                _ => return Err(Resp::invalid(format!("Invalid auto pick strategy: {s}"))),
             },
             _ => {}
         }
     }
 
-    if config.rares + config.uncommons + config.commons != config.cards_per_pack {
-        return Resp::e422(format!(
-            "Count of rares ({}) + uncommons ({}) + commons ({}) greater than number of cards in pack ({}).",
-            config.rares,
-            config.uncommons,
-            config.commons,
-            config.cards_per_pack
+    if empty {
+        return Err(Resp::basic(
+            "No fields in launch request.",
+            StatusCode::BAD_REQUEST,
         ));
     }
 
-    let Some(list) = list else {
-        return Resp::e422("No card list provided for draft.");
+    validate_config(&config, server_config).map_err(Resp::invalid)?;
+
+    Ok(LaunchForm {
+        config,
+        name,
+        list,
+        exclude,
+        cards,
+        dry_run,
+        strict,
+    })
+}
+
+/// Response to a launch made before the card database has loaded. Card
+/// names can't be resolved until then, so the launch would otherwise fail.
+pub fn loading_response() -> axum::response::Response<String> {
+    Resp::basic(
+        "Card database still loading, try again shortly.",
+        StatusCode::SERVICE_UNAVAILABLE,
+    )
+}
+
+/// Launch a draft from the card list in the form. The owner is the token of
+/// the user launching the draft, if known.
+pub async fn handle_launch_request(
+    carddb: Option<Arc<CardDatabase>>,
+    servers: Servers,
+    server_config: ServerConfig,
+    data: axum::extract::Multipart,
+    owner: Option<String>,
+) -> axum::response::Response<String> {
+    let Some(carddb) = carddb else {
+        return loading_response();
+    };
+
+    let form = match read_launch_form(&server_config, data).await {
+        Ok(form) => form,
+        Err(resp) => return resp,
+    };
+
+    match form_pool(&carddb, &form, server_config.max_pool_size) {
+        Ok(pool) => launch(servers, server_config, form, pool, owner).await,
+        Err(e) => Resp::error(e, StatusCode::UNPROCESSABLE_ENTITY),
+    }
+}
+
+/// Launch a draft from a JSON request body. Launches identically to a form
+/// request, with the configuration given directly.
+pub async fn handle_json_launch_request(
+    carddb: Option<Arc<CardDatabase>>,
+    servers: Servers,
+    server_config: ServerConfig,
+    body: LaunchJson,
+    owner: Option<String>,
+) -> axum::response::Response<String> {
+    let Some(carddb) = carddb else {
+        return loading_response();
     };
 
-    let mut pool = DraftPool::new();
-    for line in list.lines() {
-        let key = &line.trim().to_lowercase();
-        if key.is_empty() {
+    if let Err(e) = validate_config(&body.config, &server_config) {
+        return Resp::invalid(e);
+    }
+
+    let name = match body.name.as_deref().map(str::trim) {
+        None | Some("") => None,
+        Some(name) => match clean_lobby_name(name) {
+            Ok(name) => Some(name),
+            Err(e) => return Resp::invalid(format!("Invalid lobby name: {e}")),
+        },
+    };
+
+    let cards = match body.card_database {
+        Some(xml) if !xml.trim().is_empty() => {
+            match crate::cards::cockatrice::decode_xml_cards(xml.into()) {
+                Ok(db) => Some(db),
+                Err(e) => {
+                    return Resp::error(
+                        DraftError::InvalidCardDatabase(e.to_string()),
+                        StatusCode::UNPROCESSABLE_ENTITY,
+                    )
+                }
+            }
+        }
+        _ => None,
+    };
+
+    let pool = match build_pool(
+        &carddb,
+        cards.as_ref().map(|decoded| &decoded.cards),
+        body.list.iter().map(String::as_str),
+        server_config.max_pool_size,
+    ) {
+        Ok(pool) => pool,
+        Err(e) => return Resp::error(e, StatusCode::UNPROCESSABLE_ENTITY),
+    };
+
+    let form = LaunchForm {
+        config: body.config,
+        name,
+        list: None,
+        exclude: (!body.exclude.is_empty()).then(|| body.exclude.join("\n")),
+        cards,
+        dry_run: body.dry_run,
+        strict: body.strict,
+    };
+    launch(servers, server_config, form, pool, owner).await
+}
+
+/// Most players who can join a table before the packs would contain more
+/// cards than the server allows. Used as the player limit of lobbies launched
+/// without one.
+fn largest_table(config: &DraftConfig, server_config: &ServerConfig) -> usize {
+    let seats = server_config.max_total_cards / config.cards_per_seat().max(1);
+    seats.saturating_sub(config.bots)
+}
+
+/// Warning listing the cards in an uploaded card database which were skipped
+/// because their rarity wasn't recognised, if there were any.
+fn skipped_warning(decoded: &DecodedCards) -> Option<String> {
+    let skipped = &decoded.skipped;
+    if skipped.is_empty() {
+        return None;
+    }
+
+    let mut names = skipped[..skipped.len().min(MAX_SKIPPED_NAMES)].join(", ");
+    if skipped.len() > MAX_SKIPPED_NAMES {
+        names.push_str(", ...");
+    }
+    Some(format!(
+        "Loaded {} cards from the card database, skipped {} with unrecognised rarities: {names}.",
+        decoded.cards.len(),
+        skipped.len()
+    ))
+}
+
+/// Build a pool from a list of card names, looking each up in the custom
+/// cards first, then the card database. Lines may start with a count of
+/// copies, and blank lines are skipped. Lists with more than max_size cards,
+/// counting copies, are rejected.
+pub(super) fn build_pool<'a>(
+    carddb: &CardDatabase,
+    cards: Option<&HashMap<String, Card>>,
+    list: impl Iterator<Item = &'a str>,
+    max_size: usize,
+) -> Res<DraftPool> {
+    let lookup = |name: &str| {
+        let key = &name.to_lowercase();
+        cards
+            .and_then(|ccs| ccs.get(key))
+            .or_else(|| carddb.get(key))
+    };
+
+    let mut resolved = Vec::new();
+    for line in list {
+        let (count, name) = card_entry(line);
+        if name.is_empty() {
             continue;
         }
 
-        let Some(card) = cards
-            .as_ref()
-            .and_then(|ccs| ccs.get(key).cloned())
-            .or_else(|| carddb.get(key).cloned())
+        // What looks like a count may be part of the name, as in "1996 World
+        // Champion", so the whole line is tried too.
+        let Some((count, card)) = lookup(name)
+            .map(|card| (count, card))
+            .or_else(|| lookup(card_name(line)).map(|card| (1, card)))
         else {
-            return Resp::e422(format!("Card not found in custom list or database: {line}"));
+            return Err(DraftError::CardNotFound(line.trim().to_string()));
         };
 
-        pool.add(card);
+        if count > max_size - resolved.len() {
+            return Err(DraftError::CardListTooLarge(max_size));
+        }
+        resolved.extend(std::iter::repeat_n(card, count));
     }
 
-    let id = servers.write().await.spawn(config, pool);
+    let mut pool = DraftPool::new();
+    pool.extend(resolved.into_iter().cloned());
+    Ok(pool)
+}
 
-    Resp::redirect(format!("/lobby/{id}"), "Draft launched.".to_string())
+/// Build a pool from the card list in a launch form, looking cards up in its
+/// card database first.
+fn form_pool(carddb: &CardDatabase, form: &LaunchForm, max_size: usize) -> Res<DraftPool> {
+    let list = form.list.as_deref().ok_or(DraftError::NoCardList)?;
+    build_pool(
+        carddb,
+        form.cards.as_ref().map(|decoded| &decoded.cards),
+        list.lines(),
+        max_size,
+    )
 }
 
-pub async fn handle_websocket_connection(mut ws: WebSocket, server: ServerHandle, seat: Uuid) {
+/// Launch a draft from a cube registered on the server. Any card list in the
+/// form is ignored.
+pub async fn handle_cube_launch_request(
+    cube: &[Card],
+    servers: Servers,
+    server_config: ServerConfig,
+    data: axum::extract::Multipart,
+    owner: Option<String>,
+) -> axum::response::Response<String> {
+    let form = match read_launch_form(&server_config, data).await {
+        Ok(form) => form,
+        Err(resp) => return resp,
+    };
+
+    let mut pool = DraftPool::new();
+    pool.extend(cube.iter().cloned());
+
+    launch(servers, server_config, form, pool, owner).await
+}
+
+/// Label the pool's rarities and choose its images as configured, and remove
+/// any excluded cards from it.
+fn prepare_pool(pool: &mut DraftPool, config: &DraftConfig, exclude: Option<&str>) -> Res<()> {
+    pool.label_rarities(&config.rarity_labels);
+    pool.use_image_quality(config.image_quality);
+
+    if let Some(exclude) = exclude {
+        let unmatched: Vec<&str> = exclude
+            .lines()
+            .map(|line| card_entry(line).1)
+            .filter(|line| !line.is_empty() && pool.remove(line) == 0)
+            .collect();
+        if !unmatched.is_empty() {
+            return Err(DraftError::ExcludedNotFound(
+                unmatched.into_iter().map(String::from).collect(),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Prepare the pool and make the packs a draft of the given number of players
+/// would open, along with any warnings about them. In strict mode, the pool
+/// must be complete for the largest table without substituting rarities, and
+/// every shortage is reported at once.
+fn draft_packs(
+    config: &mut DraftConfig,
+    pool: &mut DraftPool,
+    exclude: Option<&str>,
+    strict: bool,
+    cards: Option<&DecodedCards>,
+    players: usize,
+) -> Res<(Vec<Pack>, Vec<String>)> {
+    prepare_pool(pool, config, exclude)?;
+
+    if strict {
+        config.allow_fallback = false;
+        let largest = config
+            .max_players
+            .unwrap_or(config.min_players)
+            .saturating_add(config.bots);
+        pool.validate(config, largest)?;
+        make_packs(largest, config, pool.clone())?;
+    }
+
+    let mut warnings = pool.validate(config, players)?;
+    let packs = make_packs(players, config, pool.clone())?;
+    warnings.extend(pack_warnings(&packs));
+    if let Some(warning) = cards.and_then(skipped_warning) {
+        warnings.push(warning);
+    }
+    Ok((packs, warnings))
+}
+
+/// Respond with the packs made for a dry run, unless there are too many cards
+/// to send.
+fn dry_run_response(packs: Vec<Pack>, warnings: Vec<String>) -> axum::response::Response<String> {
+    let cards: usize = packs.iter().map(Vec::len).sum();
+    if cards > MAX_DRY_RUN_CARDS {
+        return Resp::invalid(format!(
+            "Dry run would return {cards} cards, more than the maximum ({MAX_DRY_RUN_CARDS})."
+        ));
+    }
+
+    Resp::json(
+        DryRunResp {
+            message: "Packs created.".to_string(),
+            success: true,
+            packs,
+            warnings,
+        },
+        StatusCode::OK,
+    )
+}
+
+/// Preview the packs a single player would open in a draft launched with the
+/// form, without launching it, so that the configuration can be tuned.
+pub async fn handle_preview_request(
+    carddb: Option<Arc<CardDatabase>>,
+    server_config: ServerConfig,
+    data: axum::extract::Multipart,
+) -> axum::response::Response<String> {
+    let Some(carddb) = carddb else {
+        return loading_response();
+    };
+
+    let form = match read_launch_form(&server_config, data).await {
+        Ok(form) => form,
+        Err(resp) => return resp,
+    };
+    let mut pool = match form_pool(&carddb, &form, server_config.max_pool_size) {
+        Ok(pool) => pool,
+        Err(e) => return Resp::error(e, StatusCode::UNPROCESSABLE_ENTITY),
+    };
+
+    let LaunchForm {
+        mut config,
+        exclude,
+        strict,
+        cards,
+        ..
+    } = form;
+    if config.max_players.is_none() {
+        config.max_players = Some(largest_table(&config, &server_config));
+    }
+    match draft_packs(
+        &mut config,
+        &mut pool,
+        exclude.as_deref(),
+        strict,
+        cards.as_ref(),
+        1,
+    ) {
+        Ok((packs, warnings)) => dry_run_response(packs, warnings),
+        Err(e) => Resp::error(e, StatusCode::UNPROCESSABLE_ENTITY),
+    }
+}
+
+/// Remove any excluded cards from the pool and spawn a server for the draft,
+/// unless the owner already has as many lobbies open as they are allowed.
+async fn launch(
+    servers: Servers,
+    server_config: ServerConfig,
+    form: LaunchForm,
+    mut pool: DraftPool,
+    owner: Option<String>,
+) -> axum::response::Response<String> {
+    let LaunchForm {
+        mut config,
+        name,
+        exclude,
+        dry_run,
+        strict,
+        cards,
+        ..
+    } = form;
+
+    // Lobbies without a player limit take as many players as the server
+    // allows, so strict mode checks the pool against that table.
+    if config.max_players.is_none() {
+        config.max_players = Some(largest_table(&config, &server_config));
+    }
+
+    // Check that the pool can supply packs for the smallest possible draft
+    // now, rather than failing when the players are ready.
+    let players = config.min_seats();
+    let (packs, warnings) = match draft_packs(
+        &mut config,
+        &mut pool,
+        exclude.as_deref(),
+        strict,
+        cards.as_ref(),
+        players,
+    ) {
+        Ok(made) => made,
+        Err(e) => return Resp::error(e, StatusCode::UNPROCESSABLE_ENTITY),
+    };
+    if dry_run {
+        return dry_run_response(packs, warnings);
+    }
+
+    let mut servers = servers.write().await;
+    if let Some(owner) = &owner
+        && servers.owned_by(owner) >= server_config.max_lobbies_per_user
+    {
+        return Resp::basic(
+            format!(
+                "Too many open lobbies, at most {} are allowed at once.",
+                server_config.max_lobbies_per_user
+            ),
+            StatusCode::TOO_MANY_REQUESTS,
+        );
+    }
+    config.allow_internal_webhook = server_config.allow_internal_webhooks;
+    let id = servers.spawn(config, pool, name, owner);
+
+    Resp::json(
+        LaunchResp {
+            message: "Draft launched.".to_string(),
+            success: true,
+            lobby: id,
+            warnings,
+        },
+        StatusCode::OK,
+    )
+}
+
+/// Quote a CSV field if it contains a delimiter, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Format players' pools as a CSV file, with one row for each card.
+fn pools_csv(pools: &[SeatPool]) -> String {
+    let mut csv = String::from("seat,player_name,card_name,set,rarity\n");
+    for pool in pools {
+        for card in &pool.cards {
+            csv.push_str(&format!(
+                "{},{},{},{},{:?}\n",
+                pool.seat,
+                csv_field(&pool.name),
+                csv_field(card.name()),
+                csv_field(card.set()),
+                card.rarity
+            ));
+        }
+    }
+    csv
+}
+
+/// Respond with a CSV file of every player's pool, if the draft has finished.
+pub async fn handle_pools_csv_request(server: ServerHandle) -> axum::response::Response<String> {
+    match request_pools(server, StatusCode::CONFLICT).await {
+        Ok(pools) => file_response(pools_csv(&pools), "text/csv"),
+        Err(resp) => resp,
+    }
+}
+
+/// Respond with a seat's pool as a Cockatrice deck file, if the draft has
+/// finished.
+pub async fn handle_pool_cod_request(
+    server: ServerHandle,
+    seat: Uuid,
+) -> axum::response::Response<String> {
+    pool_cod(server, seat, StatusCode::CONFLICT).await
+}
+
+/// Respond with a seat's pool as a Cockatrice deck file, as for
+/// `handle_pool_cod_request`, except that the pool isn't found until the
+/// draft has finished.
+pub async fn handle_pool_cockatrice_request(
+    server: ServerHandle,
+    seat: Uuid,
+) -> axum::response::Response<String> {
+    pool_cod(server, seat, StatusCode::NOT_FOUND).await
+}
+
+/// Respond with a seat's pool as a Cockatrice deck file, or with the given
+/// status if the draft hasn't finished.
+async fn pool_cod(
+    server: ServerHandle,
+    seat: Uuid,
+    unfinished: StatusCode,
+) -> axum::response::Response<String> {
+    let pools = match request_pools(server, unfinished).await {
+        Ok(pools) => pools,
+        Err(resp) => return resp,
+    };
+
+    let Some(pool) = pools.into_iter().find(|pool| pool.seat == seat) else {
+        return Resp::basic("Seat not found.", StatusCode::NOT_FOUND);
+    };
+
+    match crate::cards::cockatrice::encode_deck(&format!("{} draft pool", pool.name), &pool.cards) {
+        Ok(xml) => file_response(xml, "application/xml"),
+        Err(e) => Resp::e500(format!("Failed to encode deck: {e}")),
+    }
+}
+
+/// Format cards as an MTG Arena deck list, with a line for each distinct
+/// card like `2 Lightning Bolt (M10)`. The set code is omitted if unknown.
+fn arena_deck(cards: &[Card]) -> String {
+    let mut entries: Vec<(usize, &Card)> = Vec::new();
+    for card in cards {
+        match entries
+            .iter_mut()
+            .find(|(_, other)| other.name() == card.name() && other.set() == card.set())
+        {
+            Some((count, _)) => *count += 1,
+            None => entries.push((1, card)),
+        }
+    }
+
+    let mut deck = String::new();
+    for (count, card) in entries {
+        if card.set().is_empty() {
+            deck.push_str(&format!("{count} {}\n", card.name()));
+        } else {
+            deck.push_str(&format!(
+                "{count} {} ({})\n",
+                card.name(),
+                card.set().to_uppercase()
+            ));
+        }
+    }
+    deck
+}
+
+/// Respond with a seat's pool as an MTG Arena deck list, if the draft has
+/// finished.
+pub async fn handle_pool_arena_request(
+    server: ServerHandle,
+    seat: Uuid,
+) -> axum::response::Response<String> {
+    let pools = match request_pools(server, StatusCode::CONFLICT).await {
+        Ok(pools) => pools,
+        Err(resp) => return resp,
+    };
+
+    match pools.into_iter().find(|pool| pool.seat == seat) {
+        Some(pool) => file_response(arena_deck(&pool.cards), "text/plain"),
+        None => Resp::basic("Seat not found.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with an SVG image of a seat's pool, if the draft has finished.
+pub async fn handle_pool_svg_request(
+    server: ServerHandle,
+    seat: Uuid,
+) -> axum::response::Response<String> {
+    let pools = match request_pools(server, StatusCode::CONFLICT).await {
+        Ok(pools) => pools,
+        Err(resp) => return resp,
+    };
+
+    match pools.into_iter().find(|pool| pool.seat == seat) {
+        Some(pool) => file_response(pool_svg(&pool), "image/svg+xml"),
+        None => Resp::basic("Seat not found.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Escape text for use in XML content or attribute values.
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Layout of the pool image. Card images are scaled to this size and laid
+/// out in rows beneath a heading with the player's name.
+const SVG_COLUMNS: usize = 8;
+const SVG_CARD_WIDTH: usize = 146;
+const SVG_CARD_HEIGHT: usize = 204;
+const SVG_GAP: usize = 8;
+const SVG_HEADER: usize = 48;
+
+/// Render a player's pool as an SVG image, with each card's image in a grid
+/// under the player's name. The images are linked rather than embedded.
+fn pool_svg(pool: &SeatPool) -> String {
+    let columns = pool.cards.len().clamp(1, SVG_COLUMNS);
+    let rows = pool.cards.len().div_ceil(SVG_COLUMNS);
+    let width = SVG_GAP + columns * (SVG_CARD_WIDTH + SVG_GAP);
+    let height = SVG_HEADER + rows * (SVG_CARD_HEIGHT + SVG_GAP);
+
+    let mut svg = format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\">",
+            "<rect width=\"100%\" height=\"100%\" fill=\"white\"/>",
+            "<text x=\"{gap}\" y=\"32\" font-family=\"sans-serif\" font-size=\"24\">{name}</text>"
+        ),
+        width = width,
+        height = height,
+        gap = SVG_GAP,
+        name = xml_escape(&pool.name)
+    );
+    for (i, card) in pool.cards.iter().enumerate() {
+        let x = SVG_GAP + (i % SVG_COLUMNS) * (SVG_CARD_WIDTH + SVG_GAP);
+        let y = SVG_HEADER + (i / SVG_COLUMNS) * (SVG_CARD_HEIGHT + SVG_GAP);
+        svg.push_str(&format!(
+            "<image href=\"{}\" x=\"{x}\" y=\"{y}\" width=\"{SVG_CARD_WIDTH}\" height=\"{SVG_CARD_HEIGHT}\"><title>{}</title></image>",
+            xml_escape(card.image()),
+            xml_escape(card.name())
+        ));
+    }
+    svg.push_str("</svg>");
+    svg
+}
+
+/// Fetch each player's final pool from the server. On failure, returns the
+/// error response to send, with the given status if the draft hasn't
+/// finished.
+async fn request_pools(
+    server: ServerHandle,
+    unfinished: StatusCode,
+) -> Result<Vec<SeatPool>, axum::response::Response<String>> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::Pools(send));
+
+    match recv.await {
+        Ok(Some(pools)) => Ok(pools),
+        Ok(None) => Err(Resp::basic("Draft has not finished.", unfinished)),
+        Err(_) => Err(Resp::basic("Draft has ended.", StatusCode::NOT_FOUND)),
+    }
+}
+
+/// Response with the body as content of the given type.
+fn file_response(body: String, content_type: &'static str) -> axum::response::Response<String> {
+    let mut resp = axum::response::Response::new(body);
+    resp.headers_mut().insert(
+        axum::http::header::CONTENT_TYPE,
+        axum::http::HeaderValue::from_static(content_type),
+    );
+    resp
+}
+
+/// Respond with the results of the draft: each seat's pool and deck, along
+/// with the config and when the draft ran.
+pub async fn handle_results_request(server: ServerHandle) -> axum::response::Response<String> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::Results(send));
+
+    match recv.await {
+        Ok(Some(results)) => Resp::json(results, StatusCode::OK),
+        Ok(None) => Resp::basic("Draft has not finished.", StatusCode::CONFLICT),
+        Err(_) => Resp::basic("Draft has ended.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with a summary of the state of the lobby, for tools which poll it
+/// rather than connecting.
+pub async fn handle_lobby_status_request(server: ServerHandle) -> axum::response::Response<String> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::Status(send));
+
+    match recv.await {
+        Ok(status) => Resp::json(status, StatusCode::OK),
+        Err(_) => Resp::basic("Draft has ended.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with the status of every open lobby, for a listing of tables
+/// players can join.
+pub async fn handle_lobbies_request(servers: Servers) -> axum::response::Response<String> {
+    let listings = servers.read().await.iter_status();
+    Resp::json(listings.await, StatusCode::OK)
+}
+
+/// Respond with the number of cards of each rarity left to draft and picked,
+/// if the draft is in progress.
+pub async fn handle_rarity_counts_request(
+    server: ServerHandle,
+) -> axum::response::Response<String> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::RarityCounts(send));
+
+    match recv.await {
+        Ok(Some(counts)) => Resp::json(counts, StatusCode::OK),
+        Ok(None) => Resp::basic("Draft is not in progress.", StatusCode::CONFLICT),
+        Err(_) => Resp::basic("Draft has ended.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with every pack opened in the draft, as it was opened, for
+/// auditing.
+pub async fn handle_opened_packs_request(server: ServerHandle) -> axum::response::Response<String> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::OpenedPacks(send));
+
+    match recv.await {
+        Ok(Some(packs)) => Resp::json(packs, StatusCode::OK),
+        Ok(None) => Resp::basic("Draft has not started.", StatusCode::CONFLICT),
+        Err(_) => Resp::basic("Draft has ended.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with the picks made by a seat, with the rest of each pack, for
+/// reviewing the draft once drafting has finished.
+pub async fn handle_pick_log_request(
+    server: ServerHandle,
+    seat: Uuid,
+) -> axum::response::Response<String> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::PickLog(seat, send));
+
+    match recv.await {
+        Ok(Some(Some(log))) => Resp::json(log, StatusCode::OK),
+        Ok(Some(None)) => Resp::basic("No picks found for seat.", StatusCode::NOT_FOUND),
+        Ok(None) => Resp::basic("Draft has not finished.", StatusCode::CONFLICT),
+        Err(_) => Resp::basic("Draft has ended.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Respond with a sample pack generated from the lobby's pool, without using
+/// up any of the cards.
+pub async fn handle_sample_pack_request(server: ServerHandle) -> axum::response::Response<String> {
+    let (send, recv) = tokio::sync::oneshot::channel();
+    server.send(DraftServerRequest::SamplePack(send));
+
+    match recv.await {
+        Ok(Some(Ok(pack))) => Resp::json(pack, StatusCode::OK),
+        Ok(Some(Err(e))) => Resp::error(e, StatusCode::UNPROCESSABLE_ENTITY),
+        Ok(None) => Resp::basic("Draft has already started.", StatusCode::CONFLICT),
+        Err(_) => Resp::basic("Draft has ended.", StatusCode::NOT_FOUND),
+    }
+}
+
+/// Decode messages received from a client, passing each to `forward`. Returns
+/// when the client disconnects, or sends a message which is too large or
+/// exceeds the message rate limit.
+async fn receive_client_messages<S, F>(mut ws_recv: S, config: ServerConfig, forward: F)
+where
+    S: Stream<Item = Result<Message, axum::Error>> + Unpin,
+    F: Fn(ClientMessage),
+{
+    let mut window = Instant::now();
+    let mut count = 0;
+
+    while let Some(Ok(message)) = ws_recv.next().await {
+        let data = match message {
+            Message::Text(text) => text.into_bytes(),
+            Message::Binary(bytes) => bytes,
+            Message::Ping(_) | Message::Pong(_) => continue, // not a message
+            Message::Close(_) => break,                      // client disconnected
+        };
+
+        if data.len() > config.max_message_size {
+            tracing::debug!("Closing connection after oversized message.");
+            break;
+        }
+
+        if window.elapsed() >= Duration::from_secs(1) {
+            window = Instant::now();
+            count = 0;
+        }
+        count += 1;
+        if count > config.max_message_rate {
+            tracing::debug!("Closing connection after exceeding message rate.");
+            break;
+        }
+
+        match serde_json::de::from_slice(&data) {
+            Ok(message) => forward(message),
+            Err(e) => tracing::debug!("Failed to decode client message: {e}"),
+        };
+    }
+}
+
+pub async fn handle_websocket_connection(
+    mut ws: WebSocket,
+    server: ServerHandle,
+    seat: Uuid,
+    connect: ConnectRequest,
+    config: ServerConfig,
+) {
     // Test sending a ping to validate the connection.
     if ws
         .send(Message::Ping("ping".as_bytes().to_owned()))
@@ -153,7 +1460,7 @@ pub async fn handle_websocket_connection(mut ws: WebSocket, server: ServerHandle
 
     // Attempt to send channel to server to allow server to message client.
     let (send, mut recv) = tokio::sync::mpsc::unbounded_channel();
-    server.send(DraftServerRequest::Connect(seat, send));
+    server.send(connect(seat, send));
 
     // Split the websocket. The send half will handle encoding messages from the
     // server and forwarding them to the client, while the receive half will
@@ -176,19 +1483,10 @@ pub async fn handle_websocket_connection(mut ws: WebSocket, server: ServerHandle
 
     let handle = server.clone();
     let mut recv_task = tokio::spawn(async move {
-        while let Some(Ok(message)) = ws_recv.next().await {
-            let msg = match message {
-                Message::Text(text) => serde_json::de::from_str(&text),
-                Message::Binary(bytes) => serde_json::de::from_slice(&bytes),
-                Message::Ping(_) | Message::Pong(_) => continue, // not a message
-                Message::Close(_) => break,                      // client disconnected
-            };
-
-            match msg {
-                Ok(message) => handle.send(DraftServerRequest::Message(seat, message)),
-                Err(e) => tracing::debug!("Failed to decode client message: {e}"),
-            };
-        }
+        receive_client_messages(&mut ws_recv, config, |message| {
+            handle.send(DraftServerRequest::Message(seat, message))
+        })
+        .await
     });
 
     // When either task completes, abort the other.
@@ -202,3 +1500,1055 @@ pub async fn handle_websocket_connection(mut ws: WebSocket, server: ServerHandle
         ClientMessage::Disconnected,
     ));
 }
+
+#[cfg(test)]
+mod test {
+    use std::{cell::RefCell, sync::Arc};
+
+    use axum::{
+        body::Body,
+        extract::{ws::Message, FromRequest, Multipart},
+        http::{Request, StatusCode},
+    };
+    use tokio::sync::RwLock;
+    use uuid::Uuid;
+
+    use crate::{
+        cards::{Card, CardDatabase, Rarity},
+        draft::{
+            server::{ClientMessage, SeatPool, ServerPool},
+            DraftConfig,
+        },
+        error::DraftError,
+        ServerConfig,
+    };
+
+    use super::{
+        arena_deck, build_pool, csv_field, handle_cube_launch_request, handle_json_launch_request,
+        handle_launch_request, handle_pool_cockatrice_request, handle_pool_cod_request,
+        handle_pools_csv_request, handle_preview_request, largest_table, parse_rate, pool_svg,
+        pools_csv, receive_client_messages, xml_escape,
+    };
+
+    const BOUNDARY: &str = "drafttoolboundary";
+
+    /// Build a multipart form containing the provided fields.
+    async fn multipart(fields: &[(&str, &str)]) -> Multipart {
+        let mut body = String::new();
+        for (name, value) in fields {
+            body.push_str(&format!(
+                "--{BOUNDARY}\r\nContent-Disposition: form-data; name=\"{name}\"\r\n\r\n{value}\r\n"
+            ));
+        }
+        body.push_str(&format!("--{BOUNDARY}--\r\n"));
+
+        let request = Request::builder()
+            .method("POST")
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::from(body))
+            .unwrap();
+        Multipart::from_request(request, &()).await.unwrap()
+    }
+
+    /// Card database containing the given number of sample cards of each
+    /// rarity, and a list including all of them.
+    fn sample_cards(rares: usize, uncommons: usize, commons: usize) -> (CardDatabase, String) {
+        let mut db = CardDatabase::new();
+        let mut list = String::new();
+        for (rarity, n) in [
+            (Rarity::Rare, rares),
+            (Rarity::Uncommon, uncommons),
+            (Rarity::Common, commons),
+        ] {
+            for _ in 0..n {
+                let card = Card::sample(rarity);
+                list.push_str(card.name());
+                list.push('\n');
+                db.add(card);
+            }
+        }
+        (db, list)
+    }
+
+    /// Submit a launch request with the given fields, returning the status.
+    async fn launch(db: CardDatabase, fields: &[(&str, &str)]) -> StatusCode {
+        launch_with(db, ServerConfig::default(), fields).await
+    }
+
+    async fn launch_with(
+        db: CardDatabase,
+        config: ServerConfig,
+        fields: &[(&str, &str)],
+    ) -> StatusCode {
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        handle_launch_request(
+            Some(Arc::new(db)),
+            servers,
+            config,
+            multipart(fields).await,
+            None,
+        )
+        .await
+        .status()
+    }
+
+    #[tokio::test]
+    async fn test_launch() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "1"),
+            ("cards_per_pack", "3"),
+            ("unique_cards", "checked"),
+            ("use_rarities", "checked"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "1"),
+            ("min_players", "2"),
+        ];
+        assert_eq!(launch(db, &fields).await, StatusCode::OK);
+    }
+
+    #[test]
+    fn test_parse_rate() {
+        assert_eq!(parse_rate("0.125"), Some(0.125));
+        assert_eq!(parse_rate("1"), Some(1.0));
+        assert_eq!(parse_rate("0"), Some(0.0));
+        assert_eq!(parse_rate("12.5%"), Some(0.125));
+        assert_eq!(parse_rate(" 12.5 % "), Some(0.125));
+        assert_eq!(parse_rate("12.5"), Some(0.125));
+        assert_eq!(parse_rate("0.5%"), Some(0.005));
+        assert_eq!(parse_rate("125"), None);
+        assert_eq!(parse_rate("125%"), None);
+        assert_eq!(parse_rate("-0.1"), None);
+        assert_eq!(parse_rate("NaN"), None);
+        assert_eq!(parse_rate("often"), None);
+        assert_eq!(parse_rate("%"), None);
+    }
+
+    #[test]
+    fn test_build_large_pool() {
+        // A 540 card cube, listed several times over.
+        let (db, list) = sample_cards(180, 180, 180);
+        let names: Vec<&str> = list.lines().collect();
+        let lines = names.iter().cycle().take(names.len() * 30).copied();
+        let mut pool = build_pool(&db, None, lines, 20_000).unwrap();
+        assert!(names.iter().all(|name| pool.remove(name) == 30));
+
+        let lines = names.iter().cycle().take(20_001).copied();
+        assert!(build_pool(&db, None, lines, 20_000).is_err());
+    }
+
+    #[test]
+    fn test_build_pool_export_lines() {
+        let (db, list) = sample_cards(1, 1, 1);
+        let names: Vec<&str> = list.lines().collect();
+        let lines = [
+            format!("{} (TST) 1", names[0]),
+            format!("{} (TST)", names[1]),
+            format!("{} (TST) 3 *F*", names[2]),
+        ];
+        let mut pool = build_pool(&db, None, lines.iter().map(String::as_str), 100).unwrap();
+        assert!(names.iter().all(|name| pool.remove(name) == 1));
+
+        // Counts before each card, as in Arena and Moxfield exports, give
+        // that many copies.
+        let lines = [
+            format!("1 {} (TST) 1", names[0]),
+            format!("3 {} (TST) 2", names[1]),
+            format!("2x {}", names[2]),
+        ];
+        let mut pool = build_pool(&db, None, lines.iter().map(String::as_str), 100).unwrap();
+        assert_eq!(pool.remove(names[0]), 1);
+        assert_eq!(pool.remove(names[1]), 3);
+        assert_eq!(pool.remove(names[2]), 2);
+
+        // Copies count towards the size limit.
+        assert_eq!(
+            build_pool(&db, None, lines.iter().map(String::as_str), 5).unwrap_err(),
+            DraftError::CardListTooLarge(5)
+        );
+
+        // A name beginning with a number is found as written.
+        let mut db = CardDatabase::new();
+        db.add(Card::new(
+            "1996 World Champion".to_string(),
+            String::new(),
+            "PCEL".to_string(),
+            String::new(),
+            Rarity::Rare,
+            Vec::new(),
+        ));
+        let mut pool = build_pool(&db, None, ["1996 World Champion"].into_iter(), 100).unwrap();
+        assert_eq!(pool.remove("1996 World Champion"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_launch_while_loading() {
+        let (_, list) = sample_cards(2, 2, 2);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "1"),
+            ("cards_per_pack", "3"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "1"),
+        ];
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let resp = handle_launch_request(
+            None,
+            servers.clone(),
+            config.clone(),
+            multipart(&fields).await,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        let body = serde_json::from_value(serde_json::json!({
+            "config": {"rounds": 1, "cards_per_pack": 3},
+            "list": list.lines().collect::<Vec<_>>(),
+        }))
+        .unwrap();
+        let resp = handle_json_launch_request(None, servers, config, body, None).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_json_launch() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let db = Some(Arc::new(db));
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let body = |rares: usize| {
+            serde_json::from_value(serde_json::json!({
+                "config": {
+                    "rounds": 1,
+                    "cards_per_pack": 3,
+                    "rares": rares,
+                    "uncommons": 1,
+                    "commons": 1,
+                    "min_players": 2,
+                    "pass_model": "linear",
+                    "first_direction": "right",
+                    "min_pick_delay": 500,
+                },
+                "name": "Scripted",
+                "list": list.lines().collect::<Vec<_>>(),
+            }))
+            .unwrap()
+        };
+
+        let resp =
+            handle_json_launch_request(db.clone(), servers.clone(), config.clone(), body(1), None)
+                .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        let lobby = resp["lobby"].as_str().unwrap().parse().unwrap();
+        assert!(servers.read().await.handle(lobby).is_some());
+
+        // The configuration is validated as for a form.
+        let resp = handle_json_launch_request(db, servers, config, body(2), None).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let resp: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        assert_eq!(resp["error"]["kind"], "invalid_config");
+    }
+
+    #[tokio::test]
+    async fn test_launch_rarity_short() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+
+        // Without fallback, a pool too small for the minimum number of
+        // players is rejected, naming the rarity which is short.
+        let body = serde_json::from_value(serde_json::json!({
+            "config": {
+                "rounds": 1,
+                "cards_per_pack": 3,
+                "rares": 2,
+                "uncommons": 1,
+                "commons": 0,
+                "min_players": 2,
+                "unique_cards": true,
+                "allow_fallback": false,
+            },
+            "list": list.lines().collect::<Vec<_>>(),
+        }))
+        .unwrap();
+        let resp =
+            handle_json_launch_request(Some(Arc::new(db)), servers, config, body, None).await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let resp: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        assert!(resp["message"]
+            .as_str()
+            .unwrap()
+            .contains("needed 2 more rares to complete 2 packs for 2 players"));
+        assert_eq!(resp["error"]["kind"], "pool_insufficient");
+        assert_eq!(resp["error"]["rarity"], "Rare");
+        assert_eq!(resp["error"]["needed"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_launch_malformed() {
+        let (db, _) = sample_cards(2, 2, 2);
+
+        // No fields at all is a bad request.
+        assert_eq!(
+            launch(CardDatabase::new(), &[]).await,
+            StatusCode::BAD_REQUEST
+        );
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let request = Request::builder()
+            .method("POST")
+            .header(
+                "Content-Type",
+                format!("multipart/form-data; boundary={BOUNDARY}"),
+            )
+            .body(Body::empty())
+            .unwrap();
+        let data = Multipart::from_request(request, &()).await.unwrap();
+        let resp = handle_launch_request(
+            Some(Arc::new(CardDatabase::new())),
+            servers,
+            config,
+            data,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        // Missing only the list is unprocessable.
+        assert_eq!(
+            launch(db, &[("packs", "1")]).await,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[tokio::test]
+    async fn test_launch_warnings() {
+        let (db, list) = sample_cards(0, 0, 2);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "1"),
+            ("cards_per_pack", "1"),
+            ("unique_cards", "checked"),
+            ("use_rarities", "checked"),
+            ("rares", "0"),
+            ("uncommons", "0"),
+            ("commons", "1"),
+            ("min_players", "2"),
+        ];
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            ServerConfig::default().lobby_timeout,
+            ServerConfig::default().lobby_idle_expiry,
+        )));
+        let resp = handle_launch_request(
+            Some(Arc::new(db)),
+            servers,
+            ServerConfig::default(),
+            multipart(&fields).await,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        assert!(body["lobby"].is_string());
+        assert_eq!(body["warnings"][0], "2 of 2 packs contain no rares.");
+    }
+
+    #[tokio::test]
+    async fn test_launch_limits() {
+        let config = ServerConfig {
+            max_rounds: 2,
+            max_cards_per_pack: 2,
+            ..Default::default()
+        };
+
+        for (packs, cards_per_pack, status) in [
+            ("2", "2", StatusCode::OK),
+            ("3", "2", StatusCode::UNPROCESSABLE_ENTITY),
+            ("2", "3", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (db, list) = sample_cards(0, 0, 4);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", packs),
+                ("cards_per_pack", cards_per_pack),
+                ("rares", "0"),
+                ("uncommons", "0"),
+                ("commons", cards_per_pack),
+            ];
+            assert_eq!(launch_with(db, config.clone(), &fields).await, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_size_ceilings() {
+        let config = ServerConfig {
+            max_pool_size: 4,
+            max_total_cards: 8,
+            ..Default::default()
+        };
+
+        for (cards, min_players, max_players, status) in [
+            (4, "2", "", StatusCode::OK),
+            (5, "2", "", StatusCode::UNPROCESSABLE_ENTITY),
+            (4, "3", "", StatusCode::UNPROCESSABLE_ENTITY),
+            (4, "2", "2", StatusCode::OK),
+            (4, "2", "3", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (db, list) = sample_cards(0, 0, cards);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", "2"),
+                ("cards_per_pack", "2"),
+                ("rares", "0"),
+                ("uncommons", "0"),
+                ("commons", "2"),
+                ("min_players", min_players),
+                ("max_players", max_players),
+            ];
+            assert_eq!(launch_with(db, config.clone(), &fields).await, status);
+        }
+
+        // Without a player limit, the table is limited to the largest which
+        // stays within the ceiling.
+        let draft = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 2,
+            bots: 1,
+            ..Default::default()
+        };
+        assert_eq!(largest_table(&draft, &config), 1);
+    }
+
+    #[tokio::test]
+    async fn test_launch_pack_sizes() {
+        for (pack_sizes, status) in [
+            ("3, 3, 2", StatusCode::OK),
+            ("3, 3", StatusCode::UNPROCESSABLE_ENTITY),
+            ("3, 0, 2", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (db, list) = sample_cards(3, 3, 3);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", "3"),
+                ("pack_sizes", pack_sizes),
+                ("cards_per_pack", "3"),
+                ("rares", "1"),
+                ("uncommons", "1"),
+                ("commons", "1"),
+            ];
+            assert_eq!(launch(db, &fields).await, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_basics_per_colour() {
+        for (basics, status) in [
+            ("20", StatusCode::OK),
+            ("0", StatusCode::UNPROCESSABLE_ENTITY),
+            ("1000000000", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (db, list) = sample_cards(3, 3, 3);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", "1"),
+                ("cards_per_pack", "3"),
+                ("rares", "1"),
+                ("uncommons", "1"),
+                ("commons", "1"),
+                ("include_basics", "checked"),
+                ("basics_per_colour", basics),
+            ];
+            assert_eq!(launch(db, &fields).await, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_internal_webhook() {
+        for (allow_internal_webhooks, status) in [
+            (false, StatusCode::UNPROCESSABLE_ENTITY),
+            (true, StatusCode::OK),
+        ] {
+            let config = ServerConfig {
+                allow_internal_webhooks,
+                ..Default::default()
+            };
+            let (db, list) = sample_cards(1, 1, 1);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", "1"),
+                ("cards_per_pack", "3"),
+                ("rares", "1"),
+                ("uncommons", "1"),
+                ("commons", "1"),
+                ("webhook_url", "http://169.254.169.254/hook"),
+            ];
+            assert_eq!(launch_with(db, config, &fields).await, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_rarity_labels() {
+        for (labels, status) in [
+            ("Legendary, Epic, Rare, Common", StatusCode::OK),
+            ("Epic, Rare, Common", StatusCode::UNPROCESSABLE_ENTITY),
+            (
+                "Legendary, , Rare, Common",
+                StatusCode::UNPROCESSABLE_ENTITY,
+            ),
+        ] {
+            let (db, list) = sample_cards(1, 1, 1);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", "1"),
+                ("cards_per_pack", "3"),
+                ("rares", "1"),
+                ("uncommons", "1"),
+                ("commons", "1"),
+                ("rarity_labels", labels),
+            ];
+            assert_eq!(launch(db, &fields).await, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_launch_slot_distribution() {
+        for (cards_per_pack, slots, status) in [
+            ("4", "0, 5, 25, 70", StatusCode::OK),
+            ("5", "0, 5, 25, 70; 1, 1, 0, 0", StatusCode::OK),
+            ("3", "0, 5, 25, 70", StatusCode::UNPROCESSABLE_ENTITY),
+            ("4", "0, 5, 25", StatusCode::UNPROCESSABLE_ENTITY),
+            ("4", "0, 0, 0, 0", StatusCode::UNPROCESSABLE_ENTITY),
+            ("4", "0, -5, 25, 70", StatusCode::UNPROCESSABLE_ENTITY),
+        ] {
+            let (db, list) = sample_cards(3, 3, 3);
+            let fields = [
+                ("list", list.as_str()),
+                ("packs", "1"),
+                ("cards_per_pack", cards_per_pack),
+                ("use_rarities", "checked"),
+                ("rares", "1"),
+                ("uncommons", "1"),
+                ("commons", "1"),
+                ("slot_distribution", slots),
+            ];
+            assert_eq!(launch(db, &fields).await, status);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cube_launch() {
+        let cube: Vec<Card> = (0..3).map(|_| Card::sample(Rarity::Common)).collect();
+        let fields = [
+            ("packs", "1"),
+            ("cards_per_pack", "1"),
+            ("unique_cards", "checked"),
+            ("rares", "0"),
+            ("uncommons", "0"),
+            ("commons", "1"),
+            ("min_players", "3"),
+        ];
+
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let resp = handle_cube_launch_request(
+            &cube,
+            servers.clone(),
+            config.clone(),
+            multipart(&fields).await,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // Too few cards in the cube for four players.
+        let fields = [
+            ("packs", "1"),
+            ("cards_per_pack", "1"),
+            ("unique_cards", "checked"),
+            ("rares", "0"),
+            ("uncommons", "0"),
+            ("commons", "1"),
+            ("min_players", "4"),
+        ];
+        let resp =
+            handle_cube_launch_request(&cube, servers, config, multipart(&fields).await, None)
+                .await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_strict_launch() {
+        let cube: Vec<Card> = [
+            (Rarity::Rare, 4),
+            (Rarity::Uncommon, 6),
+            (Rarity::Common, 8),
+        ]
+        .into_iter()
+        .flat_map(|(rarity, n)| (0..n).map(move |_| Card::sample(rarity)))
+        .collect();
+        let fields = |max_players| {
+            [
+                ("packs", "1"),
+                ("cards_per_pack", "4"),
+                ("unique_cards", "checked"),
+                ("use_rarities", "checked"),
+                ("rares", "1"),
+                ("uncommons", "1"),
+                ("commons", "2"),
+                ("max_players", max_players),
+                ("strict", "checked"),
+            ]
+        };
+
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+
+        // The cube is complete for four players.
+        let resp = handle_cube_launch_request(
+            &cube,
+            servers.clone(),
+            config.clone(),
+            multipart(&fields("4")).await,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        // But not for eight, even though forms allow fallback by default, and
+        // every rarity which is short is reported.
+        let resp =
+            handle_cube_launch_request(&cube, servers, config, multipart(&fields("8")).await, None)
+                .await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        assert_eq!(body["error"]["kind"], "pool_incomplete");
+        let shortfalls = body["error"]["shortfalls"].as_array().unwrap();
+        assert_eq!(
+            shortfalls
+                .iter()
+                .map(|s| (s["rarity"].as_str().unwrap(), s["needed"].as_u64().unwrap()))
+                .collect::<Vec<_>>(),
+            [("Rare", 4), ("Uncommon", 2), ("Common", 8)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_strict_launch_without_max_players() {
+        let cube: Vec<Card> = [
+            (Rarity::Rare, 4),
+            (Rarity::Uncommon, 4),
+            (Rarity::Common, 8),
+        ]
+        .into_iter()
+        .flat_map(|(rarity, n)| (0..n).map(move |_| Card::sample(rarity)))
+        .collect();
+        let fields = [
+            ("packs", "1"),
+            ("cards_per_pack", "4"),
+            ("unique_cards", "checked"),
+            ("use_rarities", "checked"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "2"),
+            ("strict", "checked"),
+        ];
+        let launch_with_ceiling = |max_total_cards| {
+            let cube = &cube;
+            async move {
+                let config = ServerConfig {
+                    max_total_cards,
+                    ..Default::default()
+                };
+                let servers = Arc::new(RwLock::new(ServerPool::new(
+                    config.lobby_timeout,
+                    config.lobby_idle_expiry,
+                )));
+                handle_cube_launch_request(cube, servers, config, multipart(&fields).await, None)
+                    .await
+                    .status()
+            }
+        };
+
+        // Without a player limit, the pool must be complete for the largest
+        // table the server allows: four players here, but not eight.
+        assert_eq!(launch_with_ceiling(16).await, StatusCode::OK);
+        assert_eq!(
+            launch_with_ceiling(32).await,
+            StatusCode::UNPROCESSABLE_ENTITY
+        );
+    }
+
+    #[tokio::test]
+    async fn test_launch_error_kinds() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let db = Arc::new(db);
+        let error = |fields: Vec<(&'static str, String)>| {
+            let db = db.clone();
+            async move {
+                let config = ServerConfig::default();
+                let servers = Arc::new(RwLock::new(ServerPool::new(
+                    config.lobby_timeout,
+                    config.lobby_idle_expiry,
+                )));
+                let fields: Vec<(&str, &str)> =
+                    fields.iter().map(|(k, v)| (*k, v.as_str())).collect();
+                let resp = handle_launch_request(
+                    Some(db),
+                    servers,
+                    config,
+                    multipart(&fields).await,
+                    None,
+                )
+                .await;
+                assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+                let body: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+                body["error"].clone()
+            }
+        };
+
+        let e = error(vec![("packs", "1".to_string())]).await;
+        assert_eq!(e["kind"], "no_card_list");
+
+        let e = error(vec![("list", format!("{list}Not A Card\n"))]).await;
+        assert_eq!(e["kind"], "card_not_found");
+        assert_eq!(e["card"], "Not A Card");
+
+        let e = error(vec![
+            ("list", list.clone()),
+            ("exclude", "Missing Card".to_string()),
+        ])
+        .await;
+        assert_eq!(e["kind"], "excluded_not_found");
+        assert_eq!(e["cards"], serde_json::json!(["Missing Card"]));
+
+        let e = error(vec![
+            ("list", list.clone()),
+            ("card_database", "<not xml".to_string()),
+        ])
+        .await;
+        assert_eq!(e["kind"], "invalid_card_database");
+    }
+
+    #[test]
+    fn test_pools_csv() {
+        assert_eq!(csv_field("Plain"), "Plain");
+        assert_eq!(
+            csv_field("Jace, the Mind Sculptor"),
+            "\"Jace, the Mind Sculptor\""
+        );
+        assert_eq!(csv_field("The \"Best\" Card"), "\"The \"\"Best\"\" Card\"");
+
+        let card = Card::sample(Rarity::Uncommon);
+        let seat = uuid::Uuid::new_v4();
+        let csv = pools_csv(&[SeatPool {
+            seat,
+            name: "Owen, Esq.".to_string(),
+            cards: vec![card.clone()],
+        }]);
+        assert_eq!(
+            csv,
+            format!(
+                "seat,player_name,card_name,set,rarity\n{seat},\"Owen, Esq.\",{},{},Uncommon\n",
+                csv_field(card.name()),
+                csv_field(card.set())
+            )
+        );
+    }
+
+    #[test]
+    fn test_arena_deck() {
+        let bolt = Card::sample(Rarity::Common);
+        let other = Card::sample(Rarity::Rare);
+        assert_eq!(
+            arena_deck(&[bolt.clone(), other.clone(), bolt.clone()]),
+            format!("2 {} (TST)\n1 {} (TST)\n", bolt.name(), other.name())
+        );
+    }
+
+    #[test]
+    fn test_pool_svg() {
+        assert_eq!(
+            xml_escape("<Owen & \"co\">"),
+            "&lt;Owen &amp; &quot;co&quot;&gt;"
+        );
+
+        let cards: Vec<Card> = (0..10).map(|_| Card::sample(Rarity::Common)).collect();
+        let svg = pool_svg(&SeatPool {
+            seat: uuid::Uuid::new_v4(),
+            name: "Owen & co".to_string(),
+            cards: cards.clone(),
+        });
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(">Owen &amp; co</text>"));
+        assert_eq!(svg.matches("<image ").count(), 10);
+        assert!(svg.contains(&format!("href=\"{}\"", cards[9].image())));
+
+        // Eight cards to a row, so ten cards take two rows.
+        assert!(svg.contains("width=\"1240\" height=\"472\""));
+    }
+
+    #[tokio::test]
+    async fn test_pools_before_finish() {
+        let cube: Vec<Card> = (0..3).map(|_| Card::sample(Rarity::Common)).collect();
+        let fields = [
+            ("packs", "1"),
+            ("cards_per_pack", "1"),
+            ("unique_cards", "checked"),
+            ("rares", "0"),
+            ("uncommons", "0"),
+            ("commons", "1"),
+        ];
+
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let resp = handle_cube_launch_request(
+            &cube,
+            servers.clone(),
+            config,
+            multipart(&fields).await,
+            None,
+        )
+        .await;
+        let body: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        let id = body["lobby"].as_str().unwrap().parse().unwrap();
+
+        let server = servers.read().await.handle(id).unwrap();
+        let resp = handle_pools_csv_request(server.clone()).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+
+        // The Cockatrice export reports the pool as not found instead.
+        let seat = Uuid::new_v4();
+        let resp = handle_pool_cod_request(server.clone(), seat).await;
+        assert_eq!(resp.status(), StatusCode::CONFLICT);
+        let resp = handle_pool_cockatrice_request(server, seat).await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_launch_dry_run() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "1"),
+            ("cards_per_pack", "3"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "1"),
+            ("min_players", "2"),
+            ("use_rarities", "checked"),
+            ("unique_cards", "checked"),
+            ("dry_run", "checked"),
+        ];
+
+        let config = ServerConfig::default();
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let resp = handle_launch_request(
+            Some(Arc::new(db)),
+            servers,
+            config,
+            multipart(&fields).await,
+            None,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        assert!(body.get("lobby").is_none());
+        let packs = body["packs"].as_array().unwrap();
+        assert_eq!(packs.len(), 2);
+        assert!(packs.iter().all(|p| p.as_array().unwrap().len() == 3));
+
+        // Validated the same as a real launch.
+        let (db, list) = sample_cards(1, 1, 1);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "1"),
+            ("cards_per_pack", "3"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "1"),
+            ("min_players", "2"),
+            ("use_rarities", "checked"),
+            ("unique_cards", "checked"),
+            ("dry_run", "checked"),
+        ];
+        assert_eq!(launch(db, &fields).await, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_preview() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "2"),
+            ("cards_per_pack", "3"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "1"),
+            ("use_rarities", "checked"),
+            ("unique_cards", "checked"),
+        ];
+        let resp = handle_preview_request(
+            Some(Arc::new(db)),
+            ServerConfig::default(),
+            multipart(&fields).await,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(resp.body()).unwrap();
+        let packs = body["packs"].as_array().unwrap();
+        assert_eq!(packs.len(), 2);
+        assert!(packs.iter().all(|p| p.as_array().unwrap().len() == 3));
+
+        // Strict previews check the pool against the largest table.
+        let (db, list) = sample_cards(2, 2, 2);
+        let mut strict = fields.to_vec();
+        strict[0] = ("list", list.as_str());
+        strict.extend([("strict", "checked"), ("max_players", "2")]);
+        let resp = handle_preview_request(
+            Some(Arc::new(db)),
+            ServerConfig::default(),
+            multipart(&strict).await,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        // Not enough cards for even a single player.
+        let (db, list) = sample_cards(1, 1, 1);
+        let mut fields = fields;
+        fields[0] = ("list", list.as_str());
+        let resp = handle_preview_request(
+            Some(Arc::new(db)),
+            ServerConfig::default(),
+            multipart(&fields).await,
+        )
+        .await;
+        assert_eq!(resp.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_lobbies_per_user() {
+        let config = ServerConfig {
+            max_lobbies_per_user: 2,
+            ..Default::default()
+        };
+        let servers = Arc::new(RwLock::new(ServerPool::new(
+            config.lobby_timeout,
+            config.lobby_idle_expiry,
+        )));
+        let launch_as = |owner: &str| {
+            let (db, list) = sample_cards(1, 1, 1);
+            let servers = servers.clone();
+            let config = config.clone();
+            let owner = owner.to_string();
+            async move {
+                let fields = [
+                    ("list", list.as_str()),
+                    ("packs", "1"),
+                    ("cards_per_pack", "3"),
+                    ("rares", "1"),
+                    ("uncommons", "1"),
+                    ("commons", "1"),
+                ];
+                let data = multipart(&fields).await;
+                handle_launch_request(Some(Arc::new(db)), servers, config, data, Some(owner))
+                    .await
+                    .status()
+            }
+        };
+
+        assert_eq!(launch_as("owen").await, StatusCode::OK);
+        assert_eq!(launch_as("owen").await, StatusCode::OK);
+        assert_eq!(launch_as("owen").await, StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(launch_as("other").await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_launch_pool_too_small() {
+        let (db, list) = sample_cards(2, 2, 2);
+        let fields = [
+            ("list", list.as_str()),
+            ("packs", "1"),
+            ("cards_per_pack", "3"),
+            ("unique_cards", "checked"),
+            ("use_rarities", "checked"),
+            ("rares", "1"),
+            ("uncommons", "1"),
+            ("commons", "1"),
+            ("min_players", "3"),
+        ];
+        assert_eq!(launch(db, &fields).await, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    /// Feed the messages to `receive_client_messages`, returning the decoded
+    /// messages which would be sent on to the server.
+    async fn receive(messages: Vec<Message>, config: ServerConfig) -> Vec<ClientMessage> {
+        let received = RefCell::new(Vec::new());
+        let stream = futures_util::stream::iter(messages.into_iter().map(Ok));
+        receive_client_messages(stream, config, |m| received.borrow_mut().push(m)).await;
+        received.into_inner()
+    }
+
+    fn heartbeat() -> Message {
+        Message::Text(r#"{"type":"HeartBeat"}"#.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_oversized_message_closes() {
+        let config = ServerConfig {
+            max_message_size: 64,
+            ..Default::default()
+        };
+        let huge = Message::Binary(vec![b' '; config.max_message_size + 1]);
+
+        // Messages after the oversized message are never decoded.
+        let received = receive(vec![heartbeat(), huge, heartbeat()], config).await;
+        assert_eq!(received.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_message_rate_limit() {
+        let config = ServerConfig {
+            max_message_rate: 3,
+            ..Default::default()
+        };
+        let received = receive(vec![heartbeat(); 10], config).await;
+        assert_eq!(received.len(), 3);
+    }
+}
@@ -1,11 +1,99 @@
-use std::fmt::Debug;
+use std::{collections::HashMap, fmt::Debug, time::Duration};
+
+use crate::cards::{ImageQuality, Rarity};
 
 mod game;
 pub mod handlers;
 mod packs;
 pub mod server;
+pub mod simulate;
+mod webhook;
+
+/// Heuristic used to choose a card when a pick is made on behalf of a player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum AutoPick {
+    /// Take the first card in the pack.
+    First,
+
+    /// Take a card at random.
+    Random,
+
+    /// Take the card of the highest rarity in the pack.
+    HighestRarity,
+
+    /// Take the card sharing the most colours with the player's picks so far.
+    OnColour,
+}
+
+/// Format of the draft.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum DraftMode {
+    /// Players open packs and pass them around the table.
+    Booster,
+
+    /// Two or three players take turns to take or pass piles fed from a
+    /// shared deck, made up of the cards the packs would have held.
+    Winston,
+
+    /// Two players take turns to take a row or column of a grid of nine cards
+    /// dealt from a shared deck, as for a Winston draft.
+    Grid,
+
+    /// Packs are opened one at a time, face up, and players take turns to
+    /// pick from them.
+    Rochester,
+}
+
+/// Direction packs are passed around the table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PassDirection {
+    #[default]
+    Left,
+    Right,
+}
+
+impl PassDirection {
+    fn reverse(self) -> Self {
+        match self {
+            PassDirection::Left => PassDirection::Right,
+            PassDirection::Right => PassDirection::Left,
+        }
+    }
+}
+
+/// How the direction packs are passed changes between rounds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+enum PassModel {
+    /// Alternate direction each round, starting to the left.
+    Snake,
+
+    /// Always pass to the left.
+    Linear,
 
-#[derive(Debug)]
+    /// Choose a direction at random each round.
+    Random,
+
+    /// Pass each pack to a seat chosen at random, every pick. Every seat
+    /// still makes the same number of picks each round.
+    RandomSeat,
+}
+
+/// A pack slot filled with a card of a rarity chosen at random, such as a
+/// wildcard slot which is usually a common but sometimes something rarer.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, serde::Serialize)]
+struct RaritySlot {
+    /// Relative chance of the slot being of each rarity.
+    weights: HashMap<Rarity, f32>,
+}
+
+/// Options for a draft. Missing fields take their default values when
+/// deserialised.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+#[serde(default)]
 struct DraftConfig {
     /// Number of packs in the draft.
     rounds: usize,
@@ -13,6 +101,10 @@ struct DraftConfig {
     /// Number of cards in each pack.
     cards_per_pack: usize,
 
+    /// Number of cards in each pack for each round, if this varies between
+    /// rounds. Overrides cards_per_pack when set.
+    pack_sizes: Option<Vec<usize>>,
+
     /// Whether to choose cards with replacement (false) or not (true).
     unique_cards: bool,
 
@@ -25,6 +117,14 @@ struct DraftConfig {
     /// Rate at which a rare is upgraded to a mythic rare.
     mythic_rate: f32,
 
+    /// Whether the draft's share of mythics is spread evenly over the packs,
+    /// rather than upgrading each rare at random.
+    identical_rarities: bool,
+
+    /// Number of dedicated mythic slots in each pack. If any are configured,
+    /// rares are no longer upgraded to mythics.
+    mythics: usize,
+
     /// Number of rares in each pack.
     rares: usize,
 
@@ -33,6 +133,155 @@ struct DraftConfig {
 
     /// Number of commons in each pack.
     commons: usize,
+
+    /// Slots in each pack whose rarity is chosen at random, in addition to
+    /// the fixed mythic, rare, uncommon and common slots.
+    slot_distribution: Vec<RaritySlot>,
+
+    /// Which image of each card to show, where there is a choice.
+    image_quality: ImageQuality,
+
+    /// Number of extra foil cards in each pack, and the relative chance of a
+    /// foil being of each rarity.
+    foils: usize,
+    foil_rarity_weights: HashMap<Rarity, f32>,
+
+    /// Whether to shuffle the cards in each pack, so that the rarest cards
+    /// aren't always first.
+    shuffle_pack_order: bool,
+
+    /// Whether the packs opened by each player should between them contain a
+    /// card of every colour, as in a sealed pool. Cards are swapped in from
+    /// the rest of the pool to make up any missing colours.
+    cover_colours: bool,
+
+    /// Whether to return to the lobby once the draft finishes, so that the
+    /// same table can draft again from the original pool.
+    keep_lobby: bool,
+
+    /// Recommended number of cards of each rarity in the pool, as a multiple
+    /// of the number needed. Launches with fewer cards warn the owner.
+    pool_ratios: HashMap<Rarity, f32>,
+
+    /// Names shown to players in place of each rarity, for games with their
+    /// own rarity tiers. Packs are still made up of the four rarities.
+    rarity_labels: HashMap<Rarity, String>,
+
+    /// Minimum number of players required for the draft.
+    min_players: usize,
+
+    /// Number of bots added to the table when the draft starts, which pick
+    /// automatically.
+    bots: usize,
+
+    /// Largest number of players the draft is meant for, if limited. Strict
+    /// launches check that the pool is complete for this many players.
+    max_players: Option<usize>,
+
+    /// URL to POST draft events to, if any. Not serialised, as it may hold a
+    /// secret.
+    #[serde(skip_serializing)]
+    webhook_url: Option<String>,
+
+    /// Whether the webhook may be sent to internal addresses. Set from the
+    /// server configuration at launch, never by the launcher.
+    #[serde(skip)]
+    allow_internal_webhook: bool,
+
+    /// Number of cards in each player's deck, if players build decks from
+    /// their pools after the draft.
+    deck_size: Option<usize>,
+
+    /// How long players have to submit a deck, if there is a limit. Decks are
+    /// built automatically for anyone who hasn't submitted by then.
+    /// Deserialised from seconds.
+    #[serde(
+        serialize_with = "serialize_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    deck_deadline: Option<Duration>,
+
+    /// How long players have to pick from each pack, if there is a limit. A
+    /// card is picked automatically for anyone who runs out of time.
+    /// Deserialised from seconds.
+    #[serde(
+        serialize_with = "serialize_secs",
+        deserialize_with = "deserialize_secs"
+    )]
+    pick_timer: Option<Duration>,
+
+    /// Picks made sooner than this after a pack is delivered are rejected.
+    /// Deserialised from milliseconds.
+    #[serde(
+        serialize_with = "serialize_millis",
+        deserialize_with = "deserialize_millis"
+    )]
+    min_pick_delay: Duration,
+
+    /// How long a player who disconnects from the lobby keeps their seat and
+    /// ready state, so that refreshing the page doesn't lose them. Seats are
+    /// freed immediately if zero. Deserialised from milliseconds.
+    #[serde(
+        serialize_with = "serialize_millis",
+        deserialize_with = "deserialize_millis"
+    )]
+    reconnect_grace: Duration,
+
+    /// Whether to show the table the best card in each pack as it is opened.
+    reveal_bombs: bool,
+
+    /// Whether to add basic lands to each player's pool once the draft is
+    /// complete, and how many of each.
+    include_basics: bool,
+    basics_per_colour: usize,
+
+    /// Whether to log every protocol message in this lobby, at trace level.
+    /// Off by default, as this is verbose and includes player names.
+    log_messages: bool,
+
+    /// Format of the draft.
+    mode: DraftMode,
+
+    /// How the passing direction changes between rounds.
+    pass_model: PassModel,
+
+    /// Direction packs are passed in the first round. Later rounds alternate
+    /// from this with the snake pass model, or keep it with the linear model.
+    first_direction: PassDirection,
+
+    /// Seed for random choices made during the draft, so that they can be
+    /// reproduced. Chosen at random if not set.
+    seed: Option<u64>,
+
+    /// Heuristic used when a pick is made automatically.
+    auto_pick: AutoPick,
+}
+
+fn serialize_millis<S: serde::Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_u64(duration.as_millis() as u64)
+}
+
+fn deserialize_millis<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    <u64 as serde::Deserialize>::deserialize(deserializer).map(Duration::from_millis)
+}
+
+fn serialize_secs<S: serde::Serializer>(
+    duration: &Option<Duration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serde::Serialize::serialize(&duration.map(|duration| duration.as_secs()), serializer)
+}
+
+fn deserialize_secs<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    <Option<u64> as serde::Deserialize>::deserialize(deserializer)
+        .map(|secs| secs.map(Duration::from_secs))
 }
 
 impl Default for DraftConfig {
@@ -40,13 +289,108 @@ impl Default for DraftConfig {
         DraftConfig {
             rounds: 3,
             cards_per_pack: 15,
+            pack_sizes: None,
             unique_cards: true,
             use_rarities: true,
             allow_fallback: true,
             mythic_rate: 0.125,
+            identical_rarities: false,
+            mythics: 0,
             rares: 1,
             uncommons: 3,
             commons: 11,
+            slot_distribution: Vec::new(),
+            image_quality: ImageQuality::default(),
+            foils: 0,
+            foil_rarity_weights: [
+                (Rarity::Mythic, 1.0),
+                (Rarity::Rare, 7.0),
+                (Rarity::Uncommon, 24.0),
+                (Rarity::Common, 68.0),
+            ]
+            .into_iter()
+            .collect(),
+            shuffle_pack_order: true,
+            cover_colours: false,
+            keep_lobby: false,
+            pool_ratios: HashMap::new(),
+            rarity_labels: HashMap::new(),
+            min_players: 1,
+            bots: 0,
+            max_players: None,
+            webhook_url: None,
+            allow_internal_webhook: false,
+            deck_size: None,
+            deck_deadline: None,
+            pick_timer: None,
+            min_pick_delay: Duration::ZERO,
+            reconnect_grace: Duration::from_secs(10),
+            reveal_bombs: false,
+            include_basics: false,
+            basics_per_colour: 10,
+            log_messages: false,
+            mode: DraftMode::Booster,
+            pass_model: PassModel::Snake,
+            first_direction: PassDirection::Left,
+            seed: None,
+            auto_pick: AutoPick::HighestRarity,
+        }
+    }
+}
+
+impl DraftConfig {
+    /// Number of seats at the smallest possible table, including bots.
+    fn min_seats(&self) -> usize {
+        self.min_players.saturating_add(self.bots)
+    }
+
+    /// Number of cards in each pack opened in the given round, including any
+    /// foils.
+    fn opened_pack_size(&self, round: usize) -> usize {
+        self.pack_size(round) + self.foils
+    }
+
+    /// Number of cards in the packs opened by each seat over the draft.
+    fn cards_per_seat(&self) -> usize {
+        (0..self.rounds)
+            .map(|round| self.opened_pack_size(round))
+            .sum()
+    }
+
+    /// Number of weighted slots in each pack. These are only used when
+    /// selecting cards by rarity.
+    fn weighted_slots(&self) -> usize {
+        if self.use_rarities {
+            self.slot_distribution.len()
+        } else {
+            0
         }
     }
+
+    /// Number of cards in each pack in the given (zero-indexed) round.
+    fn pack_size(&self, round: usize) -> usize {
+        self.pack_sizes
+            .as_ref()
+            .and_then(|sizes| sizes.get(round))
+            .copied()
+            .unwrap_or(self.cards_per_pack)
+    }
+
+    /// Number of mythics, rares, uncommons and commons in each pack in the
+    /// given round, excluding weighted slots. If the pack size for the round
+    /// differs from cards_per_pack, commons make up the difference. Packs too
+    /// small to hold the configured slots lose uncommons, then rares, then
+    /// mythics.
+    fn pack_layout(&self, round: usize) -> (usize, usize, usize, usize) {
+        let size = self.pack_size(round).saturating_sub(self.weighted_slots());
+        let mythics = self.mythics.min(size);
+        let rares = self.rares.min(size - mythics);
+        let uncommons = self.uncommons.min(size - mythics - rares);
+        (
+            mythics,
+            rares,
+            uncommons,
+            size - mythics - rares - uncommons,
+        )
+    }
 }
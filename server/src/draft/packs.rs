@@ -1,15 +1,19 @@
-use std::fmt::Debug;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Debug,
+};
 
-use rand::{seq::SliceRandom, thread_rng, Rng};
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 
 use crate::{
-    cards::{Card, Rarity},
-    err, Res,
+    cards::{Card, Colour, ImageQuality, Rarity},
+    error::DraftError,
+    Res,
 };
 
 use super::DraftConfig;
 
-#[derive(Clone)]
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 pub struct DraftPool {
     mythics: Vec<Card>,
     rares: Vec<Card>,
@@ -55,6 +59,164 @@ impl DraftPool {
         }
     }
 
+    /// Add each of the cards to the pool, reserving space for every card of
+    /// each rarity up front.
+    pub fn extend(&mut self, cards: impl IntoIterator<Item = Card>) {
+        let cards: Vec<Card> = cards.into_iter().collect();
+        for rarity in [
+            Rarity::Mythic,
+            Rarity::Rare,
+            Rarity::Uncommon,
+            Rarity::Common,
+        ] {
+            let count = cards.iter().filter(|card| card.rarity == rarity).count();
+            if let Some(bucket) = self.cards_of_mut(rarity) {
+                bucket.reserve(count);
+            }
+        }
+        for card in cards {
+            self.add(card);
+        }
+    }
+
+    /// Remove all cards with the given name (case insensitive) from the pool.
+    /// Returns the number of cards removed.
+    pub fn remove(&mut self, name: &str) -> usize {
+        let mut removed = 0;
+        for cards in [
+            &mut self.mythics,
+            &mut self.rares,
+            &mut self.uncommons,
+            &mut self.commons,
+        ] {
+            let before = cards.len();
+            cards.retain(|card| !card.name().eq_ignore_ascii_case(name));
+            removed += before - cards.len();
+        }
+        removed
+    }
+
+    /// Give every card the label for its rarity, if there is one.
+    pub fn label_rarities(&mut self, labels: &HashMap<Rarity, String>) {
+        for cards in [
+            &mut self.mythics,
+            &mut self.rares,
+            &mut self.uncommons,
+            &mut self.commons,
+        ] {
+            for card in cards.iter_mut() {
+                if let Some(label) = labels.get(&card.rarity) {
+                    card.set_rarity_label(label.clone());
+                }
+            }
+        }
+    }
+
+    /// Show every card's image of the given quality by default.
+    pub fn use_image_quality(&mut self, quality: ImageQuality) {
+        for cards in [
+            &mut self.mythics,
+            &mut self.rares,
+            &mut self.uncommons,
+            &mut self.commons,
+        ] {
+            for card in cards.iter_mut() {
+                card.use_image_quality(quality);
+            }
+        }
+    }
+
+    /// Check that the pool has enough cards for a draft with the given number
+    /// of players, counting as the packs are made. Cards can only run out if
+    /// they are unique, otherwise one card of each kind needed is enough.
+    /// Without fallback each rarity is checked, with mythic slots using rares
+    /// once the mythics run out, otherwise only the total. Every shortfall is
+    /// reported in the error. Rarities with fewer cards than recommended by
+    /// the configured ratios are described in the returned warnings.
+    pub fn validate(&self, config: &DraftConfig, players: usize) -> Res<Vec<String>> {
+        let layouts = pack_layouts(players, config);
+        let packs = layouts.len();
+        let unique = config.unique_cards;
+        let needed = |slots: usize| if unique { slots } else { slots.min(1) };
+
+        let mut shortfalls = Vec::new();
+        let mut check = |rarity: Option<Rarity>, slots: usize, available: usize| {
+            if available < needed(slots) {
+                shortfalls.push(DraftError::PoolInsufficient {
+                    rarity,
+                    needed: needed(slots) - available,
+                    packs,
+                    players,
+                });
+            }
+        };
+
+        // A random slot could roll any rarity it has a chance of in every
+        // pack, so it's counted against each of them.
+        let random = |rarity: Rarity| {
+            let slots = config
+                .slot_distribution
+                .iter()
+                .filter(|slot| slot.weights.get(&rarity).is_some_and(|w| *w > 0.0))
+                .count();
+            if config.use_rarities {
+                slots * packs
+            } else {
+                0
+            }
+        };
+        let mythics = layouts.iter().map(|l| l.0).sum::<usize>() + random(Rarity::Mythic);
+        let rares = layouts.iter().map(|l| l.1).sum::<usize>() + random(Rarity::Rare);
+        let uncommons = layouts.iter().map(|l| l.2).sum::<usize>() + random(Rarity::Uncommon);
+        let commons = layouts.iter().map(|l| l.3).sum::<usize>() + random(Rarity::Common);
+
+        // Foils fall back to any rarity, so only count towards the total.
+        let total = layouts.iter().map(|l| l.0 + l.1 + l.2 + l.3).sum::<usize>()
+            + packs * (config.weighted_slots() + config.foils);
+        let available =
+            self.mythics.len() + self.rares.len() + self.uncommons.len() + self.commons.len();
+        if config.use_rarities && !config.allow_fallback {
+            let spill = if self.mythics.len() < needed(mythics) {
+                mythics - self.mythics.len()
+            } else {
+                0
+            };
+            check(Some(Rarity::Rare), rares + spill, self.rares.len());
+            check(Some(Rarity::Uncommon), uncommons, self.uncommons.len());
+            check(Some(Rarity::Common), commons, self.commons.len());
+            if config.foils > 0 {
+                check(None, total, available);
+            }
+        } else {
+            check(None, total, available);
+        }
+        if !shortfalls.is_empty() {
+            return Err(DraftError::incomplete(shortfalls));
+        }
+
+        let mut warnings = Vec::new();
+        if unique && config.use_rarities {
+            for (rarity, needed) in [
+                (Rarity::Mythic, mythics),
+                (Rarity::Rare, rares),
+                (Rarity::Uncommon, uncommons),
+                (Rarity::Common, commons),
+            ] {
+                let available = self.cards_of(rarity).len();
+                if let Some(ratio) = config.pool_ratios.get(&rarity)
+                    && (available as f32) < needed as f32 * ratio
+                {
+                    warnings.push(format!(
+                        "{rarity:?}s are tight: {available} available for {players} players, {} recommended.",
+                        (needed as f32 * ratio).ceil()
+                    ));
+                }
+            }
+        }
+
+        Ok(warnings)
+    }
+
     fn empty(&self) -> bool {
         self.mythics.is_empty()
             && self.rares.is_empty()
@@ -72,6 +234,55 @@ impl DraftPool {
         }
     }
 
+    fn cards_of_mut(&mut self, rarity: Rarity) -> Option<&mut Vec<Card>> {
+        match rarity {
+            Rarity::Mythic => Some(&mut self.mythics),
+            Rarity::Rare => Some(&mut self.rares),
+            Rarity::Uncommon => Some(&mut self.uncommons),
+            Rarity::Common => Some(&mut self.commons),
+            Rarity::Bonus | Rarity::Special => None,
+        }
+    }
+
+    /// Choose a card of the given colour at random, of the given rarity if
+    /// any. Unique cards are removed from the pool, otherwise a copy is
+    /// returned.
+    fn take_coloured<R: Rng>(
+        &mut self,
+        colour: Colour,
+        rarity: Option<Rarity>,
+        unique: bool,
+        rng: &mut R,
+    ) -> Option<Card> {
+        let rarities = match rarity {
+            Some(rarity) => vec![rarity],
+            None => vec![
+                Rarity::Mythic,
+                Rarity::Rare,
+                Rarity::Uncommon,
+                Rarity::Common,
+            ],
+        };
+        let candidates: Vec<(Rarity, usize)> = rarities
+            .into_iter()
+            .flat_map(|rarity| {
+                self.cards_of(rarity)
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, card)| card.colours.contains(&colour))
+                    .map(move |(i, _)| (rarity, i))
+            })
+            .collect();
+
+        let &(rarity, i) = candidates.choose(rng)?;
+        let cards = self.cards_of_mut(rarity)?;
+        if unique {
+            Some(cards.swap_remove(i))
+        } else {
+            Some(cards[i].clone())
+        }
+    }
+
     /// Given a rarity that we are out of, which rarity should we replace that
     /// card slot with.
     fn replacement_rarity(&self, rarity: Rarity) -> Option<Rarity> {
@@ -95,7 +306,7 @@ impl DraftPool {
 
     fn take(&mut self, rarity: Rarity, allow_fallback: bool) -> Res<Card> {
         if self.empty() {
-            return err("Insufficient cards in pool.");
+            return Err(DraftError::PoolEmpty);
         }
 
         let exact = match rarity {
@@ -112,15 +323,38 @@ impl DraftPool {
             if let Some(fallback) = self.replacement_rarity(rarity) {
                 self.take(fallback, false)
             } else {
-                err(format!("Insufficient {rarity:?}s in pool."))
+                Err(DraftError::RarityUnavailable(rarity))
             }
         } else {
-            err("Insufficient cards in pool.")
+            Err(DraftError::PoolEmpty)
         }
     }
 
-    fn roll(&self, rarity: Rarity, allow_fallback: bool) -> Res<Card> {
-        let rng = &mut thread_rng();
+    /// Error describing how many more cards were needed to complete the packs
+    /// after failing to take a card of the given rarity. If the pool is empty,
+    /// the shortfall is in total cards, otherwise it's in that rarity.
+    fn shortfall<T>(
+        &self,
+        rarity: Rarity,
+        rarity_needed: usize,
+        cards_needed: usize,
+        packs: usize,
+        players: usize,
+    ) -> Res<T> {
+        let (rarity, needed) = if self.empty() {
+            (None, cards_needed)
+        } else {
+            (Some(rarity), rarity_needed)
+        };
+        Err(DraftError::PoolInsufficient {
+            rarity,
+            needed,
+            packs,
+            players,
+        })
+    }
+
+    fn roll<R: Rng>(&self, rarity: Rarity, allow_fallback: bool, rng: &mut R) -> Res<Card> {
         let exact = match rarity {
             Rarity::Mythic => self.mythics.choose(rng),
             Rarity::Rare => self.rares.choose(rng),
@@ -133,14 +367,14 @@ impl DraftPool {
             Ok(card.clone())
         } else if allow_fallback || rarity == Rarity::Mythic && !self.rares.is_empty() {
             if let Some(fallback) = self.replacement_rarity(rarity)
-                && let Ok(card) = self.roll(fallback, false)
+                && let Ok(card) = self.roll(fallback, false, rng)
             {
                 Ok(card)
             } else {
-                err(format!("Insufficient {rarity:?}s in pool."))
+                Err(DraftError::RarityUnavailable(rarity))
             }
         } else {
-            err(format!("Insufficient {rarity:?}s in pool."))
+            Err(DraftError::RarityUnavailable(rarity))
         }
     }
 }
@@ -160,36 +394,135 @@ impl Debug for DraftPool {
 
 pub type Pack = Vec<Card>;
 
-fn make_cube_packs_rarities(
+/// Layout (mythics, rares, uncommons, commons) of each pack to generate, in the
+/// order the packs will be opened. Each round has one pack per player.
+fn pack_layouts(players: usize, config: &DraftConfig) -> Vec<(usize, usize, usize, usize)> {
+    (0..config.rounds)
+        .flat_map(|round| std::iter::repeat_n(config.pack_layout(round), players))
+        .collect()
+}
+
+/// Number of rare slots to fill with mythics in the pack at the given index of
+/// the layouts. This is rolled for each slot, unless packs should have
+/// identical rarities, in which case the draft gets the expected number of
+/// mythics, spread as evenly as possible over the packs' rare slots. Rares are
+/// never upgraded if packs have dedicated mythic slots, or if the pool has no
+/// mythics to upgrade to, so that the rate isn't spent on rolls which can only
+/// fall back to a rare.
+fn mythic_count<R: Rng>(
+    config: &DraftConfig,
+    mythics_available: bool,
+    layouts: &[(usize, usize, usize, usize)],
+    index: usize,
+    rng: &mut R,
+) -> usize {
+    let rares = layouts[index].1;
+    if config.mythics > 0 || !mythics_available {
+        0
+    } else if config.identical_rarities {
+        // Mythics placed in the first n packs, in proportion to their rare
+        // slots, so that a rate too low for every pack to get one still gives
+        // the draft its share.
+        let slots: usize = layouts.iter().map(|l| l.1).sum();
+        let total = (slots as f32 * config.mythic_rate).round() as usize;
+        let placed = |n: usize| {
+            let slots_before: usize = layouts[..n].iter().map(|l| l.1).sum();
+            (slots_before * total).checked_div(slots).unwrap_or(0)
+        };
+        (placed(index + 1) - placed(index)).min(rares)
+    } else {
+        (0..rares)
+            .filter(|_| rng.gen_range(0.0..=1.0) < config.mythic_rate)
+            .count()
+    }
+}
+
+fn make_cube_packs_rarities<R: Rng>(
     players: usize,
     config: &DraftConfig,
-    mut pool: DraftPool,
+    pool: &mut DraftPool,
+    rng: &mut R,
 ) -> Res<Vec<Pack>> {
-    let mut rng = thread_rng();
-    pool.mythics.shuffle(&mut rng);
-    pool.rares.shuffle(&mut rng);
-    pool.uncommons.shuffle(&mut rng);
-    pool.commons.shuffle(&mut rng);
+    pool.mythics.shuffle(rng);
+    pool.rares.shuffle(rng);
+    pool.uncommons.shuffle(rng);
+    pool.commons.shuffle(rng);
 
     let mut packs = Vec::new();
+    let layouts = pack_layouts(players, config);
+    let total = layouts.len();
 
-    for _ in 0..(players * config.rounds) {
+    for (i, &(mythics, rares, uncommons, commons)) in layouts.iter().enumerate() {
         let mut pack = Vec::new();
+        let later = &layouts[i + 1..];
+        let pack_size = mythics + rares + uncommons + commons;
+        let later_cards: usize = later.iter().map(|(m, r, u, c)| m + r + u + c).sum();
+        let mythics_available = !pool.mythics.is_empty();
 
-        for _ in 0..config.rares {
-            if rng.gen_range(0.0..=1.0) < config.mythic_rate {
-                pack.push(pool.take(Rarity::Mythic, config.allow_fallback)?);
-            } else {
-                pack.push(pool.take(Rarity::Rare, config.allow_fallback)?);
+        // Take a card for a slot of the given rarity. On failure, report how
+        // many more cards would have been needed to fill the `remaining` slots
+        // of this rarity in this pack and the `later` slots in later packs.
+        let mut fill = |pack: &mut Pack, rarity, slot, remaining, later| match pool
+            .take(rarity, config.allow_fallback)
+        {
+            Ok(card) => {
+                pack.push(card);
+                Ok(())
             }
+            Err(_) => pool.shortfall(
+                slot,
+                remaining + later,
+                pack_size - pack.len() + later_cards,
+                total,
+                players,
+            ),
+        };
+
+        // Mythic slots fall back to rares if the pool is out of mythics.
+        let later_mythics = later.iter().map(|l| l.0).sum::<usize>();
+        for j in 0..mythics {
+            fill(
+                &mut pack,
+                Rarity::Mythic,
+                Rarity::Mythic,
+                mythics - j,
+                later_mythics,
+            )?;
+        }
+
+        let later_rares = later.iter().map(|l| l.1).sum::<usize>();
+        let upgrades = mythic_count(config, mythics_available, &layouts, i, rng);
+        for j in 0..rares {
+            let rarity = if j < upgrades {
+                Rarity::Mythic
+            } else {
+                Rarity::Rare
+            };
+            fill(&mut pack, rarity, Rarity::Rare, rares - j, later_rares)?;
         }
 
-        for _ in 0..config.uncommons {
-            pack.push(pool.take(Rarity::Uncommon, config.allow_fallback)?);
+        let later_uncommons = later.iter().map(|l| l.2).sum::<usize>();
+        for j in 0..uncommons {
+            let remaining = uncommons - j;
+            fill(
+                &mut pack,
+                Rarity::Uncommon,
+                Rarity::Uncommon,
+                remaining,
+                later_uncommons,
+            )?;
         }
 
-        for _ in 0..config.commons {
-            pack.push(pool.take(Rarity::Common, config.allow_fallback)?);
+        let later_commons = later.iter().map(|l| l.3).sum::<usize>();
+        for j in 0..commons {
+            let remaining = commons - j;
+            fill(
+                &mut pack,
+                Rarity::Common,
+                Rarity::Common,
+                remaining,
+                later_commons,
+            )?;
         }
 
         packs.push(pack)
@@ -198,55 +531,77 @@ fn make_cube_packs_rarities(
     Ok(packs)
 }
 
-fn make_cube_packs_no_rarities(
+fn make_cube_packs_no_rarities<R: Rng>(
     players: usize,
     config: &DraftConfig,
-    mut pool: DraftPool,
+    pool: &mut DraftPool,
+    rng: &mut R,
 ) -> Res<Vec<Pack>> {
     let mut cards = Vec::new();
     cards.append(&mut pool.mythics);
     cards.append(&mut pool.rares);
     cards.append(&mut pool.uncommons);
     cards.append(&mut pool.commons);
-    cards.shuffle(&mut thread_rng());
+    cards.shuffle(rng);
+
+    let sizes: Vec<usize> = pack_layouts(players, config)
+        .into_iter()
+        .map(|(m, r, u, c)| m + r + u + c)
+        .collect();
+    let needed: usize = sizes.iter().sum();
+    if cards.len() < needed {
+        return Err(DraftError::PoolInsufficient {
+            rarity: None,
+            needed: needed - cards.len(),
+            packs: sizes.len(),
+            players,
+        });
+    }
 
     let mut packs = Vec::new();
-    for _ in 0..(players * config.rounds) {
-        let mut pack = Vec::new();
-        for _ in 0..config.cards_per_pack {
-            if let Some(card) = cards.pop() {
-                pack.push(card);
-            } else {
-                return err("Insufficient cards in pool.");
-            }
-        }
-        packs.push(pack);
+    for size in sizes {
+        packs.push(cards.split_off(cards.len() - size));
+    }
+
+    // Return the unused cards to the pool.
+    for card in cards {
+        pool.add(card);
     }
 
     Ok(packs)
 }
 
-fn make_draft_packs(players: usize, config: &DraftConfig, pool: DraftPool) -> Res<Vec<Pack>> {
-    let rng = &mut thread_rng();
+fn make_draft_packs<R: Rng>(
+    players: usize,
+    config: &DraftConfig,
+    pool: &DraftPool,
+    rng: &mut R,
+) -> Res<Vec<Pack>> {
     let mut packs = Vec::new();
 
-    for _ in 0..(players * config.rounds) {
+    let layouts = pack_layouts(players, config);
+    for (i, &(mythics, rares, uncommons, commons)) in layouts.iter().enumerate() {
         let mut pack = Vec::new();
 
-        for _ in 0..config.rares {
-            if rng.gen_range(0.0..=1.0) < config.mythic_rate {
-                pack.push(pool.roll(Rarity::Mythic, config.allow_fallback)?);
+        for _ in 0..mythics {
+            pack.push(pool.roll(Rarity::Mythic, config.allow_fallback, rng)?);
+        }
+
+        let upgrades = mythic_count(config, !pool.mythics.is_empty(), &layouts, i, rng);
+        for j in 0..rares {
+            if j < upgrades {
+                pack.push(pool.roll(Rarity::Mythic, config.allow_fallback, rng)?);
             } else {
-                pack.push(pool.roll(Rarity::Rare, config.allow_fallback)?);
+                pack.push(pool.roll(Rarity::Rare, config.allow_fallback, rng)?);
             }
         }
 
-        for _ in 0..config.uncommons {
-            pack.push(pool.roll(Rarity::Uncommon, config.allow_fallback)?);
+        for _ in 0..uncommons {
+            pack.push(pool.roll(Rarity::Uncommon, config.allow_fallback, rng)?);
         }
 
-        for _ in 0..config.commons {
-            pack.push(pool.roll(Rarity::Common, config.allow_fallback)?);
+        for _ in 0..commons {
+            pack.push(pool.roll(Rarity::Common, config.allow_fallback, rng)?);
         }
 
         packs.push(pack);
@@ -255,26 +610,238 @@ fn make_draft_packs(players: usize, config: &DraftConfig, pool: DraftPool) -> Re
     Ok(packs)
 }
 
-pub fn make_packs(players: usize, config: &DraftConfig, pool: DraftPool) -> Res<Vec<Pack>> {
-    if config.unique_cards {
+/// Swap a card of the given colour into one of the packs, from the rest of
+/// the pool. The replaced card is the last one which has spare colours, else
+/// the last colourless card, as cards are added rarest first. Cards which are
+/// the only source of one of their colours are never replaced. Returns whether
+/// a card was swapped.
+fn swap_in_colour<R: Rng>(
+    packs: &mut [Pack],
+    seat_packs: &[usize],
+    colour: Colour,
+    config: &DraftConfig,
+    pool: &mut DraftPool,
+    rng: &mut R,
+) -> bool {
+    let mut counts: HashMap<Colour, usize> = HashMap::new();
+    for card in seat_packs.iter().flat_map(|&i| packs[i].iter()) {
+        for c in &card.colours {
+            *counts.entry(*c).or_insert(0) += 1;
+        }
+    }
+
+    let mut slots: Vec<(usize, usize)> = seat_packs
+        .iter()
+        .rev()
+        .flat_map(|&i| (0..packs[i].len()).rev().map(move |j| (i, j)))
+        .filter(|&(i, j)| packs[i][j].colours.iter().all(|c| counts[c] > 1))
+        .collect();
+    slots.sort_by_key(|&(i, j)| packs[i][j].colours.is_empty());
+
+    // Rarities without a replacement are skipped.
+    let mut exhausted = HashSet::new();
+    for (i, j) in slots {
+        let rarity = config.use_rarities.then_some(packs[i][j].rarity);
+        if exhausted.contains(&rarity) {
+            continue;
+        }
+
+        match pool.take_coloured(colour, rarity, config.unique_cards, rng) {
+            Some(replacement) => {
+                let replaced = std::mem::replace(&mut packs[i][j], replacement);
+                if config.unique_cards {
+                    pool.add(replaced);
+                }
+                return true;
+            }
+            None => {
+                exhausted.insert(rarity);
+            }
+        }
+    }
+    false
+}
+
+/// Swap cards into the packs opened by each seat so that, between them, they
+/// contain a card of every colour, as in a sealed pool. Replacements are drawn
+/// from the rest of the pool, matching the rarity of the card replaced if using
+/// rarities. Colours which the pool can't supply are left missing.
+fn cover_colours<R: Rng>(
+    packs: &mut [Pack],
+    players: usize,
+    config: &DraftConfig,
+    pool: &mut DraftPool,
+    rng: &mut R,
+) {
+    if players == 0 {
+        return;
+    }
+
+    // Pack i is opened by seat i % players.
+    let seats: Vec<Vec<usize>> = (0..players)
+        .map(|seat| (seat..packs.len()).step_by(players).collect())
+        .collect();
+
+    // Each swap covers a colour for a seat without uncovering any, so this
+    // terminates. Repeated as cards returned to the pool by later seats may
+    // cover colours for earlier ones.
+    let mut swapped = true;
+    while swapped {
+        swapped = false;
+        for seat_packs in &seats {
+            for colour in Colour::ALL {
+                let covered = seat_packs
+                    .iter()
+                    .flat_map(|&i| packs[i].iter())
+                    .any(|card| card.colours.contains(&colour));
+                if !covered {
+                    swapped |= swap_in_colour(packs, seat_packs, colour, config, pool, rng);
+                }
+            }
+        }
+    }
+}
+
+/// Choose a rarity at random, with the given relative weights. Falls back to
+/// common if no rarity has a positive weight.
+fn choose_rarity<R: Rng>(weights: &HashMap<Rarity, f32>, rng: &mut R) -> Rarity {
+    // Sorted, as map order varies between runs, which would make the choice
+    // differ for the same seed.
+    let mut weights: Vec<(Rarity, f32)> = weights
+        .iter()
+        .map(|(rarity, weight)| (*rarity, *weight))
+        .collect();
+    weights.sort_by_key(|(rarity, _)| rarity.rank());
+    weights
+        .choose_weighted(rng, |(_, weight)| *weight)
+        .map(|(rarity, _)| *rarity)
+        .unwrap_or(Rarity::Common)
+}
+
+/// Fill the weighted slots of each pack, after its fixed slots. The rarity of
+/// each slot is chosen by weight, then a card is taken or rolled as for a
+/// fixed slot of that rarity.
+fn fill_weighted_slots<R: Rng>(
+    packs: &mut [Pack],
+    config: &DraftConfig,
+    pool: &mut DraftPool,
+    rng: &mut R,
+) -> Res<()> {
+    if config.weighted_slots() == 0 {
+        return Ok(());
+    }
+
+    for pack in packs {
+        for slot in &config.slot_distribution {
+            let rarity = choose_rarity(&slot.weights, rng);
+            let card = if config.unique_cards {
+                pool.take(rarity, config.allow_fallback)?
+            } else {
+                pool.roll(rarity, config.allow_fallback, rng)?
+            };
+            pack.push(card);
+        }
+    }
+    Ok(())
+}
+
+/// Add the configured number of foils to the end of each pack. The rarity of
+/// each foil is chosen by weight, falling back to other rarities if the pool
+/// has none left. With unique cards, foils are taken from the rest of the pool.
+fn add_foils<R: Rng>(
+    packs: &mut [Pack],
+    config: &DraftConfig,
+    pool: &mut DraftPool,
+    rng: &mut R,
+) -> Res<()> {
+    if config.foils == 0 {
+        return Ok(());
+    }
+
+    for pack in packs {
+        for _ in 0..config.foils {
+            let rarity = choose_rarity(&config.foil_rarity_weights, rng);
+            let mut card = if config.unique_cards {
+                pool.take(rarity, true)?
+            } else {
+                pool.roll(rarity, true, rng)?
+            };
+            card.foil = true;
+            pack.push(card);
+        }
+    }
+    Ok(())
+}
+
+/// Generate the packs for a draft, in the order they will be opened. If the
+/// configuration has a seed, the same pool always gives the same packs.
+pub fn make_packs(players: usize, config: &DraftConfig, mut pool: DraftPool) -> Res<Vec<Pack>> {
+    let rng = &mut match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut packs = if config.unique_cards {
         if config.use_rarities {
-            make_cube_packs_rarities(players, config, pool)
+            make_cube_packs_rarities(players, config, &mut pool, rng)?
         } else {
-            make_cube_packs_no_rarities(players, config, pool)
+            make_cube_packs_no_rarities(players, config, &mut pool, rng)?
         }
     } else {
-        make_draft_packs(players, config, pool)
+        make_draft_packs(players, config, &pool, rng)?
+    };
+
+    fill_weighted_slots(&mut packs, config, &mut pool, rng)?;
+    add_foils(&mut packs, config, &mut pool, rng)?;
+    if config.cover_colours {
+        cover_colours(&mut packs, players, config, &mut pool, rng);
+    }
+
+    // Packs are built rarest first, so shuffle them so that a card's position
+    // doesn't give away its rarity.
+    if config.shuffle_pack_order {
+        for pack in &mut packs {
+            pack.shuffle(rng);
+        }
     }
+
+    Ok(packs)
+}
+
+/// Diagnostics for generated packs which may indicate a mistake in the draft
+/// configuration. These don't prevent the draft from running.
+pub fn pack_warnings(packs: &[Pack]) -> Vec<String> {
+    let mut warnings = Vec::new();
+
+    let rareless = packs
+        .iter()
+        .filter(|pack| {
+            !pack
+                .iter()
+                .any(|card| card.rarity.rank() >= Rarity::Rare.rank())
+        })
+        .count();
+    if rareless > 0 {
+        warnings.push(format!(
+            "{rareless} of {} packs contain no rares.",
+            packs.len()
+        ));
+    }
+
+    warnings
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::{HashMap, HashSet};
+
     use crate::{
-        cards::{Card, Rarity},
-        draft::DraftConfig,
+        cards::{Card, Colour, Rarity},
+        draft::{DraftConfig, RaritySlot},
+        error::DraftError,
     };
 
-    use super::{make_packs, DraftPool};
+    use super::{make_packs, pack_warnings, DraftPool};
 
     fn test_config() -> DraftConfig {
         DraftConfig {
@@ -304,6 +871,78 @@ mod test {
             .all(|p| p.iter().any(|c| c.rarity == Rarity::Mythic)));
     }
 
+    #[test]
+    fn test_foils() {
+        let mut config = test_config();
+        config.foils = 1;
+        config.foil_rarity_weights = [(Rarity::Uncommon, 1.0)].into_iter().collect();
+
+        // Foils are taken from the rest of the pool with unique cards, or
+        // rolled from the whole pool otherwise.
+        for unique_cards in [true, false] {
+            config.unique_cards = unique_cards;
+            let packs = make_packs(2, &config, DraftPool::sample(4, 4, 8, 4)).unwrap();
+            assert_eq!(packs.len(), 4);
+            assert!(packs.iter().all(|p| p.len() == 4));
+            assert!(packs.iter().all(|p| {
+                let foils: Vec<&Card> = p.iter().filter(|c| c.foil).collect();
+                foils.len() == 1 && foils[0].rarity == Rarity::Uncommon
+            }));
+        }
+
+        // Rarities fall back as usual once the pool runs out.
+        config.unique_cards = true;
+        let packs = make_packs(2, &config, DraftPool::sample(4, 4, 4, 8)).unwrap();
+        assert_eq!(packs.iter().flatten().filter(|c| c.foil).count(), 4);
+    }
+
+    #[test]
+    fn test_weighted_slots() {
+        let mut config = test_config();
+        config.cards_per_pack = 4;
+        config.mythic_rate = 0.0;
+        config.slot_distribution = vec![RaritySlot {
+            weights: [(Rarity::Uncommon, 1.0), (Rarity::Common, 0.0)]
+                .into_iter()
+                .collect(),
+        }];
+
+        for unique_cards in [true, false] {
+            config.unique_cards = unique_cards;
+            let packs = make_packs(2, &config, DraftPool::sample(0, 4, 8, 4)).unwrap();
+            assert_eq!(packs.len(), 4);
+            assert!(packs.iter().all(|p| p.len() == 4));
+            assert!(packs
+                .iter()
+                .all(|p| { p.iter().filter(|c| c.rarity == Rarity::Uncommon).count() == 2 }));
+        }
+
+        // Random slots are counted when validating, so a pool which only has
+        // enough cards for the fixed slots is rejected up front.
+        config.unique_cards = true;
+        let pool = DraftPool::sample(0, 4, 4, 4);
+        for (allow_fallback, rarity) in [(true, None), (false, Some(Rarity::Uncommon))] {
+            config.allow_fallback = allow_fallback;
+            assert!(make_packs(2, &config, pool.clone()).is_err());
+            assert_eq!(
+                pool.validate(&config, 2).unwrap_err(),
+                DraftError::PoolInsufficient {
+                    rarity,
+                    needed: 4,
+                    packs: 4,
+                    players: 2
+                }
+            );
+            assert!(DraftPool::sample(0, 4, 8, 4).validate(&config, 2).is_ok());
+        }
+
+        // Weighted slots are ignored when not using rarities.
+        config.use_rarities = false;
+        config.cards_per_pack = 3;
+        let packs = make_packs(2, &config, DraftPool::sample(0, 4, 4, 4)).unwrap();
+        assert!(packs.iter().all(|p| p.len() == 3));
+    }
+
     #[test]
     fn test_make_draft_packs() {
         let mut pool = DraftPool::new();
@@ -328,6 +967,234 @@ mod test {
             .all(|card| pack.iter().any(|pack_card| pack_card.name() == card.name()))))
     }
 
+    #[test]
+    fn test_shuffle_pack_order() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            unique_cards: false,
+            rares: 1,
+            uncommons: 0,
+            commons: 2,
+            mythic_rate: 0.0,
+            ..Default::default()
+        };
+
+        // The rare is equally likely to be at each position in the pack.
+        let players = 3000;
+        let packs = make_packs(players, &config, DraftPool::sample(0, 1, 0, 2)).unwrap();
+        let mut positions = [0; 3];
+        for pack in &packs {
+            positions[pack.iter().position(|c| c.rarity == Rarity::Rare).unwrap()] += 1;
+        }
+        assert!(
+            positions.iter().all(|&n| (800..1200).contains(&n)),
+            "positions were {positions:?}"
+        );
+
+        // Unless shuffling is disabled.
+        let config = DraftConfig {
+            shuffle_pack_order: false,
+            ..config
+        };
+        let packs = make_packs(100, &config, DraftPool::sample(0, 1, 0, 2)).unwrap();
+        assert!(packs.iter().all(|pack| pack[0].rarity == Rarity::Rare));
+    }
+
+    #[test]
+    fn test_seeded_packs() {
+        let mut config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 5,
+            rares: 1,
+            uncommons: 1,
+            commons: 2,
+            mythic_rate: 0.5,
+            foils: 1,
+            slot_distribution: vec![RaritySlot {
+                weights: [(Rarity::Uncommon, 1.0), (Rarity::Common, 3.0)]
+                    .into_iter()
+                    .collect(),
+            }],
+            seed: Some(7),
+            ..Default::default()
+        };
+        let pool = DraftPool::sample(8, 8, 16, 40);
+        let names = |config: &DraftConfig| -> Vec<Vec<String>> {
+            make_packs(3, config, pool.clone())
+                .unwrap()
+                .iter()
+                .map(|pack| pack.iter().map(|c| c.name().to_string()).collect())
+                .collect()
+        };
+
+        // The same seed always gives the same packs, whether cards are unique
+        // or not, and different seeds give different packs.
+        for unique_cards in [true, false] {
+            config.unique_cards = unique_cards;
+            let packs = names(&config);
+            assert_eq!(names(&config), packs);
+
+            config.seed = Some(8);
+            assert_ne!(names(&config), packs);
+            config.seed = Some(7);
+        }
+    }
+
+    #[test]
+    fn test_validate_pool() {
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 3,
+            rares: 1,
+            uncommons: 1,
+            commons: 1,
+            pool_ratios: [(Rarity::Rare, 2.0)].into_iter().collect(),
+            ..Default::default()
+        };
+
+        // 4 players need 8 rares, 16 are recommended.
+        let pool = DraftPool::sample(0, 10, 8, 8);
+        assert_eq!(
+            pool.validate(&config, 4).unwrap(),
+            vec!["Rares are tight: 10 available for 4 players, 16 recommended."]
+        );
+        assert!(pool.validate(&config, 2).unwrap().is_empty());
+
+        // Running short of a rarity is an error only if there's no fallback.
+        let pool = DraftPool::sample(0, 10, 7, 8);
+        assert!(pool.validate(&config, 4).is_ok());
+        let config = DraftConfig {
+            allow_fallback: false,
+            ..config
+        };
+        assert_eq!(
+            pool.validate(&config, 4).unwrap_err(),
+            DraftError::PoolInsufficient {
+                rarity: Some(Rarity::Uncommon),
+                needed: 1,
+                packs: 8,
+                players: 4
+            }
+        );
+
+        // Running short overall is always an error.
+        let pool = DraftPool::sample(0, 10, 7, 6);
+        let config = DraftConfig {
+            allow_fallback: true,
+            ..config
+        };
+        assert_eq!(
+            pool.validate(&config, 8).unwrap_err(),
+            DraftError::PoolInsufficient {
+                rarity: None,
+                needed: 25,
+                packs: 16,
+                players: 8
+            }
+        );
+    }
+
+    #[test]
+    fn test_validate_shortfalls() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            mythics: 1,
+            rares: 1,
+            uncommons: 1,
+            commons: 0,
+            allow_fallback: false,
+            ..Default::default()
+        };
+
+        // Mythic slots use up rares once the mythics run out, and every
+        // shortfall is reported. The packs agree.
+        let pool = DraftPool::sample(1, 3, 2, 0);
+        assert!(pool.validate(&config, 2).is_ok());
+        assert!(make_packs(2, &config, pool.clone()).is_ok());
+        assert_eq!(
+            pool.validate(&config, 3).unwrap_err(),
+            DraftError::PoolIncomplete(vec![
+                DraftError::PoolInsufficient {
+                    rarity: Some(Rarity::Rare),
+                    needed: 2,
+                    packs: 3,
+                    players: 3
+                },
+                DraftError::PoolInsufficient {
+                    rarity: Some(Rarity::Uncommon),
+                    needed: 1,
+                    packs: 3,
+                    players: 3
+                },
+            ])
+        );
+        assert!(make_packs(3, &config, pool.clone()).is_err());
+
+        // Without unique cards, one card of each rarity is enough.
+        let config = DraftConfig {
+            unique_cards: false,
+            ..config
+        };
+        assert!(pool.validate(&config, 3).is_ok());
+        assert!(make_packs(3, &config, pool).is_ok());
+    }
+
+    #[test]
+    fn test_mythic_rate() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 1,
+            unique_cards: false,
+            rares: 1,
+            uncommons: 0,
+            commons: 0,
+            mythic_rate: 0.25,
+            ..Default::default()
+        };
+
+        // With plenty of mythics, rares are upgraded at the configured rate.
+        let players = 4000;
+        let packs = make_packs(players, &config, DraftPool::sample(10, 10, 0, 0)).unwrap();
+        let mythics = packs
+            .iter()
+            .flatten()
+            .filter(|c| c.rarity == Rarity::Mythic)
+            .count();
+        let rate = mythics as f32 / players as f32;
+        assert!((rate - config.mythic_rate).abs() < 0.03, "rate was {rate}");
+
+        // Without mythics, every pack still gets its rare.
+        let packs = make_packs(100, &config, DraftPool::sample(0, 10, 0, 0)).unwrap();
+        assert!(packs.iter().flatten().all(|c| c.rarity == Rarity::Rare));
+    }
+
+    #[test]
+    fn test_mythic_slots() {
+        let count = |pack: &Vec<Card>, rarity| pack.iter().filter(|c| c.rarity == rarity).count();
+        for unique_cards in [true, false] {
+            let config = DraftConfig {
+                rounds: 1,
+                cards_per_pack: 3,
+                unique_cards,
+                mythics: 1,
+                rares: 1,
+                uncommons: 0,
+                commons: 1,
+                mythic_rate: 1.0, // Ignored with dedicated mythic slots.
+                ..Default::default()
+            };
+            let packs = make_packs(4, &config, DraftPool::sample(4, 4, 0, 4)).unwrap();
+            assert_eq!(packs.len(), 4);
+            for pack in &packs {
+                assert_eq!(count(pack, Rarity::Mythic), 1);
+                assert_eq!(count(pack, Rarity::Rare), 1);
+                assert_eq!(count(pack, Rarity::Common), 1);
+            }
+        }
+    }
+
     #[test]
     fn test_fail_make_packs() {
         // 1 pack with 1 rare per player.
@@ -356,4 +1223,242 @@ mod test {
         };
         assert!(make_packs(2, &config, pool).is_ok());
     }
+
+    #[test]
+    fn test_shortfall_message() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            allow_fallback: false,
+            rares: 0,
+            uncommons: 0,
+            commons: 2,
+            ..Default::default()
+        };
+
+        // Out of commons, but uncommons remain.
+        let pool = DraftPool::sample(0, 0, 1, 2);
+        let e = make_packs(2, &config, pool).unwrap_err();
+        assert_eq!(
+            e,
+            DraftError::PoolInsufficient {
+                rarity: Some(Rarity::Common),
+                needed: 2,
+                packs: 2,
+                players: 2
+            }
+        );
+        assert!(e
+            .to_string()
+            .contains("needed 2 more commons to complete 2 packs for 2 players"));
+
+        // Pool exhausted entirely.
+        let config = DraftConfig {
+            allow_fallback: true,
+            ..config
+        };
+        let pool = DraftPool::sample(0, 0, 0, 3);
+        let e = make_packs(2, &config, pool).unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("needed 1 more cards to complete 2 packs for 2 players"));
+
+        // Without rarities, shortfall is in total cards.
+        let config = DraftConfig {
+            use_rarities: false,
+            ..config
+        };
+        let pool = DraftPool::sample(0, 0, 0, 1);
+        let e = make_packs(3, &config, pool).unwrap_err();
+        assert!(e
+            .to_string()
+            .contains("needed 5 more cards to complete 3 packs for 3 players"));
+    }
+
+    #[test]
+    fn test_rareless_pack_warning() {
+        let mut config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            rares: 1,
+            uncommons: 0,
+            commons: 1,
+            ..Default::default()
+        };
+        let packs = make_packs(2, &config, DraftPool::sample(1, 1, 0, 2)).unwrap();
+        assert!(pack_warnings(&packs).is_empty());
+
+        config.rares = 0;
+        config.commons = 2;
+        let packs = make_packs(2, &config, DraftPool::sample(0, 0, 0, 4)).unwrap();
+        assert_eq!(
+            pack_warnings(&packs),
+            vec!["2 of 2 packs contain no rares."]
+        );
+    }
+
+    #[test]
+    fn test_excluded_card_not_in_packs() {
+        let mut pool = DraftPool::sample(0, 0, 0, 4);
+        let excluded = pool.commons[0].name().to_uppercase();
+        assert_eq!(pool.remove(&excluded), 1);
+        assert_eq!(pool.remove(&excluded), 0);
+
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let packs = make_packs(1, &config, pool).unwrap();
+        assert!(packs
+            .iter()
+            .flatten()
+            .all(|card| !card.name().eq_ignore_ascii_case(&excluded)));
+    }
+
+    #[test]
+    fn test_identical_rarities() {
+        for unique_cards in [true, false] {
+            let config = DraftConfig {
+                rounds: 2,
+                cards_per_pack: 3,
+                unique_cards,
+                rares: 3,
+                uncommons: 0,
+                commons: 0,
+                mythic_rate: 1.0 / 3.0,
+                identical_rarities: true,
+                ..Default::default()
+            };
+            let packs = make_packs(4, &config, DraftPool::sample(20, 20, 0, 0)).unwrap();
+            assert_eq!(packs.len(), 8);
+            assert!(packs.iter().all(|pack| pack
+                .iter()
+                .filter(|card| card.rarity == Rarity::Mythic)
+                .count()
+                == 1));
+        }
+
+        // At the default rate, a single rare slot would round to no mythics
+        // in every pack, so the draft's share is spread over the packs.
+        let config = DraftConfig {
+            rounds: 3,
+            cards_per_pack: 1,
+            rares: 1,
+            uncommons: 0,
+            commons: 0,
+            identical_rarities: true,
+            ..Default::default()
+        };
+        assert_eq!(config.mythic_rate, 0.125);
+        let packs = make_packs(8, &config, DraftPool::sample(10, 30, 0, 0)).unwrap();
+        let mythics: Vec<usize> = packs
+            .iter()
+            .map(|pack| pack.iter().filter(|c| c.rarity == Rarity::Mythic).count())
+            .collect();
+        assert_eq!(mythics.iter().sum::<usize>(), 3);
+        for round in mythics.chunks(8) {
+            assert_eq!(round.iter().sum::<usize>(), 1);
+        }
+    }
+
+    #[test]
+    fn test_cover_colours() {
+        let coloured = |rarity, colour| {
+            let mut card = Card::sample(rarity);
+            card.colours = vec![colour];
+            card
+        };
+
+        for use_rarities in [true, false] {
+            let config = DraftConfig {
+                rounds: 1,
+                cards_per_pack: 5,
+                use_rarities,
+                rares: 0,
+                uncommons: 0,
+                commons: 5,
+                cover_colours: true,
+                ..Default::default()
+            };
+
+            // Two of each colour, which must be split between the seats.
+            let mut pool = DraftPool::sample(0, 0, 0, 20);
+            for colour in Colour::ALL {
+                pool.add(coloured(Rarity::Common, colour));
+                pool.add(coloured(Rarity::Common, colour));
+            }
+            let packs = make_packs(2, &config, pool).unwrap();
+            for pack in &packs {
+                for colour in Colour::ALL {
+                    assert!(pack.iter().any(|card| card.colours.contains(&colour)));
+                }
+            }
+
+            // Swapped cards aren't duplicated.
+            let names: HashSet<&str> = packs.iter().flatten().map(|c| c.name()).collect();
+            assert_eq!(names.len(), 10);
+        }
+
+        // A colour the pool doesn't have is left missing.
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 2,
+            unique_cards: false,
+            rares: 0,
+            uncommons: 0,
+            commons: 2,
+            cover_colours: true,
+            ..Default::default()
+        };
+        let mut pool = DraftPool::sample(0, 0, 0, 2);
+        pool.add(coloured(Rarity::Common, Colour::G));
+        let packs = make_packs(3, &config, pool).unwrap();
+        for seat in 0..3 {
+            let cards: Vec<&Card> = packs.iter().skip(seat).step_by(3).flatten().collect();
+            assert!(cards.iter().any(|c| c.colours == vec![Colour::G]));
+            assert!(cards.iter().all(|c| !c.colours.contains(&Colour::W)));
+        }
+    }
+
+    #[test]
+    fn test_label_rarities() {
+        let mut pool = DraftPool::sample(0, 1, 0, 1);
+        pool.label_rarities(&HashMap::from([(Rarity::Rare, "Epic".to_string())]));
+
+        let rare = serde_json::to_value(&pool.rares[0]).unwrap();
+        assert_eq!(rare["rarity"], "Rare");
+        assert_eq!(rare["rarity_label"], "Epic");
+        let common = serde_json::to_value(&pool.commons[0]).unwrap();
+        assert!(common.get("rarity_label").is_none());
+    }
+
+    #[test]
+    fn test_extend() {
+        let mut cards = Vec::new();
+        for _ in 0..10_000 {
+            for rarity in [
+                Rarity::Mythic,
+                Rarity::Rare,
+                Rarity::Uncommon,
+                Rarity::Common,
+                Rarity::Special,
+            ] {
+                cards.push(Card::sample(rarity));
+            }
+        }
+
+        let mut pool = DraftPool::new();
+        pool.extend(cards);
+        for rarity in [
+            Rarity::Mythic,
+            Rarity::Rare,
+            Rarity::Uncommon,
+            Rarity::Common,
+        ] {
+            assert_eq!(pool.cards_of(rarity).len(), 10_000);
+        }
+        assert!(pool.mythics.capacity() >= 10_000);
+    }
 }
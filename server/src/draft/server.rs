@@ -1,17 +1,38 @@
-use std::collections::HashMap;
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
+    fmt::Debug,
+    future::Future,
+    path::PathBuf,
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
+use rand::Rng;
 use tokio::{
-    sync::mpsc::{UnboundedReceiver, UnboundedSender},
-    time::Instant,
+    sync::{
+        mpsc::{UnboundedReceiver, UnboundedSender},
+        oneshot, watch,
+    },
+    task::JoinHandle,
+    time::{sleep_until, Duration, Instant},
 };
 use uuid::Uuid;
 
-use crate::cards::Card;
+use crate::{
+    cards::{Card, Colour},
+    err,
+    error::DraftError,
+    Res,
+};
 
 use super::{
-    game::Draft,
+    game::{
+        auto_pick, bomb, bot_pick, Draft, DraftPack, GridDraft, GridSelection, NewPacks,
+        OpenedPack, PickRecord, RarityCounts, WinstonAction, WinstonDraft, WinstonState,
+    },
     packs::{make_packs, DraftPool, Pack},
-    DraftConfig,
+    webhook::{Webhook, WebhookEvent},
+    DraftConfig, DraftMode,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
@@ -19,6 +40,9 @@ pub enum ClientStatus {
     Ok,
     Warning,
     Error,
+
+    /// Player deliberately left the draft, rather than disconnecting.
+    Left,
 }
 
 #[derive(Clone, Debug, serde::Serialize)]
@@ -29,35 +53,112 @@ pub struct PlayerDetails {
     status: ClientStatus,
 }
 
+/// The best card in a pack opened by the given seat.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct Reveal {
+    seat: Uuid,
+    card: Card,
+}
+
+/// Format of a draft which is about to start.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct DraftSummary {
+    rounds: usize,
+
+    /// Number of cards in the pack opened in each round.
+    pack_sizes: Vec<usize>,
+
+    /// Number of cards in each deck, if there is deckbuilding.
+    deck_size: Option<usize>,
+}
+
+/// Colours of the cards in a player's final pool.
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize)]
+pub struct PoolSummary {
+    /// Number of cards of each colour. Multicoloured cards count towards each
+    /// of their colours.
+    colours: HashMap<Colour, usize>,
+
+    /// Number of colourless cards.
+    colourless: usize,
+}
+
+impl PoolSummary {
+    fn of(pool: &[Card]) -> Self {
+        let mut summary = Self::default();
+        for card in pool {
+            if card.colours.is_empty() {
+                summary.colourless += 1;
+            }
+            for &colour in &card.colours {
+                *summary.colours.entry(colour).or_default() += 1;
+            }
+        }
+        summary
+    }
+}
+
 #[derive(Clone, Debug, serde::Serialize)]
 #[serde(tag = "type", content = "value")]
 pub enum ServerMessage {
     /// Draft already started, cannot join.
     Started,
+    /// Lobby has as many players as the draft allows, cannot join.
+    LobbyFull,
     /// Draft ended.
     Ended,
     /// Server terminated due to fatal error.
-    FatalError(String),
+    FatalError(DraftError),
+
+    /// New pack for user to pick from, with the seconds left to pick from it
+    /// if there is a pick timer.
+    Pack {
+        pack: DraftPack,
+        time_left: Option<u64>,
+    },
 
-    /// New pack for user to pick from.
-    Pack(Pack), // TODO this should include an ID to handle out of order events
+    /// The pack queued behind the user's current pack, to look at only.
+    PeekPack(DraftPack),
 
     /// Pick was successful, current pack has been passed on.
     PickSuccessful(Card),
 
+    /// Your last pick was undone. Holds your pool without the card, which is
+    /// back in the pack sent after this.
+    PickUndone(Vec<Card>),
+
+    /// Your last pick couldn't be undone, for this reason.
+    UndoRejected(String),
+
+    /// The pack you were passed was taken back to undo a pick, and you have
+    /// no other pack to pick from.
+    PackWithdrawn,
+
+    /// The last pick of the draft has been made. Sent to the whole table and
+    /// spectators, before each player is sent their pool.
+    DraftComplete,
+
     /// Draft finished, here's your final pool.
     Finished(Vec<Card>),
 
+    /// Colours of your final pool, sent after the pool itself.
+    PoolSummary(PoolSummary),
+
     /// Successfully connected to the lobby.
-    Connected { draft: Uuid, seat: Uuid },
+    Connected {
+        draft: Uuid,
+        seat: Uuid,
+        name: Option<String>,
+    },
 
     /// Successfully reconnected to in progress or completed draft.
     Reconnected {
         draft: Uuid,
         seat: Uuid,
+        name: Option<String>,
         in_progress: bool,
         pool: Vec<Card>,
-        pack: Option<Vec<Card>>,
+        pack: Option<DraftPack>,
     },
 
     /// Client sent us a message that doesn't make sense, their state must be
@@ -67,11 +168,113 @@ pub enum ServerMessage {
     /// Change to the list of connected players.
     PlayerList(Vec<PlayerDetails>),
 
+    /// Everyone is ready and the draft is about to begin with these players.
+    /// Sent before the first packs.
+    Starting {
+        players: Vec<PlayerDetails>,
+        summary: DraftSummary,
+    },
+
     /// Client name, ready state or status update.
     PlayerUpdate(PlayerDetails),
 
+    /// A round is starting, here's the best card in each new pack.
+    Revealed(Vec<Reveal>),
+
     /// Seat ID, number of queued packs.
     QueueSize { seat: Uuid, count: usize },
+
+    /// The host renamed the lobby.
+    LobbyName(String),
+
+    /// Successfully connected to the draft as a spectator.
+    Spectating { draft: Uuid, name: Option<String> },
+
+    /// Number of packs waiting for each seat. Sent to spectators as they join
+    /// a draft in progress and whenever packs are passed.
+    SpectatorView(HashMap<Uuid, usize>),
+
+    /// Now following this seat. Further packs, picks and pools for the seat
+    /// will be forwarded to the spectator.
+    Following {
+        seat: Uuid,
+        pool: Vec<Card>,
+        pack: Option<DraftPack>,
+    },
+
+    /// Deckbuilding has begun, submit a deck of this many cards from your
+    /// pool.
+    DeckBuilding(usize),
+
+    /// Deck was accepted. May be resubmitted until deckbuilding ends.
+    DeckSubmitted(Vec<Card>),
+
+    /// Deck wasn't accepted, for the given reason.
+    DeckRejected(String),
+
+    /// Seconds left to submit a deck. Once they are up, a deck is built from
+    /// the pool of each player who hasn't submitted one.
+    DeckDeadline(u64),
+
+    /// The deadline passed without a deck being submitted, so this deck was
+    /// built and submitted automatically.
+    DeckAutoSubmitted(Vec<Card>),
+
+    /// Deckbuilding finished, here's each player's deck.
+    Decks(Pools),
+
+    /// A pick from the draft being replayed.
+    ReplayPick(PickEvent),
+
+    /// Every pick made before the replay was requested has been sent.
+    ReplayFinished,
+
+    /// The host removed you from the draft.
+    Kicked,
+
+    /// State of a Winston draft, sent to the table after each action.
+    Winston(WinstonState),
+
+    /// Your turn in a Winston draft, here are the cards in the pile you're
+    /// looking at.
+    WinstonPile(Vec<Card>),
+
+    /// You took these cards in a Winston draft.
+    WinstonTaken(Vec<Card>),
+
+    /// Winston action wasn't accepted, for the given reason.
+    WinstonRejected(String),
+
+    /// Slots of the grid in a grid draft, row by row, sent to the table after
+    /// each line is taken.
+    Grid(Vec<Option<Card>>),
+
+    /// Your turn to take a row or column of the grid.
+    GridTurn,
+
+    /// You took these cards from the grid.
+    GridTaken(Vec<Card>),
+
+    /// Grid selection wasn't accepted, for the given reason.
+    GridRejected(String),
+
+    /// Code which can be used to reclaim your seat. Only a hash of the code
+    /// is kept, so it can't be sent again.
+    ReclaimCode(String),
+
+    /// Seat couldn't be reclaimed, for this reason.
+    ReclaimRejected(String),
+
+    /// Your turn to pick from the open pack in a rochester draft.
+    YourTurn,
+
+    /// Waiting for this seat to pick from the open pack in a rochester
+    /// draft. Sent after the pack, which is shown to the whole table.
+    WaitingFor(Uuid),
+
+    /// The draft is over and the table is back in the lobby, where players
+    /// can ready up to draft again.
+    ReturnedToLobby,
 }
 
 #[derive(Debug, serde::Deserialize)]
@@ -80,17 +283,288 @@ pub enum ClientMessage {
     HeartBeat,
     ReadyState(bool),
     Disconnected,
+
+    /// Leave the draft for good. In the lobby this frees the seat, later the
+    /// seat is marked as abandoned.
+    Leave,
     SetName(String),
-    Pick(usize),
+    /// Pick the card at the index in the pack with the given ID. Picks for any
+    /// pack other than the player's current pack are rejected, so that a
+    /// repeated pick can't take a card from the next pack.
+    Pick {
+        pack: usize,
+        index: usize,
+    },
+
+    /// Undo your last pick, taking back the pack you passed. Only possible
+    /// until the next player picks from it.
+    UndoPick,
+    SetLobbyName(String),
+
+    /// Look at the pack queued after the current one, without picking.
+    PeekNext,
+
+    /// Spectator only. See the draft from the perspective of this seat.
+    Follow(Uuid),
+
+    /// Indices of the cards in the player's pool to use as their deck.
+    SubmitDeck(Vec<usize>),
+
+    /// Host only. End deckbuilding without waiting for every deck.
+    EndBuilding,
+
+    /// Spectator only. Replay the picks made so far, at this multiple of the
+    /// speed at which they were made.
+    Replay(f32),
+
+    /// Host only. Remove this seat from the lobby or draft.
+    Kick(Uuid),
+
+    /// Take or pass the pile you're looking at in a Winston draft.
+    Winston(WinstonAction),
+
+    /// Take a row or column of the grid in a grid draft.
+    Grid(GridSelection),
+
+    /// Request a code which can be used to reclaim your seat, for example
+    /// from another device. Replaces any code issued before.
+    RequestReclaimCode,
+
+    /// Take over the seat the reclaim code was issued for.
+    Reclaim(String),
+}
+
+/// A pick made during the draft, recorded so that the draft can be replayed.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct PickEvent {
+    seat: Uuid,
+    round: usize,
+    pack: usize,
+    card: Card,
+
+    /// Seat which opened the pack the card was picked from.
+    origin: Uuid,
+
+    /// Milliseconds since the draft began.
+    at: u64,
+}
+
+/// A player's pool once drafting has finished.
+#[derive(Debug)]
+pub struct SeatPool {
+    pub seat: Uuid,
+    pub name: String,
+    pub cards: Vec<Card>,
+}
+
+/// A player's pool and deck in the draft results.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct SeatResult {
+    seat: Uuid,
+    name: String,
+    pool: Vec<Card>,
+
+    /// Deck submitted by the player, if there was deckbuilding.
+    deck: Option<Vec<Card>>,
+}
+
+/// Outcome of a finished draft, for league and rating tools.
+#[derive(Clone, Debug, serde::Deserialize, serde::Serialize)]
+pub struct DraftResults {
+    draft: Uuid,
+    name: Option<String>,
+    seats: Vec<SeatResult>,
+    config: DraftConfig,
+
+    /// When the draft began and finished, in seconds since the Unix epoch.
+    started: Option<u64>,
+    finished: u64,
+}
+
+/// Summary of the state of a lobby, for tools polling it over HTTP.
+#[derive(Debug, serde::Serialize)]
+pub struct LobbyStatus {
+    phase: &'static str,
+    players: usize,
+
+    /// Number of players ready to start. Every player is ready once the
+    /// draft has begun.
+    ready: usize,
+
+    /// Current round, starting from one, while drafting.
+    round: Option<usize>,
+
+    /// Whether new players may join the table.
+    joinable: bool,
+}
+
+/// Status of an open lobby, as listed for players looking for a table.
+#[derive(Debug, serde::Serialize)]
+pub struct LobbyListing {
+    id: Uuid,
+
+    #[serde(flatten)]
+    status: LobbyStatus,
+}
+
+/// A seat at the table, as saved in a snapshot.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct SeatSnapshot {
+    id: Uuid,
+    name: String,
+    left: bool,
+}
+
+/// State of a lobby saved to disk, from which it can be restored if the
+/// server restarts. Connections and timers aren't saved, nor is the webhook
+/// URL, as it may hold a secret, so restored lobbies don't send webhook
+/// events.
+#[derive(serde::Deserialize, serde::Serialize)]
+struct DraftSnapshot {
+    id: Uuid,
+    name: Option<String>,
+    host: Option<Uuid>,
+    config: DraftConfig,
+    phase: Phase,
+    seats: Vec<SeatSnapshot>,
+    picks: Vec<PickEvent>,
+    opened_packs: Vec<OpenedPack>,
+    #[serde(default)]
+    pick_log: HashMap<Uuid, Vec<PickRecord>>,
+    kicked: HashSet<Uuid>,
+    bots: HashSet<Uuid>,
+    #[serde(default)]
+    seat_names: HashMap<Uuid, String>,
+    retained_pool: Option<DraftPool>,
+    started_at: Option<SystemTime>,
+    results: Option<DraftResults>,
+    #[serde(default)]
+    reclaim_codes: HashMap<String, Uuid>,
+}
+
+/// Where lobbies are saved, and the longest a change may go unsaved.
+#[derive(Clone, Debug)]
+pub struct SnapshotConfig {
+    pub dir: PathBuf,
+    pub interval: Duration,
+}
+
+/// Handle to a task which writes snapshots of a lobby to disk, so that the
+/// lobby never waits on the filesystem. If several snapshots are sent while
+/// one is being written, only the latest is written next. The snapshot file
+/// is removed once the handle is dropped, when the lobby closes.
+struct SnapshotWriter {
+    latest: watch::Sender<Option<Arc<DraftSnapshot>>>,
+    interval: Duration,
+}
+
+impl SnapshotWriter {
+    fn spawn(config: SnapshotConfig, lobby: Uuid) -> Self {
+        let (send, recv) = watch::channel(None);
+        let path = config.dir.join(format!("{lobby}.json"));
+        tokio::spawn(write_snapshots(path, recv));
+        Self {
+            latest: send,
+            interval: config.interval,
+        }
+    }
+
+    fn save(&self, snapshot: DraftSnapshot) {
+        self.latest.send_replace(Some(Arc::new(snapshot)));
+    }
+}
+
+/// Write each snapshot sent to the path, removing the file once the sender
+/// is dropped. Snapshots are written to a temporary file first, so that a
+/// crash mid-write doesn't corrupt the last one.
+async fn write_snapshots(path: PathBuf, mut latest: watch::Receiver<Option<Arc<DraftSnapshot>>>) {
+    while latest.changed().await.is_ok() {
+        let Some(snapshot) = latest.borrow_and_update().clone() else {
+            continue;
+        };
+
+        let temp = path.with_extension("json.tmp");
+        let dest = path.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            let json = serde_json::to_vec(&*snapshot).map_err(|e| e.to_string())?;
+            std::fs::write(&temp, json).map_err(|e| e.to_string())?;
+            std::fs::rename(&temp, &dest).map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| e.to_string())
+        .and_then(|result| result);
+        if let Err(e) = result {
+            tracing::warn!("Failed to save snapshot to {}: {e}", path.display());
+        }
+    }
+
+    tokio::fs::remove_file(&path).await.ok();
+}
+
+fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
 }
 
 #[derive(Debug)]
 pub enum DraftServerRequest {
     Connect(Uuid, UnboundedSender<ServerMessage>),
+    Spectate(Uuid, UnboundedSender<ServerMessage>),
     Message(Uuid, ClientMessage),
     Terminate(String),
+
+    /// Request each player's final pool. Responds with None if drafting
+    /// hasn't finished.
+    Pools(oneshot::Sender<Option<Vec<SeatPool>>>),
+
+    /// Request the results of the draft, to share with other tools. Responds
+    /// with None if no draft has finished.
+    Results(oneshot::Sender<Option<DraftResults>>),
+
+    /// Request the number of cards of each rarity left to draft and picked.
+    /// Responds with None if the draft isn't in progress.
+    RarityCounts(oneshot::Sender<Option<RarityCounts>>),
+
+    /// Request a pack generated from a copy of the lobby's pool, to preview
+    /// the draft. Responds with None if the draft has already started.
+    SamplePack(oneshot::Sender<Option<Res<Pack>>>),
+
+    /// Request every pack opened in the draft, as it was opened, in order.
+    /// Responds with None if the draft hasn't started.
+    OpenedPacks(oneshot::Sender<Option<Vec<OpenedPack>>>),
+
+    /// Request the picks made by a seat, in order. Responds with None until
+    /// drafting has finished, as picks are private until then, and with
+    /// Some(None) if the seat isn't in the draft.
+    PickLog(Uuid, oneshot::Sender<Option<Option<Vec<PickRecord>>>>),
+
+    /// Request a summary of the state of the lobby.
+    Status(oneshot::Sender<LobbyStatus>),
+}
+
+impl DraftServerRequest {
+    /// Whether handling the request may change the state saved in a
+    /// snapshot. Spectators and queries don't.
+    fn changes_state(&self) -> bool {
+        match self {
+            DraftServerRequest::Connect(..) | DraftServerRequest::Terminate(..) => true,
+            DraftServerRequest::Message(_, message) => !matches!(
+                message,
+                ClientMessage::HeartBeat
+                    | ClientMessage::PeekNext
+                    | ClientMessage::Follow(..)
+                    | ClientMessage::Replay(..)
+            ),
+            _ => false,
+        }
+    }
 }
 
+/// Constructor for the request used to join a draft server, either
+/// `DraftServerRequest::Connect` or `DraftServerRequest::Spectate`.
+pub type ConnectRequest = fn(Uuid, UnboundedSender<ServerMessage>) -> DraftServerRequest;
+
 #[derive(Clone)]
 pub struct ServerHandle {
     id: Uuid,
@@ -109,34 +583,180 @@ impl ServerHandle {
 
 pub struct ServerPool {
     servers: HashMap<Uuid, ServerHandle>,
+
+    /// Token of the user who launched each lobby, where known.
+    owners: HashMap<Uuid, String>,
+
+    /// How long a lobby may go without any connected players before it is
+    /// closed.
+    lobby_timeout: Duration,
+
+    /// How long a lobby may stay open without the draft starting.
+    idle_expiry: Duration,
+
+    /// Where lobbies are saved, so that they survive a restart.
+    snapshots: Option<SnapshotConfig>,
 }
 
 impl ServerPool {
-    pub fn new() -> Self {
+    pub fn new(lobby_timeout: Duration, idle_expiry: Duration) -> Self {
         Self {
             servers: HashMap::new(),
+            owners: HashMap::new(),
+            lobby_timeout,
+            idle_expiry,
+            snapshots: None,
+        }
+    }
+
+    /// Restore the lobbies saved in the directory, and save lobbies there
+    /// from now on. Returns the number of lobbies restored.
+    pub fn restore(&mut self, snapshots: SnapshotConfig) -> usize {
+        let dir = &snapshots.dir;
+        if let Err(e) = std::fs::create_dir_all(dir) {
+            tracing::warn!("Failed to create snapshot directory {}: {e}", dir.display());
+        }
+
+        let mut restored = 0;
+        let entries = std::fs::read_dir(dir).into_iter().flatten().flatten();
+        for path in entries.map(|entry| entry.path()) {
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+
+            let snapshot = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<DraftSnapshot>(&json).map_err(|e| e.to_string())
+                });
+            match snapshot {
+                Ok(snapshot) => {
+                    let handle = DraftServer::restore(
+                        snapshot,
+                        self.lobby_timeout,
+                        self.idle_expiry,
+                        Some(snapshots.clone()),
+                    );
+                    self.servers.insert(handle.id, handle);
+                    restored += 1;
+                }
+                Err(e) => tracing::warn!("Failed to restore lobby from {}: {e}", path.display()),
+            }
         }
+
+        self.snapshots = Some(snapshots);
+        restored
     }
 
-    pub(super) fn spawn(&mut self, config: DraftConfig, pool: DraftPool) -> Uuid {
-        let handle = DraftServer::spawn(config, pool);
+    pub(super) fn spawn(
+        &mut self,
+        config: DraftConfig,
+        pool: DraftPool,
+        name: Option<String>,
+        owner: Option<String>,
+    ) -> Uuid {
+        self.reap();
+
+        let handle = DraftServer::spawn(
+            config,
+            pool,
+            name,
+            self.lobby_timeout,
+            self.idle_expiry,
+            self.snapshots.clone(),
+        );
         let id = handle.id;
         self.servers.insert(id, handle);
+        if let Some(owner) = owner {
+            self.owners.insert(id, owner);
+        }
         id
     }
 
+    /// Forget lobbies which have since closed, returning the number removed.
+    pub fn reap(&mut self) -> usize {
+        let before = self.servers.len();
+        self.servers.retain(|_, handle| handle.is_open());
+        self.owners.retain(|id, _| self.servers.contains_key(id));
+        before - self.servers.len()
+    }
+
+    /// Number of open lobbies launched by the user with this token.
+    pub fn owned_by(&self, owner: &str) -> usize {
+        self.owners
+            .iter()
+            .filter(|(id, token)| *token == owner && self.handle(**id).is_some())
+            .count()
+    }
+
     pub fn handle(&self, id: Uuid) -> Option<ServerHandle> {
-        self.servers.get(&id).cloned()
+        self.servers
+            .get(&id)
+            .filter(|handle| handle.is_open())
+            .cloned()
+    }
+
+    /// Request the status of every open lobby at once. The returned future
+    /// doesn't borrow the pool, so it can be awaited after releasing the
+    /// lock on it. Lobbies which don't answer within `STATUS_TIMEOUT` are
+    /// left out.
+    pub fn iter_status(&self) -> impl Future<Output = Vec<LobbyListing>> + use<> {
+        let pending: Vec<(Uuid, oneshot::Receiver<LobbyStatus>)> = self
+            .servers
+            .values()
+            .filter(|handle| handle.is_open())
+            .map(|handle| {
+                let (send, recv) = oneshot::channel();
+                handle.send(DraftServerRequest::Status(send));
+                (handle.id, recv)
+            })
+            .collect();
+
+        async move {
+            let deadline = Instant::now() + STATUS_TIMEOUT;
+            let mut listings = Vec::new();
+            for (id, recv) in pending {
+                if let Ok(Ok(status)) = tokio::time::timeout_at(deadline, recv).await {
+                    listings.push(LobbyListing { id, status });
+                }
+            }
+            listings
+        }
     }
 }
 
+/// Map from seat to a collection of cards, either a pool or a deck.
+type Pools = HashMap<Uuid, Vec<Card>>;
+
+#[derive(Clone, serde::Deserialize, serde::Serialize)]
 enum Phase {
-    Lobby(HashMap<Uuid, bool>, DraftConfig, DraftPool),
-    Draft(Draft),
-    Finished(HashMap<Uuid, Vec<Card>>),
+    Lobby(HashMap<Uuid, bool>, DraftPool),
+    Draft(Box<Draft>),
+    Winston(WinstonDraft),
+    Grid(GridDraft),
+
+    /// Players are building decks. Holds each player's pool, the decks
+    /// submitted so far and when any remaining decks will be submitted
+    /// automatically, if there is a deadline.
+    Building(Pools, Pools, #[serde(skip)] Option<Instant>),
+
+    /// Holds each player's pool and deck, if there was deckbuilding.
+    Finished(Pools, Pools),
     Terminated,
 }
 
+impl Phase {
+    fn name(&self) -> &'static str {
+        match self {
+            Phase::Lobby(..) => "lobby",
+            Phase::Draft(..) | Phase::Winston(..) | Phase::Grid(..) => "draft",
+            Phase::Building(..) => "building",
+            Phase::Finished(..) => "finished",
+            Phase::Terminated => "terminated",
+        }
+    }
+}
+
 struct Client {
     id: Uuid,
     name: String,
@@ -151,7 +771,9 @@ impl Client {
     }
 
     fn status(&self) -> ClientStatus {
-        if self.chan.is_closed() {
+        if self.known_status == ClientStatus::Left {
+            ClientStatus::Left
+        } else if self.chan.is_closed() {
             ClientStatus::Error
         } else {
             self.known_status
@@ -159,6 +781,38 @@ impl Client {
     }
 }
 
+struct Spectator {
+    id: Uuid,
+    chan: UnboundedSender<ServerMessage>,
+
+    /// Seat whose packs and picks are forwarded to this spectator, if any.
+    following: Option<Uuid>,
+
+    /// Task replaying the draft to this spectator, if any. Only one replay
+    /// runs at a time.
+    replay: Option<JoinHandle<()>>,
+}
+
+impl Spectator {
+    fn send(&self, message: ServerMessage) {
+        self.chan.send(message).ok();
+    }
+
+    fn stop_replay(&mut self) {
+        if let Some(replay) = self.replay.take() {
+            replay.abort();
+        }
+    }
+}
+
+/// Replays are stopped once the spectator leaves, rather than running on
+/// until their next send fails.
+impl Drop for Spectator {
+    fn drop(&mut self) {
+        self.stop_replay();
+    }
+}
+
 struct DraftClients {
     clients: Vec<Client>,
 }
@@ -168,6 +822,10 @@ impl DraftClients {
         self.clients.is_empty()
     }
 
+    fn len(&self) -> usize {
+        self.clients.len()
+    }
+
     fn add(&mut self, client: Client) {
         self.clients.push(client);
     }
@@ -189,123 +847,854 @@ impl DraftClients {
     }
 }
 
-pub struct DraftServer {
-    id: Uuid,
-    phase: Phase,
-    chan: UnboundedReceiver<DraftServerRequest>,
-    clients: DraftClients,
-}
+/// Longest that player updates are held back to be coalesced with others
+/// while the server is busy.
+const PLAYER_UPDATE_WINDOW: Duration = Duration::from_millis(100);
 
-impl DraftServer {
-    fn spawn(config: DraftConfig, pool: DraftPool) -> ServerHandle {
-        let id = Uuid::new_v4();
-        let (send, recv) = tokio::sync::mpsc::unbounded_channel();
+/// Limits on the playback speed of a replay.
+const MIN_REPLAY_SPEED: f32 = 0.25;
+const MAX_REPLAY_SPEED: f32 = 100.0;
 
-        tokio::spawn(async move {
-            let mut server = DraftServer {
-                id,
-                phase: Phase::Lobby(HashMap::new(), config, pool),
-                chan: recv,
-                clients: DraftClients {
-                    clients: Vec::new(),
-                },
-            };
-            server.run().await;
-        });
+/// Longest to wait for lobbies to report their status when listing them.
+const STATUS_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// Characters of a code used to reclaim a seat, leaving out those easily
+/// mistaken for each other, and the number of them in a code.
+const RECLAIM_CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const RECLAIM_CODE_LENGTH: usize = 12;
 
-        ServerHandle { id, chan: send }
+/// Number of wrong codes a connection may try before it is refused.
+const MAX_RECLAIM_ATTEMPTS: usize = 5;
+
+/// Most connections kept waiting to reclaim a seat at once, and how long
+/// each is kept for.
+const MAX_UNSEATED: usize = 16;
+const UNSEATED_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Maximum length of a lobby name, in characters.
+const MAX_LOBBY_NAME_LENGTH: usize = 64;
+
+/// Validate a lobby name, stripping any control characters and surrounding
+/// whitespace.
+pub fn clean_lobby_name(name: &str) -> Res<String> {
+    let name: String = name.chars().filter(|c| !c.is_control()).collect();
+    let name = name.trim();
+    if name.is_empty() {
+        err("Lobby name must not be empty.")
+    } else if name.chars().count() > MAX_LOBBY_NAME_LENGTH {
+        err(format!(
+            "Lobby name must be at most {MAX_LOBBY_NAME_LENGTH} characters."
+        ))
+    } else {
+        Ok(name.to_string())
     }
+}
 
-    fn terminate(&mut self, error: String) {
-        self.phase = Phase::Terminated;
-        self.chan.close();
-        self.broadcast(ServerMessage::FatalError(error), None);
+/// Take the cards at the given indices of a player's pool as their deck,
+/// checking that the deck is the required size and uses each card at most
+/// once.
+fn build_deck(pool: &[Card], indices: &[usize], size: usize) -> Res<Vec<Card>> {
+    if indices.len() != size {
+        return err(format!("Deck must contain exactly {size} cards."));
     }
 
-    fn broadcast(&self, message: ServerMessage, exclude: Option<Uuid>) {
-        for client in self.clients.iter() {
-            if Some(client.id) != exclude {
-                client.send(message.clone());
-            }
+    let mut used = HashSet::new();
+    for &index in indices {
+        if index >= pool.len() {
+            return err("Deck contains a card not in your pool.");
+        } else if !used.insert(index) {
+            return err("Deck contains the same card more than once.");
         }
     }
 
-    fn broadcast_player_update(&self, player: Uuid) {
-        let ready = if let Phase::Lobby(readys, ..) = &self.phase {
-            readys.get(&player).cloned().unwrap_or(false)
-        } else {
-            true
-        };
+    Ok(indices.iter().map(|&index| pool[index].clone()).collect())
+}
 
-        if let Some(client) = self.clients.get(player) {
-            self.broadcast(
-                ServerMessage::PlayerUpdate(PlayerDetails {
-                    seat: player,
-                    name: client.name.clone(),
-                    ready,
-                    status: client.status(),
-                }),
-                Some(player),
-            );
-        }
+/// Build a deck of the given size from a pool, for a player who didn't submit
+/// one in time. Favours cards in the pool's two most common colours, then
+/// colourless cards, breaking ties by rarity. Small pools are used whole.
+fn auto_deck(pool: &[Card], size: usize) -> Vec<Card> {
+    if pool.len() <= size {
+        return pool.to_vec();
     }
 
-    fn set_client_status(&mut self, id: Uuid, status: ClientStatus) {
-        if let Some(client) = self.clients.get_mut(id) {
-            if client.known_status != status {
-                client.known_status = status;
-                self.broadcast_player_update(id);
-            }
-        }
+    let mut counts: HashMap<Colour, usize> = HashMap::new();
+    for colour in pool.iter().flat_map(|card| card.colours.iter()) {
+        *counts.entry(*colour).or_default() += 1;
     }
+    let mut main = Colour::ALL.to_vec();
+    main.sort_by_key(|colour| std::cmp::Reverse(counts.get(colour).copied().unwrap_or(0)));
+    main.truncate(2);
 
-    async fn run(&mut self) {
-        while let Some(req) = self.chan.recv().await {
-            match req {
-                DraftServerRequest::Connect(id, chan) => self.handle_client_connection(id, chan),
-                DraftServerRequest::Message(id, msg) => self.handle_client_message(id, msg),
-                DraftServerRequest::Terminate(reason) => self.terminate(reason),
-            }
-        }
-    }
+    let mut cards: Vec<&Card> = pool.iter().collect();
+    cards.sort_by_key(|card| {
+        let on_colour = card.colours.iter().all(|colour| main.contains(colour));
+        let fit = match (on_colour, card.colours.is_empty()) {
+            (true, false) => 2,
+            (true, true) => 1,
+            (false, _) => 0,
+        };
+        std::cmp::Reverse((fit, card.rarity.rank()))
+    });
+    cards.into_iter().take(size).cloned().collect()
+}
 
-    fn handle_client_connection(&mut self, id: Uuid, chan: UnboundedSender<ServerMessage>) {
-        if let Some(client) = self.clients.get_mut(id) {
-            client.chan = chan;
-            self.set_client_status(id, ClientStatus::Ok);
-            let client = self.clients.get(id).unwrap(); // de-mut reference.
-            match &self.phase {
-                Phase::Lobby(..) => client.send(ServerMessage::Connected {
-                    draft: self.id,
-                    seat: id,
-                }),
-                Phase::Draft(draft) => {
-                    client.send(ServerMessage::Reconnected {
-                        draft: self.id,
-                        seat: id,
-                        in_progress: true,
-                        pool: draft.drafted_cards(id).cloned().unwrap_or_default(),
-                        pack: draft.current_pack(id),
-                    });
-                    client.send(ServerMessage::PlayerList(self.player_list()));
-                }
-                Phase::Finished(pools) => {
-                    client.send(ServerMessage::Reconnected {
-                        draft: self.id,
-                        seat: id,
-                        in_progress: false,
-                        pool: pools.get(&id).cloned().unwrap_or_default(),
-                        pack: None,
-                    });
-                    client.send(ServerMessage::PlayerList(self.player_list()));
-                }
-                Phase::Terminated => {
-                    client.send(ServerMessage::FatalError("Draft terminated.".into()))
-                }
-            }
-        } else if let Phase::Lobby(readys, ..) = &mut self.phase {
-            readys.insert(id, false);
-            let client = Client {
+pub struct DraftServer {
+    id: Uuid,
+    name: Option<String>,
+
+    /// Seat of the player who administers the lobby. This is the first player
+    /// to join, passing to the next seat if they leave the lobby.
+    host: Option<Uuid>,
+    config: DraftConfig,
+    phase: Phase,
+    chan: UnboundedReceiver<DraftServerRequest>,
+    clients: DraftClients,
+    spectators: Vec<Spectator>,
+    webhook: Option<Webhook>,
+
+    /// How long the lobby may go without any connected players before it is
+    /// closed, and when it was last left without any.
+    lobby_timeout: Duration,
+    abandoned_since: Option<Instant>,
+
+    /// When the lobby was created, and how long it may stay open without the
+    /// draft starting, even if players are connected.
+    created: Instant,
+    idle_expiry: Duration,
+
+    /// Players whose details have changed since the table was last updated,
+    /// and when the oldest of those changes was made.
+    pending_updates: HashSet<Uuid>,
+    pending_since: Option<Instant>,
+
+    /// When each player's current pack was delivered to them.
+    pack_delivered: HashMap<Uuid, Instant>,
+
+    /// When a card will be picked automatically from each player's current
+    /// pack, if there is a pick timer.
+    pick_deadlines: HashMap<Uuid, Instant>,
+
+    /// When the draft began, and every pick made since.
+    draft_started: Option<Instant>,
+    picks: Vec<PickEvent>,
+
+    /// Packs opened during the draft, and each seat's picks, kept once
+    /// drafting finishes.
+    opened_packs: Vec<OpenedPack>,
+    pick_log: HashMap<Uuid, Vec<PickRecord>>,
+
+    /// Seats removed by the host. Picks are made on behalf of kicked seats
+    /// still in the draft, so that the table isn't held up.
+    kicked: HashSet<Uuid>,
+
+    /// Seats added to the draft to fill the table, which pick as soon as a
+    /// pack reaches them. Bots have no connection, so are never sent messages.
+    bots: HashSet<Uuid>,
+
+    /// Names of seats in the draft without a connected player, bots and seats
+    /// kicked mid-draft, so that their pools can be named in the results.
+    seat_names: HashMap<Uuid, String>,
+
+    /// Copy of the pool the draft began with, kept to return to the lobby
+    /// with if the lobby is kept open.
+    retained_pool: Option<DraftPool>,
+
+    /// Lobby seats which have disconnected, and when. Each is freed once the
+    /// reconnection grace period passes, unless the player reconnects first.
+    away_since: HashMap<Uuid, Instant>,
+
+    /// Wall clock time the draft began, for the results.
+    started_at: Option<SystemTime>,
+
+    /// Results of the last draft to finish. Kept after returning to the lobby
+    /// until the next draft begins.
+    results: Option<DraftResults>,
+
+    /// Seat each reclaim code was issued for, keyed by a hash of the code,
+    /// so that players can reclaim their seat without its ID.
+    reclaim_codes: HashMap<String, Uuid>,
+
+    /// Connections which reclaimed a seat with a code, mapped to the seat
+    /// they act for.
+    aliases: HashMap<Uuid, Uuid>,
+
+    /// Connections which couldn't be seated as the draft had started, kept
+    /// for a while so that they can reclaim a seat with a code.
+    unseated: HashMap<Uuid, (UnboundedSender<ServerMessage>, Instant)>,
+
+    /// Number of wrong reclaim codes tried by each connection.
+    reclaim_attempts: HashMap<Uuid, usize>,
+
+    /// Writes snapshots of the lobby, if it is saved, and when changes to
+    /// the lobby are next due to be saved.
+    snapshots: Option<SnapshotWriter>,
+    snapshot_due: Option<Instant>,
+}
+
+impl DraftServer {
+    fn spawn(
+        config: DraftConfig,
+        pool: DraftPool,
+        name: Option<String>,
+        lobby_timeout: Duration,
+        idle_expiry: Duration,
+        snapshots: Option<SnapshotConfig>,
+    ) -> ServerHandle {
+        let phase = Phase::Lobby(HashMap::new(), pool);
+        let (mut server, handle) = Self::new(
+            Uuid::new_v4(),
+            config,
+            phase,
+            name,
+            lobby_timeout,
+            idle_expiry,
+        );
+        server.snapshots = snapshots.map(|config| SnapshotWriter::spawn(config, server.id));
+        server.start();
+        handle
+    }
+
+    /// Restore a lobby from a snapshot. Players are disconnected, but can
+    /// reconnect to their seats.
+    fn restore(
+        snapshot: DraftSnapshot,
+        lobby_timeout: Duration,
+        idle_expiry: Duration,
+        snapshots: Option<SnapshotConfig>,
+    ) -> ServerHandle {
+        let (mut server, handle) = Self::new(
+            snapshot.id,
+            snapshot.config,
+            snapshot.phase,
+            snapshot.name,
+            lobby_timeout,
+            idle_expiry,
+        );
+        let now = Instant::now();
+        server.snapshots = snapshots.map(|config| SnapshotWriter::spawn(config, server.id));
+        server.host = snapshot.host;
+        for seat in snapshot.seats {
+            // The receiver is dropped, so the seat shows as disconnected
+            // until the player reconnects.
+            let (chan, _) = tokio::sync::mpsc::unbounded_channel();
+            server.clients.add(Client {
+                id: seat.id,
+                name: seat.name,
+                chan,
+                known_status: if seat.left {
+                    ClientStatus::Left
+                } else {
+                    ClientStatus::Ok
+                },
+                heartbeat: now,
+            });
+            if matches!(server.phase, Phase::Lobby(..)) {
+                server.away_since.insert(seat.id, now);
+            }
+        }
+        server.picks = snapshot.picks;
+        server.opened_packs = snapshot.opened_packs;
+        server.pick_log = snapshot.pick_log;
+        server.kicked = snapshot.kicked;
+        server.bots = snapshot.bots;
+        server.seat_names = snapshot.seat_names;
+        server.retained_pool = snapshot.retained_pool;
+        server.started_at = snapshot.started_at;
+        server.results = snapshot.results;
+        server.reclaim_codes = snapshot.reclaim_codes;
+
+        // Timers restart from now, as the server may have been down for a
+        // while.
+        server.draft_started = snapshot
+            .started_at
+            .and_then(|started| started.elapsed().ok())
+            .and_then(|elapsed| now.checked_sub(elapsed));
+        if let Phase::Building(.., deadline) = &mut server.phase {
+            *deadline = server.config.deck_deadline.map(|limit| now + limit);
+        }
+        if let Phase::Draft(draft) = &server.phase {
+            let holding: Vec<Uuid> = server
+                .clients
+                .iter()
+                .map(|client| client.id)
+                .filter(|&id| draft.current_pack(id).is_some())
+                .collect();
+            for id in holding {
+                server.pack_delivered.insert(id, now);
+                if let Some(timer) = server.config.pick_timer
+                    && !server.kicked.contains(&id)
+                {
+                    server.pick_deadlines.insert(id, now + timer);
+                }
+            }
+        }
+
+        server.start();
+        handle
+    }
+
+    fn new(
+        id: Uuid,
+        config: DraftConfig,
+        phase: Phase,
+        name: Option<String>,
+        lobby_timeout: Duration,
+        idle_expiry: Duration,
+    ) -> (Self, ServerHandle) {
+        let (send, recv) = tokio::sync::mpsc::unbounded_channel();
+        let webhook = config
+            .webhook_url
+            .clone()
+            .map(|url| Webhook::spawn(url, config.allow_internal_webhook));
+        let server = DraftServer {
+            id,
+            name,
+            host: None,
+            config,
+            phase,
+            chan: recv,
+            clients: DraftClients {
+                clients: Vec::new(),
+            },
+            spectators: Vec::new(),
+            webhook,
+            lobby_timeout,
+
+            // No one has joined yet, so the timer starts immediately.
+            abandoned_since: Some(Instant::now()),
+            created: Instant::now(),
+            idle_expiry,
+            pending_updates: HashSet::new(),
+            pending_since: None,
+            pack_delivered: HashMap::new(),
+            pick_deadlines: HashMap::new(),
+            draft_started: None,
+            picks: Vec::new(),
+            opened_packs: Vec::new(),
+            pick_log: HashMap::new(),
+            kicked: HashSet::new(),
+            bots: HashSet::new(),
+            seat_names: HashMap::new(),
+            retained_pool: None,
+            away_since: HashMap::new(),
+            started_at: None,
+            results: None,
+            reclaim_codes: HashMap::new(),
+            aliases: HashMap::new(),
+            unseated: HashMap::new(),
+            reclaim_attempts: HashMap::new(),
+            snapshots: None,
+            snapshot_due: None,
+        };
+
+        (server, ServerHandle { id, chan: send })
+    }
+
+    fn start(mut self) {
+        // The snapshot is removed when the server is dropped.
+        tokio::spawn(async move { self.run().await });
+    }
+
+    /// Capture the state of the lobby, so that it can be restored if the
+    /// server restarts.
+    fn snapshot(&self) -> DraftSnapshot {
+        DraftSnapshot {
+            id: self.id,
+            name: self.name.clone(),
+            host: self.host,
+            config: self.config.clone(),
+            phase: self.phase.clone(),
+            seats: self
+                .clients
+                .iter()
+                .map(|client| SeatSnapshot {
+                    id: client.id,
+                    name: client.name.clone(),
+                    left: client.known_status == ClientStatus::Left,
+                })
+                .collect(),
+            picks: self.picks.clone(),
+            opened_packs: self.opened_packs.clone(),
+            pick_log: self.pick_log.clone(),
+            kicked: self.kicked.clone(),
+            bots: self.bots.clone(),
+            seat_names: self.seat_names.clone(),
+            retained_pool: self.retained_pool.clone(),
+            started_at: self.started_at,
+            results: self.results.clone(),
+            reclaim_codes: self.reclaim_codes.clone(),
+        }
+    }
+
+    /// Note that the lobby has changed, so that a snapshot is saved once the
+    /// snapshot interval has passed. Changes made in the meantime are saved
+    /// with it.
+    fn schedule_snapshot(&mut self) {
+        if let Some(writer) = &self.snapshots {
+            self.snapshot_due
+                .get_or_insert_with(|| Instant::now() + writer.interval);
+        }
+    }
+
+    /// Send a snapshot of the lobby to be written to disk.
+    fn save_snapshot(&mut self) {
+        self.snapshot_due = None;
+        if let Some(writer) = &self.snapshots
+            && !matches!(self.phase, Phase::Terminated)
+        {
+            writer.save(self.snapshot());
+        }
+    }
+
+    fn terminate(&mut self, error: DraftError) {
+        self.phase = Phase::Terminated;
+        self.chan.close();
+        self.stop_replays();
+        self.broadcast(ServerMessage::FatalError(error), None);
+    }
+
+    /// Close a lobby which was never started, telling everyone in it that the
+    /// draft is over.
+    fn expire(&mut self) {
+        self.phase = Phase::Terminated;
+        self.chan.close();
+        self.stop_replays();
+        self.broadcast(ServerMessage::Ended, None);
+    }
+
+    /// Stop every replay in progress, as the picks being replayed are no
+    /// longer those of the lobby's draft.
+    fn stop_replays(&mut self) {
+        for spectator in &mut self.spectators {
+            spectator.stop_replay();
+        }
+    }
+
+    /// Log a protocol message to or from the given seat, if message logging
+    /// is enabled for this lobby.
+    fn log_message<M: Debug>(&self, seat: Option<Uuid>, direction: &str, message: &M) {
+        if self.config.log_messages {
+            tracing::trace!(
+                lobby = %self.id,
+                seat = ?seat,
+                phase = self.phase.name(),
+                "{direction} {message:?}"
+            );
+        }
+    }
+
+    fn broadcast(&self, message: ServerMessage, exclude: Option<Uuid>) {
+        self.log_message(None, "broadcast", &message);
+        for client in self.clients.iter() {
+            if Some(client.id) != exclude {
+                client.send(message.clone());
+            }
+        }
+        for spectator in &self.spectators {
+            spectator.send(message.clone());
+        }
+    }
+
+    fn notify(&self, event: WebhookEvent) {
+        if let Some(webhook) = &self.webhook {
+            webhook.send(event);
+        }
+    }
+
+    /// Queue an update of this player's details to the rest of the table.
+    /// Updates are sent once the server has handled any other waiting
+    /// requests, see flush_player_updates.
+    fn broadcast_player_update(&mut self, player: Uuid) {
+        self.pending_updates.insert(player);
+        self.pending_since.get_or_insert_with(Instant::now);
+    }
+
+    /// Send queued player updates. A single update is sent as is, while
+    /// several are coalesced into a single player list. Details are read at
+    /// this point, so the table always receives the latest.
+    fn flush_player_updates(&mut self) {
+        self.pending_since = None;
+        let pending = std::mem::take(&mut self.pending_updates);
+        if pending.len() == 1 {
+            let player = pending.into_iter().next().unwrap();
+            if let Some(details) = self.details_of(player) {
+                self.broadcast(ServerMessage::PlayerUpdate(details), Some(player));
+            }
+        } else if !pending.is_empty() {
+            self.broadcast(ServerMessage::PlayerList(self.player_list()), None);
+        }
+    }
+
+    fn set_client_status(&mut self, id: Uuid, status: ClientStatus) {
+        if let Some(client) = self.clients.get_mut(id) {
+            if client.known_status != status {
+                client.known_status = status;
+                self.broadcast_player_update(id);
+            }
+        }
+    }
+
+    async fn run(&mut self) {
+        loop {
+            let abandoned = self.abandoned_since.map(|since| since + self.lobby_timeout);
+            let building = match self.phase {
+                Phase::Building(.., deadline) => deadline,
+                _ => None,
+            };
+            let idle =
+                matches!(self.phase, Phase::Lobby(..)).then(|| self.created + self.idle_expiry);
+            let away = self
+                .away_since
+                .values()
+                .min()
+                .map(|since| *since + self.config.reconnect_grace);
+            let pick = self.pick_deadlines.values().min().copied();
+            let unseated = self
+                .unseated
+                .values()
+                .map(|(_, since)| *since + UNSEATED_TIMEOUT)
+                .min();
+            let snapshot = self.snapshot_due;
+            let deadline = abandoned
+                .into_iter()
+                .chain(idle)
+                .chain(away)
+                .chain(building)
+                .chain(pick)
+                .chain(unseated)
+                .chain(snapshot)
+                .min();
+
+            tokio::select! {
+                req = self.chan.recv() => match req {
+                    Some(req) => {
+                        if req.changes_state() {
+                            self.schedule_snapshot();
+                        }
+                        self.handle_request(req);
+
+                        // Hold updates back while more requests are waiting,
+                        // so that a burst of changes is sent together.
+                        if self.chan.is_empty()
+                            || self
+                                .pending_since
+                                .is_some_and(|since| since.elapsed() >= PLAYER_UPDATE_WINDOW)
+                        {
+                            self.flush_player_updates();
+                        }
+                    }
+                    None => break,
+                },
+                _ = sleep_until(deadline.unwrap_or_else(Instant::now)), if deadline.is_some() => {
+                    let now = Instant::now();
+                    if snapshot.is_some_and(|snapshot| snapshot <= now) {
+                        self.save_snapshot();
+                        continue;
+                    }
+
+                    if idle.is_some_and(|idle| idle <= now) {
+                        self.expire();
+                    } else if abandoned.is_some_and(|abandoned| abandoned <= now) {
+                        self.terminate(DraftError::Other(
+                            "Lobby closed as no players joined.".into(),
+                        ));
+                    } else if building.is_some_and(|building| building <= now) {
+                        self.auto_submit_decks();
+                    } else if pick.is_some_and(|pick| pick <= now) {
+                        self.pick_for_late();
+                    } else if unseated.is_some_and(|unseated| unseated <= now) {
+                        self.unseated
+                            .retain(|_, (_, since)| *since + UNSEATED_TIMEOUT > now);
+                    } else {
+                        self.free_away_seats();
+                    }
+                    self.flush_player_updates();
+                    self.schedule_snapshot();
+                }
+            }
+
+            self.update_abandoned();
+        }
+    }
+
+    fn handle_request(&mut self, req: DraftServerRequest) {
+        match req {
+            DraftServerRequest::Connect(id, chan) => self.handle_client_connection(id, chan),
+            DraftServerRequest::Spectate(id, chan) => self.handle_spectator_connection(id, chan),
+            DraftServerRequest::Message(id, msg) => self.handle_client_message(id, msg),
+            DraftServerRequest::Terminate(reason) => self.terminate(DraftError::Other(reason)),
+            DraftServerRequest::Results(respond) => {
+                respond.send(self.results.clone()).ok();
+            }
+            DraftServerRequest::Pools(respond) => {
+                respond.send(self.final_pools()).ok();
+            }
+            DraftServerRequest::RarityCounts(respond) => {
+                let counts = match &self.phase {
+                    Phase::Draft(draft) => Some(draft.remaining_by_rarity()),
+                    _ => None,
+                };
+                respond.send(counts).ok();
+            }
+            DraftServerRequest::SamplePack(respond) => {
+                respond.send(self.sample_pack()).ok();
+            }
+            DraftServerRequest::OpenedPacks(respond) => {
+                let packs = match &self.phase {
+                    Phase::Draft(draft) => Some(draft.opened_packs().to_vec()),
+                    Phase::Building(..) | Phase::Finished(..) => Some(self.opened_packs.clone()),
+                    Phase::Lobby(..) | Phase::Winston(..) | Phase::Grid(..) | Phase::Terminated => {
+                        None
+                    }
+                };
+                respond.send(packs).ok();
+            }
+            DraftServerRequest::PickLog(seat, respond) => {
+                let log = match &self.phase {
+                    Phase::Building(..) | Phase::Finished(..) => {
+                        Some(self.pick_log.get(&seat).cloned())
+                    }
+                    Phase::Lobby(..)
+                    | Phase::Draft(..)
+                    | Phase::Winston(..)
+                    | Phase::Grid(..)
+                    | Phase::Terminated => None,
+                };
+                respond.send(log).ok();
+            }
+            DraftServerRequest::Status(respond) => {
+                respond.send(self.status()).ok();
+            }
+        }
+    }
+
+    fn status(&self) -> LobbyStatus {
+        let players = self.clients.len();
+        let (ready, round, joinable) = match &self.phase {
+            Phase::Lobby(ready, _) => (
+                ready.values().filter(|ready| **ready).count(),
+                None,
+                self.config.max_players.is_none_or(|max| players < max),
+            ),
+            Phase::Draft(draft) => (players, Some(draft.round()), false),
+            _ => (players, None, false),
+        };
+
+        LobbyStatus {
+            phase: self.phase.name(),
+            players,
+            ready,
+            round,
+            joinable,
+        }
+    }
+
+    /// Generate a first round pack from a copy of the pool, if the draft
+    /// hasn't started yet.
+    fn sample_pack(&self) -> Option<Res<Pack>> {
+        let Phase::Lobby(_, pool) = &self.phase else {
+            return None;
+        };
+
+        Some(make_packs(1, &self.config, pool.clone()).and_then(|packs| {
+            match packs.into_iter().next() {
+                Some(pack) => Ok(pack),
+                None => err("Draft has no packs."),
+            }
+        }))
+    }
+
+    /// Each player's pool, in seat order, once drafting has finished.
+    fn final_pools(&self) -> Option<Vec<SeatPool>> {
+        let (Phase::Building(pools, ..) | Phase::Finished(pools, _)) = &self.phase else {
+            return None;
+        };
+
+        Some(
+            self.pool_seats(pools)
+                .into_iter()
+                .map(|(seat, name)| SeatPool {
+                    seat,
+                    name,
+                    cards: pools.get(&seat).cloned().unwrap_or_default(),
+                })
+                .collect(),
+        )
+    }
+
+    /// Each seat with a pool and its name, connected players first in seat
+    /// order, then bots and kicked seats by name.
+    fn pool_seats(&self, pools: &Pools) -> Vec<(Uuid, String)> {
+        let mut seats: Vec<(Uuid, String)> = self
+            .clients
+            .iter()
+            .filter(|client| pools.contains_key(&client.id))
+            .map(|client| (client.id, client.name.clone()))
+            .collect();
+        let mut others: Vec<(Uuid, String)> = pools
+            .keys()
+            .filter(|&&seat| self.clients.get(seat).is_none())
+            .map(|&seat| {
+                let name = self.seat_names.get(&seat).cloned();
+                (seat, name.unwrap_or_else(|| "Absent player".to_string()))
+            })
+            .collect();
+        others.sort_by(|a, b| a.1.cmp(&b.1));
+        seats.extend(others);
+        seats
+    }
+
+    /// Start or stop the abandoned lobby timer, depending on whether there is
+    /// anyone in the lobby.
+    fn update_abandoned(&mut self) {
+        let abandoned = matches!(self.phase, Phase::Lobby(..))
+            && self
+                .clients
+                .iter()
+                .all(|client| client.chan.is_closed() || self.away_since.contains_key(&client.id));
+        if !abandoned {
+            self.abandoned_since = None;
+        } else if self.abandoned_since.is_none() {
+            self.abandoned_since = Some(Instant::now());
+        }
+    }
+
+    fn handle_client_connection(&mut self, id: Uuid, chan: UnboundedSender<ServerMessage>) {
+        if self.kicked.contains(&id) {
+            chan.send(ServerMessage::Kicked).ok();
+        } else if let Some(client) = self.clients.get_mut(id) {
+            self.away_since.remove(&id);
+            client.chan = chan;
+            client.known_status = ClientStatus::Ok;
+            client.heartbeat = Instant::now();
+
+            // Always tell the table, even if the status didn't change, so
+            // that everyone converges on the player's current details.
+            self.broadcast_player_update(id);
+
+            // Packs sent while the player was disconnected were lost, so
+            // their current pack is delivered again with the reconnection.
+            if let Phase::Draft(draft) = &self.phase
+                && draft.current_pack(id).is_some()
+            {
+                self.pack_delivered.insert(id, Instant::now());
+            }
+
+            match &self.phase {
+                Phase::Lobby(..) => {
+                    self.send_player(
+                        id,
+                        ServerMessage::Connected {
+                            draft: self.id,
+                            seat: id,
+                            name: self.name.clone(),
+                        },
+                    );
+                    self.send_player(id, ServerMessage::PlayerList(self.player_list()));
+                }
+                Phase::Draft(draft) => {
+                    self.send_player(
+                        id,
+                        ServerMessage::Reconnected {
+                            draft: self.id,
+                            seat: id,
+                            name: self.name.clone(),
+                            in_progress: true,
+                            pool: draft.drafted_cards(id).cloned().unwrap_or_default(),
+                            pack: draft.current_pack(id),
+                        },
+                    );
+                    self.send_player(id, ServerMessage::PlayerList(self.player_list()));
+                    if let Some(active) = draft.rochester_turn() {
+                        if active == id {
+                            self.send_player(id, ServerMessage::YourTurn);
+                        } else if let Some(pack) = draft.current_pack(active) {
+                            self.send_player(id, self.pack_message(active, pack));
+                            self.send_player(id, ServerMessage::WaitingFor(active));
+                        }
+                    }
+                }
+                Phase::Winston(draft) => {
+                    self.send_player(
+                        id,
+                        ServerMessage::Reconnected {
+                            draft: self.id,
+                            seat: id,
+                            name: self.name.clone(),
+                            in_progress: true,
+                            pool: draft.drafted_cards(id).cloned().unwrap_or_default(),
+                            pack: None,
+                        },
+                    );
+                    self.send_player(id, ServerMessage::PlayerList(self.player_list()));
+                    self.send_player(id, ServerMessage::Winston(draft.state()));
+                    if let Some(pile) = draft.current_pile(id) {
+                        self.send_player(id, ServerMessage::WinstonPile(pile.to_vec()));
+                    }
+                }
+                Phase::Grid(draft) => {
+                    self.send_player(
+                        id,
+                        ServerMessage::Reconnected {
+                            draft: self.id,
+                            seat: id,
+                            name: self.name.clone(),
+                            in_progress: true,
+                            pool: draft.drafted_cards(id).cloned().unwrap_or_default(),
+                            pack: None,
+                        },
+                    );
+                    self.send_player(id, ServerMessage::PlayerList(self.player_list()));
+                    self.send_player(id, ServerMessage::Grid(draft.grid().to_vec()));
+                    if draft.active_player() == Some(id) {
+                        self.send_player(id, ServerMessage::GridTurn);
+                    }
+                }
+                Phase::Building(pools, decks, _) | Phase::Finished(pools, decks) => {
+                    self.send_player(
+                        id,
+                        ServerMessage::Reconnected {
+                            draft: self.id,
+                            seat: id,
+                            name: self.name.clone(),
+                            in_progress: false,
+                            pool: pools.get(&id).cloned().unwrap_or_default(),
+                            pack: None,
+                        },
+                    );
+                    self.send_player(id, ServerMessage::PlayerList(self.player_list()));
+                    if let Phase::Building(.., deadline) = self.phase {
+                        self.send_player(
+                            id,
+                            ServerMessage::DeckBuilding(self.config.deck_size.unwrap_or_default()),
+                        );
+                        if let Some(deadline) = deadline {
+                            let remaining = deadline.saturating_duration_since(Instant::now());
+                            self.send_player(id, ServerMessage::DeckDeadline(remaining.as_secs()));
+                        }
+                        if let Some(deck) = decks.get(&id) {
+                            self.send_player(id, ServerMessage::DeckSubmitted(deck.clone()));
+                        }
+                    } else if !decks.is_empty() {
+                        self.send_player(id, ServerMessage::Decks(decks.clone()));
+                    }
+                }
+                Phase::Terminated => self.send_player(
+                    id,
+                    ServerMessage::FatalError(DraftError::Other("Draft terminated.".into())),
+                ),
+            }
+        } else if matches!(self.phase, Phase::Lobby(..))
+            && self
+                .config
+                .max_players
+                .is_some_and(|max| self.clients.len() >= max)
+        {
+            chan.send(ServerMessage::LobbyFull).ok();
+        } else if let Phase::Lobby(readys, ..) = &mut self.phase {
+            readys.insert(id, false);
+            let client = Client {
                 id,
                 name: id.to_string()[0..8].to_string(),
                 chan,
@@ -313,20 +1702,138 @@ impl DraftServer {
                 heartbeat: Instant::now(),
             };
             self.clients.add(client);
+            self.host.get_or_insert(id);
             self.send_to(
                 id,
                 ServerMessage::Connected {
                     draft: self.id,
                     seat: id,
+                    name: self.name.clone(),
                 },
             );
             self.broadcast(ServerMessage::PlayerList(self.player_list()), None);
         } else {
             chan.send(ServerMessage::Started).ok();
+            if self.unseated.len() < MAX_UNSEATED {
+                self.unseated.insert(id, (chan, Instant::now()));
+            }
+        }
+    }
+
+    fn handle_spectator_connection(&mut self, id: Uuid, chan: UnboundedSender<ServerMessage>) {
+        self.spectators.retain(|s| s.id != id);
+        let spectator = Spectator {
+            id,
+            chan,
+            following: None,
+            replay: None,
+        };
+        if let Phase::Terminated = self.phase {
+            spectator.send(ServerMessage::FatalError(DraftError::Other(
+                "Draft terminated.".into(),
+            )));
+            return;
+        }
+
+        spectator.send(ServerMessage::Spectating {
+            draft: self.id,
+            name: self.name.clone(),
+        });
+        spectator.send(ServerMessage::PlayerList(self.player_list()));
+        if let Some(view) = self.spectator_view() {
+            spectator.send(view);
+        }
+        self.spectators.push(spectator);
+    }
+
+    /// Summary of the packs waiting for each seat, if drafting packs.
+    fn spectator_view(&self) -> Option<ServerMessage> {
+        let Phase::Draft(draft) = &self.phase else {
+            return None;
+        };
+
+        let queues = self
+            .clients
+            .iter()
+            .map(|client| (client.id, draft.queue_size(client.id)))
+            .collect();
+        Some(ServerMessage::SpectatorView(queues))
+    }
+
+    fn handle_spectator_message(&mut self, id: Uuid, msg: ClientMessage) {
+        match msg {
+            ClientMessage::Disconnected => self.spectators.retain(|s| s.id != id),
+            ClientMessage::Follow(seat) if self.clients.get(seat).is_some() => {
+                let (pool, pack) = match &self.phase {
+                    Phase::Draft(draft) => (
+                        draft.drafted_cards(seat).cloned().unwrap_or_default(),
+                        draft.current_pack(seat),
+                    ),
+                    Phase::Winston(draft) => {
+                        (draft.drafted_cards(seat).cloned().unwrap_or_default(), None)
+                    }
+                    Phase::Grid(draft) => {
+                        (draft.drafted_cards(seat).cloned().unwrap_or_default(), None)
+                    }
+                    Phase::Building(pools, ..) | Phase::Finished(pools, _) => {
+                        (pools.get(&seat).cloned().unwrap_or_default(), None)
+                    }
+                    Phase::Lobby(..) | Phase::Terminated => (Vec::new(), None),
+                };
+                if let Some(spectator) = self.spectators.iter_mut().find(|s| s.id == id) {
+                    spectator.following = Some(seat);
+                    spectator.send(ServerMessage::Following { seat, pool, pack });
+                }
+            }
+            ClientMessage::Replay(speed) if !speed.is_nan() => {
+                if let Some(spectator) = self.spectators.iter_mut().find(|s| s.id == id) {
+                    spectator.stop_replay();
+                    spectator.replay = Some(tokio::spawn(replay(
+                        spectator.chan.clone(),
+                        self.picks.clone(),
+                        speed.clamp(MIN_REPLAY_SPEED, MAX_REPLAY_SPEED),
+                    )));
+                }
+            }
+            _ => {} // Spectators can't otherwise affect the draft.
         }
     }
 
     fn handle_client_message(&mut self, id: Uuid, msg: ClientMessage) {
+        self.log_message(Some(id), "received", &msg);
+
+        if self.spectators.iter().any(|s| s.id == id) {
+            self.handle_spectator_message(id, msg);
+            return;
+        }
+
+        if matches!(msg, ClientMessage::Disconnected) {
+            self.reclaim_attempts.remove(&id);
+        }
+
+        // Connections without a seat may only reclaim one.
+        if self.unseated.contains_key(&id) {
+            match msg {
+                ClientMessage::Disconnected => {
+                    self.unseated.remove(&id);
+                }
+                ClientMessage::Reclaim(code) => self.reclaim_seat(id, &code),
+                _ => {}
+            }
+            return;
+        }
+
+        // Connections which reclaimed a seat act for it.
+        let id = match self.aliases.get(&id).copied() {
+            Some(seat) => {
+                if matches!(msg, ClientMessage::Disconnected) {
+                    self.aliases.remove(&id);
+                }
+                seat
+            }
+            None => id,
+        };
+
         if let Some(client) = self.clients.get_mut(id) {
             client.heartbeat = Instant::now();
             match msg {
@@ -340,52 +1847,522 @@ impl DraftServer {
                     }
                 }
                 ClientMessage::Disconnected => {
-                    if let Phase::Lobby(readys, ..) = &mut self.phase {
-                        self.clients.remove(id);
-                        readys.remove(&id);
-                        self.broadcast(ServerMessage::PlayerList(self.player_list()), None);
-                    } else {
+                    if matches!(self.phase, Phase::Lobby(..)) {
+                        if self.config.reconnect_grace.is_zero() {
+                            self.remove_from_lobby(id);
+                        } else {
+                            self.away_since.insert(id, Instant::now());
+                            self.set_client_status(id, ClientStatus::Error);
+                        }
+                    } else if client.known_status != ClientStatus::Left {
                         self.set_client_status(id, ClientStatus::Error);
                     }
                 }
+                ClientMessage::Leave => {
+                    if matches!(self.phase, Phase::Lobby(..)) {
+                        self.remove_from_lobby(id);
+                    } else {
+                        self.set_client_status(id, ClientStatus::Left);
+                    }
+                }
                 ClientMessage::SetName(name) => {
                     client.name = name;
                     self.broadcast_player_update(id);
                 }
-                ClientMessage::Pick(index) => {
-                    if let Phase::Draft(draft) = &mut self.phase {
-                        if let Ok((card, packs)) = draft.handle_pick(id, index) {
-                            client.send(ServerMessage::PickSuccessful(card));
-                            self.send_packs(packs);
-                            self.finish_if_done();
-                        } else if let Some(pack) = draft.current_pack(id) {
-                            // Invalid pick command. Maybe client pack is
-                            // desynced? Resend current pack.
-                            client.send(ServerMessage::Pack(pack));
+                ClientMessage::Pick { pack, index } => {
+                    let warned = client.known_status == ClientStatus::Warning;
+                    let too_soon = self
+                        .pack_delivered
+                        .get(&id)
+                        .is_some_and(|at| at.elapsed() < self.config.min_pick_delay);
+                    if !matches!(self.phase, Phase::Draft(..)) {
+                        self.send_player(id, ServerMessage::Refresh);
+                    } else if !too_soon && self.make_pick(id, pack, index) {
+                        if warned {
+                            self.set_client_status(id, ClientStatus::Ok);
+                        }
+                        self.pick_automatically();
+                    } else if let Phase::Draft(draft) = &self.phase
+                        && let Some(pack) = draft.current_pack(id)
+                    {
+                        // Invalid, early or repeated pick command. Maybe
+                        // client pack is desynced? Resend current pack.
+                        self.send_player(id, self.pack_message(id, pack));
+                    } else if let Phase::Draft(draft) = &self.phase
+                        && let Some(active) = draft.rochester_turn()
+                    {
+                        // Pick out of turn in a rochester draft.
+                        self.send_player(id, ServerMessage::WaitingFor(active));
+                    }
+                }
+                ClientMessage::UndoPick => self.undo_pick(id),
+                ClientMessage::PeekNext => {
+                    if let Phase::Draft(draft) = &self.phase
+                        && let Some(pack) = draft.next_pack(id)
+                    {
+                        self.send_player(id, ServerMessage::PeekPack(pack));
+                    }
+                }
+                ClientMessage::SetLobbyName(name) => {
+                    if self.host == Some(id)
+                        && let Ok(name) = clean_lobby_name(&name)
+                    {
+                        self.name = Some(name.clone());
+                        self.broadcast(ServerMessage::LobbyName(name), None);
+                    }
+                }
+                ClientMessage::Follow(_) | ClientMessage::Replay(_) => {} // Spectators only.
+                ClientMessage::SubmitDeck(indices) => {
+                    if let Phase::Building(pools, decks, _) = &mut self.phase {
+                        let pool = pools.get(&id).map(Vec::as_slice).unwrap_or_default();
+                        let size = self.config.deck_size.unwrap_or_default();
+                        match build_deck(pool, &indices, size) {
+                            Ok(deck) => {
+                                decks.insert(id, deck.clone());
+                                self.send_player(id, ServerMessage::DeckSubmitted(deck));
+                                self.broadcast_player_update(id);
+                                self.finish_building_if_done();
+                            }
+                            Err(e) => {
+                                self.send_player(id, ServerMessage::DeckRejected(e.to_string()))
+                            }
                         }
                     } else {
-                        client.send(ServerMessage::Refresh);
+                        self.send_player(id, ServerMessage::Refresh);
+                    }
+                }
+                ClientMessage::EndBuilding => {
+                    if self.host == Some(id) {
+                        self.finish_building();
+                    }
+                }
+                ClientMessage::Winston(action) => self.handle_winston_action(id, action),
+                ClientMessage::Grid(selection) => self.handle_grid_pick(id, selection),
+                ClientMessage::RequestReclaimCode => self.issue_reclaim_code(id),
+                ClientMessage::Reclaim(code) => self.reclaim_seat(id, &code),
+                ClientMessage::Kick(seat) => {
+                    if self.host == Some(id) && seat != id && self.clients.get(seat).is_some() {
+                        self.kick(seat);
                     }
                 }
             }
         }
     }
 
-    fn send_to(&self, id: Uuid, message: ServerMessage) {
-        if let Some(client) = self.clients.get(id) {
-            client.send(message);
-        }
+    /// Hash of a reclaim code, salted with the lobby ID, so that codes
+    /// aren't kept. Codes are compared ignoring case, spaces and dashes.
+    fn reclaim_hash(&self, code: &str) -> String {
+        use sha1::Digest;
+
+        let code: String = code
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .map(|c| c.to_ascii_uppercase())
+            .collect();
+        let mut hasher = sha1::Sha1::new();
+        hasher.update(self.id.as_bytes());
+        hasher.update(code.as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect()
     }
 
-    fn send_packs(&self, packs: Vec<(Uuid, Pack)>) {
-        for (id, pack) in packs {
-            self.send_to(id, ServerMessage::Pack(pack));
+    /// Issue a random code the player can use to reclaim their seat,
+    /// replacing any issued before.
+    fn issue_reclaim_code(&mut self, id: Uuid) {
+        let mut rng = rand::thread_rng();
+        let code: String = (0..RECLAIM_CODE_LENGTH)
+            .map(|_| RECLAIM_CODE_ALPHABET[rng.gen_range(0..RECLAIM_CODE_ALPHABET.len())] as char)
+            .collect();
+        let code = format!("{}-{}", &code[..6], &code[6..]);
+
+        self.reclaim_codes.retain(|_, seat| *seat != id);
+        self.reclaim_codes.insert(self.reclaim_hash(&code), id);
+        self.send_player(id, ServerMessage::ReclaimCode(code));
+    }
+
+    /// Move the connection to the seat the code was issued for, as if the
+    /// player had reconnected to it. A seat the connection was given in the
+    /// lobby is freed. Connections which try too many wrong codes are
+    /// refused.
+    fn reclaim_seat(&mut self, id: Uuid, code: &str) {
+        let chan = match self.unseated.get(&id) {
+            Some((chan, _)) => chan.clone(),
+            None => match self.clients.get(id) {
+                Some(client) => client.chan.clone(),
+                None => return,
+            },
+        };
+
+        let attempts = self.reclaim_attempts.get(&id).copied().unwrap_or_default();
+        if attempts >= MAX_RECLAIM_ATTEMPTS {
+            let reason = "Too many attempts to reclaim a seat.".to_string();
+            chan.send(ServerMessage::ReclaimRejected(reason)).ok();
+            self.unseated.remove(&id);
+            return;
         }
 
-        if let Phase::Draft(game) = &self.phase {
-            for player in self.player_list().iter() {
-                self.broadcast(
-                    ServerMessage::QueueSize {
+        let Some(&seat) = self.reclaim_codes.get(&self.reclaim_hash(code)) else {
+            self.reclaim_attempts.insert(id, attempts + 1);
+            let reason = "No seat has that reclaim code.".to_string();
+            chan.send(ServerMessage::ReclaimRejected(reason)).ok();
+            return;
+        };
+        if seat == id {
+            return;
+        }
+
+        self.unseated.remove(&id);
+        self.reclaim_attempts.remove(&id);
+        self.remove_from_lobby(id);
+        self.aliases.insert(id, seat);
+        self.handle_client_connection(seat, chan);
+    }
+
+    /// Pick the card at the index in the seat's current pack, if that pack has
+    /// the given ID, and pass the pack on. Returns whether the pick was made.
+    fn make_pick(&mut self, id: Uuid, pack: usize, index: usize) -> bool {
+        let Phase::Draft(draft) = &mut self.phase else {
+            return false;
+        };
+
+        let round = draft.round();
+        let Some(origin) = draft
+            .current_pack(id)
+            .filter(|current| current.id == pack)
+            .map(|current| current.origin)
+        else {
+            return false;
+        };
+        let Ok((card, packs)) = draft.handle_pick(id, index) else {
+            return false;
+        };
+
+        let new_round = draft.round() != round;
+        self.picks.push(PickEvent {
+            seat: id,
+            round,
+            pack,
+            card: card.clone(),
+            origin,
+            at: self
+                .draft_started
+                .map(|start| start.elapsed().as_millis() as u64)
+                .unwrap_or_default(),
+        });
+        self.notify(WebhookEvent::Pick {
+            draft: self.id,
+            seat: id,
+            card: Box::new(card.clone()),
+        });
+        self.pick_deadlines.remove(&id);
+        self.send_to(id, ServerMessage::PickSuccessful(card));
+        if new_round {
+            self.reveal_bombs(&packs);
+        }
+        self.send_packs(packs);
+        self.terminate_if_stalled();
+        self.finish_if_done();
+        true
+    }
+
+    /// Undo the player's last pick, returning the pack they passed to them.
+    /// The player it was taken back from moves on to their next pack, if any.
+    fn undo_pick(&mut self, id: Uuid) {
+        let Phase::Draft(draft) = &mut self.phase else {
+            let reason = "Picks can only be undone while drafting packs.".to_string();
+            self.send_player(id, ServerMessage::UndoRejected(reason));
+            return;
+        };
+
+        let holding = |draft: &Draft, seat| draft.current_pack(seat).map(|pack| pack.id);
+        let (passed_to, was_holding) = match draft.last_passed_to(id) {
+            Some(seat) => (seat, holding(draft, seat)),
+            None => (id, None),
+        };
+        if let Err(e) = draft.undo_pick(id) {
+            self.send_player(id, ServerMessage::UndoRejected(e.to_string()));
+            return;
+        }
+
+        let pool = draft.drafted_cards(id).cloned().unwrap_or_default();
+        let mut packs: NewPacks = draft
+            .current_pack(id)
+            .map(|pack| (id, pack))
+            .into_iter()
+            .collect();
+        let now_holding = draft.current_pack(passed_to);
+        if passed_to != id && now_holding.as_ref().map(|pack| pack.id) != was_holding {
+            self.pack_delivered.remove(&passed_to);
+            self.pick_deadlines.remove(&passed_to);
+            match now_holding {
+                Some(pack) => packs.push((passed_to, pack)),
+                None => self.send_to(passed_to, ServerMessage::PackWithdrawn),
+            }
+        }
+
+        if let Some(index) = self.picks.iter().rposition(|pick| pick.seat == id) {
+            self.picks.remove(index);
+        }
+        self.send_to(id, ServerMessage::PickUndone(pool));
+        self.send_packs(packs);
+    }
+
+    /// Make picks for kicked seats and bots until none of them are holding a
+    /// pack. In a Winston draft, they take the first pile on their turn, and
+    /// in a grid draft the fullest line.
+    fn pick_automatically(&mut self) {
+        while let Phase::Grid(draft) = &mut self.phase
+            && let Some(seat) = draft
+                .active_player()
+                .filter(|seat| self.kicked.contains(seat) || self.bots.contains(seat))
+        {
+            let selection = draft.auto_selection();
+            if draft.handle_grid_pick(seat, selection).is_err() {
+                return;
+            }
+            self.send_grid_state();
+            self.finish_if_done();
+        }
+
+        while let Phase::Winston(draft) = &mut self.phase
+            && let Some(seat) = draft
+                .active_player()
+                .filter(|seat| self.kicked.contains(seat) || self.bots.contains(seat))
+        {
+            if draft
+                .handle_winston_action(seat, WinstonAction::Take)
+                .is_err()
+            {
+                return;
+            }
+            self.send_winston_state();
+            self.finish_if_done();
+        }
+
+        loop {
+            let Phase::Draft(draft) = &self.phase else {
+                return;
+            };
+
+            let Some((seat, pack, index)) =
+                self.kicked.iter().chain(&self.bots).find_map(|&seat| {
+                    let pack = draft.current_pack(seat)?;
+                    let rng = &mut draft.pack_rng(&pack);
+                    let index = if self.bots.contains(&seat) {
+                        bot_pick(&pack.cards, rng)
+                    } else {
+                        let pool = draft.drafted_cards(seat).map(Vec::as_slice);
+                        auto_pick(
+                            &pack.cards,
+                            pool.unwrap_or_default(),
+                            self.config.auto_pick,
+                            rng,
+                        )
+                    };
+                    Some((seat, pack.id, index))
+                })
+            else {
+                return;
+            };
+
+            if !self.make_pick(seat, pack, index) {
+                return;
+            }
+        }
+    }
+
+    /// Pick for each player who has run out of time to pick from their pack,
+    /// marking them with a warning.
+    fn pick_for_late(&mut self) {
+        let now = Instant::now();
+        let late: Vec<Uuid> = self
+            .pick_deadlines
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(seat, _)| *seat)
+            .collect();
+
+        for seat in late {
+            self.pick_deadlines.remove(&seat);
+            let Phase::Draft(draft) = &self.phase else {
+                return;
+            };
+            let Some(pack) = draft.current_pack(seat) else {
+                continue;
+            };
+            let pool = draft.drafted_cards(seat).map(Vec::as_slice);
+            let index = auto_pick(
+                &pack.cards,
+                pool.unwrap_or_default(),
+                self.config.auto_pick,
+                &mut draft.pack_rng(&pack),
+            );
+
+            self.set_client_status(seat, ClientStatus::Warning);
+            self.make_pick(seat, pack.id, index);
+        }
+        self.pick_automatically();
+    }
+
+    /// Take or pass the pile the player is looking at in a Winston draft.
+    fn handle_winston_action(&mut self, id: Uuid, action: WinstonAction) {
+        let Phase::Winston(draft) = &mut self.phase else {
+            self.send_player(id, ServerMessage::Refresh);
+            return;
+        };
+
+        match draft.handle_winston_action(id, action) {
+            Ok(taken) => {
+                if !taken.is_empty() {
+                    self.send_to(id, ServerMessage::WinstonTaken(taken));
+                }
+                self.send_winston_state();
+                self.pick_automatically();
+                self.finish_if_done();
+            }
+            Err(e) => self.send_player(id, ServerMessage::WinstonRejected(e.to_string())),
+        }
+    }
+
+    /// Tell the table the state of the Winston draft, and show the active
+    /// player the pile they're looking at.
+    fn send_winston_state(&self) {
+        if let Phase::Winston(draft) = &self.phase {
+            self.broadcast(ServerMessage::Winston(draft.state()), None);
+            if let Some(active) = draft.active_player()
+                && let Some(pile) = draft.current_pile(active)
+            {
+                self.send_to(active, ServerMessage::WinstonPile(pile.to_vec()));
+            }
+        }
+    }
+
+    /// Take a row or column of the grid for the player in a grid draft.
+    fn handle_grid_pick(&mut self, id: Uuid, selection: GridSelection) {
+        let Phase::Grid(draft) = &mut self.phase else {
+            self.send_player(id, ServerMessage::Refresh);
+            return;
+        };
+
+        match draft.handle_grid_pick(id, selection) {
+            Ok(taken) => {
+                self.send_to(id, ServerMessage::GridTaken(taken));
+                self.send_grid_state();
+                self.pick_automatically();
+                self.finish_if_done();
+            }
+            Err(e) => self.send_player(id, ServerMessage::GridRejected(e.to_string())),
+        }
+    }
+
+    /// Show the table the grid, and tell the active player it's their turn.
+    fn send_grid_state(&self) {
+        if let Phase::Grid(draft) = &self.phase {
+            self.broadcast(ServerMessage::Grid(draft.grid().to_vec()), None);
+            if let Some(active) = draft.active_player() {
+                self.send_to(active, ServerMessage::GridTurn);
+            }
+        }
+    }
+
+    /// Message delivering a pack to a player, with the time they have left to
+    /// pick from it.
+    fn pack_message(&self, id: Uuid, pack: DraftPack) -> ServerMessage {
+        let time_left = self
+            .pick_deadlines
+            .get(&id)
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()).as_secs());
+        ServerMessage::Pack { pack, time_left }
+    }
+
+    /// Remove a player at the host's request. In the lobby this frees the
+    /// seat. Mid-draft the seat keeps its place at the table, with picks made
+    /// for it, including from any pack it was holding.
+    fn kick(&mut self, seat: Uuid) {
+        self.send_player(seat, ServerMessage::Kicked);
+        match self.phase {
+            Phase::Lobby(..) => {
+                self.kicked.insert(seat);
+                self.remove_from_lobby(seat);
+            }
+            Phase::Draft(..) | Phase::Winston(..) | Phase::Grid(..) => {
+                self.kicked.insert(seat);
+                if let Some(client) = self.clients.get(seat) {
+                    self.seat_names.insert(seat, client.name.clone());
+                }
+                self.clients.remove(seat);
+                self.pack_delivered.remove(&seat);
+                self.pick_deadlines.remove(&seat);
+                self.broadcast(ServerMessage::PlayerList(self.player_list()), None);
+                self.pick_automatically();
+            }
+            Phase::Building(..) | Phase::Finished(..) | Phase::Terminated => {}
+        }
+    }
+
+    /// Send a message to a player only.
+    fn send_player(&self, id: Uuid, message: ServerMessage) {
+        self.log_message(Some(id), "sent", &message);
+        if let Some(client) = self.clients.get(id) {
+            client.send(message);
+        }
+    }
+
+    /// Send a message to a player, and any spectators following them.
+    fn send_to(&self, id: Uuid, message: ServerMessage) {
+        for spectator in &self.spectators {
+            if spectator.following == Some(id) {
+                spectator.send(message.clone());
+            }
+        }
+        self.send_player(id, message);
+    }
+
+    /// If enabled, show the table the best card in each newly opened pack.
+    fn reveal_bombs(&self, packs: &NewPacks) {
+        if self.config.reveal_bombs {
+            let reveals = packs
+                .iter()
+                .filter_map(|(seat, pack)| {
+                    Some(Reveal {
+                        seat: *seat,
+                        card: bomb(&pack.cards)?.clone(),
+                    })
+                })
+                .collect();
+            self.broadcast(ServerMessage::Revealed(reveals), None);
+        }
+    }
+
+    /// Deliver packs to the players who are to pick from them. In a rochester
+    /// draft, the open pack is shown to the whole table, with the player whose
+    /// turn it is.
+    fn send_packs(&mut self, packs: NewPacks) {
+        let rochester = matches!(&self.phase, Phase::Draft(draft) if draft.is_rochester());
+        for (id, pack) in packs {
+            self.pack_delivered.insert(id, Instant::now());
+            if let Some(timer) = self.config.pick_timer
+                && !self.kicked.contains(&id)
+                && !self.bots.contains(&id)
+            {
+                self.pick_deadlines.insert(id, Instant::now() + timer);
+            }
+            if rochester {
+                self.broadcast(self.pack_message(id, pack), None);
+                self.broadcast(ServerMessage::WaitingFor(id), Some(id));
+                self.send_to(id, ServerMessage::YourTurn);
+            } else {
+                self.send_to(id, self.pack_message(id, pack));
+            }
+        }
+
+        if let Phase::Draft(game) = &self.phase {
+            for player in self.player_list().iter() {
+                self.broadcast(
+                    ServerMessage::QueueSize {
                         seat: player.seat,
                         count: game.queue_size(player.seat),
                     },
@@ -393,13 +2370,18 @@ impl DraftServer {
                 );
             }
         }
+        if let Some(view) = self.spectator_view() {
+            for spectator in &self.spectators {
+                spectator.send(view.clone());
+            }
+        }
     }
 
     fn ready_state(&self, seat: Uuid) -> bool {
-        if let Phase::Lobby(readys, ..) = &self.phase {
-            readys.get(&seat).cloned().unwrap_or(false)
-        } else {
-            self.clients.get(seat).is_some()
+        match &self.phase {
+            Phase::Lobby(readys, ..) => readys.get(&seat).cloned().unwrap_or(false),
+            Phase::Building(_, decks, _) => decks.contains_key(&seat),
+            _ => self.clients.get(seat).is_some(),
         }
     }
 
@@ -423,196 +2405,2402 @@ impl DraftServer {
     /// If all players are ready to start, attempt to build packs and start the
     /// draft. Returns true if the draft was started, else false.
     fn start_if_ready(&mut self) -> bool {
-        if let Phase::Lobby(readys, config, pool) = &self.phase {
+        if let Phase::Lobby(readys, pool) = &self.phase {
             if !self.clients.is_empty()
+                && self.clients.len() >= self.config.min_players
                 && self
                     .clients
                     .iter()
                     .all(|c| readys.get(&c.id).copied().unwrap_or(false))
             {
-                let players: Vec<Uuid> = self.clients.iter().map(|c| c.id).collect();
-                match make_packs(players.len(), config, pool.clone()) {
+                let mut players: Vec<Uuid> = self.clients.iter().map(|c| c.id).collect();
+                self.bots = (0..self.config.bots).map(|_| Uuid::new_v4()).collect();
+                players.extend(&self.bots);
+                self.seat_names = self
+                    .bots
+                    .iter()
+                    .enumerate()
+                    .map(|(i, &bot)| (bot, format!("Bot {}", i + 1)))
+                    .collect();
+
+                // Winston and grid drafts wait until the table is the right
+                // size.
+                if self.config.mode == DraftMode::Winston && !(2..=3).contains(&players.len())
+                    || self.config.mode == DraftMode::Grid && players.len() != 2
+                {
+                    return false;
+                }
+
+                if self.config.keep_lobby {
+                    self.retained_pool = Some(pool.clone());
+                }
+                match make_packs(players.len(), &self.config, pool.clone()) {
                     Ok(packs) => {
-                        let mut draft = Draft::new(players, config.rounds, packs);
+                        let summary = DraftSummary {
+                            rounds: self.config.rounds,
+                            pack_sizes: (0..self.config.rounds)
+                                .map(|round| self.config.opened_pack_size(round))
+                                .collect(),
+                            deck_size: self.config.deck_size,
+                        };
+                        self.broadcast(
+                            ServerMessage::Starting {
+                                players: self.player_list(),
+                                summary,
+                            },
+                            None,
+                        );
+                        self.notify(WebhookEvent::DraftStarted {
+                            draft: self.id,
+                            players: players.clone(),
+                        });
+                        self.draft_started = Some(Instant::now());
+                        self.started_at = Some(SystemTime::now());
+                        self.results = None;
+
+                        if self.config.mode == DraftMode::Winston {
+                            let deck = packs.into_iter().flatten().collect();
+                            let seed = self.config.seed.unwrap_or_else(rand::random);
+                            self.phase = Phase::Winston(WinstonDraft::new(players, deck, seed));
+                            self.send_winston_state();
+                            self.pick_automatically();
+                            return true;
+                        }
+
+                        if self.config.mode == DraftMode::Grid {
+                            let deck = packs.into_iter().flatten().collect();
+                            let seed = self.config.seed.unwrap_or_else(rand::random);
+                            self.phase = Phase::Grid(GridDraft::new(players, deck, seed));
+                            self.send_grid_state();
+                            self.pick_automatically();
+                            return true;
+                        }
+
+                        let mut draft = Draft::new(
+                            players,
+                            self.config.rounds,
+                            packs,
+                            self.config.pass_model,
+                            self.config.first_direction,
+                            self.config.seed.unwrap_or_else(rand::random),
+                        );
+                        if self.config.mode == DraftMode::Rochester {
+                            draft = draft.rochester();
+                        }
                         let packs = draft.begin();
-                        self.phase = Phase::Draft(draft);
+                        self.phase = Phase::Draft(Box::new(draft));
+                        self.reveal_bombs(&packs);
                         self.send_packs(packs);
+                        self.terminate_if_stalled();
+                        self.pick_automatically();
                         return true;
                     }
-                    Err(e) => self.terminate(format!("Failed to create packs for draft: {e}")),
+                    Err(e) => self.terminate(e),
+                }
+            }
+        }
+        false
+    }
+
+    /// Safety net for a draft where no player has a pack but the draft isn't
+    /// complete, which would otherwise hang forever.
+    fn terminate_if_stalled(&mut self) {
+        if let Phase::Draft(draft) = &self.phase
+            && draft.stalled()
+        {
+            tracing::error!(lobby = %self.id, ?draft, "Draft stalled with no packs to pick.");
+            self.terminate(DraftError::Internal(
+                "Draft stalled: no player has a pack to pick from.".into(),
+            ));
+        }
+    }
+
+    /// Free the lobby seats of players who haven't reconnected within the
+    /// grace period. Seats are kept once the draft starts.
+    fn free_away_seats(&mut self) {
+        if !matches!(self.phase, Phase::Lobby(..)) {
+            self.away_since.clear();
+            return;
+        }
+
+        let grace = self.config.reconnect_grace;
+        let gone: Vec<Uuid> = self
+            .away_since
+            .iter()
+            .filter(|(_, since)| since.elapsed() >= grace)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in gone {
+            self.remove_from_lobby(id);
+        }
+    }
+
+    /// Free a seat in the lobby, passing the host role on if needed.
+    fn remove_from_lobby(&mut self, id: Uuid) {
+        if let Phase::Lobby(readys, ..) = &mut self.phase {
+            self.away_since.remove(&id);
+            self.reclaim_codes.retain(|_, seat| *seat != id);
+            self.clients.remove(id);
+            readys.remove(&id);
+            if self.host == Some(id) {
+                self.host = self.clients.iter().next().map(|c| c.id);
+            }
+            self.broadcast(ServerMessage::PlayerList(self.player_list()), None);
+        }
+    }
+
+    fn finish_if_done(&mut self) {
+        let mut pools = match &self.phase {
+            Phase::Draft(draft) if draft.draft_complete() => {
+                self.opened_packs = draft.opened_packs().to_vec();
+                self.pick_log = draft.pick_log().clone();
+                draft.pools().clone()
+            }
+            Phase::Winston(draft) if draft.draft_complete() => draft.pools().clone(),
+            Phase::Grid(draft) if draft.draft_complete() => draft.pools().clone(),
+            _ => return,
+        };
+
+        self.broadcast(ServerMessage::DraftComplete, None);
+        for (id, pool) in &mut pools {
+            // The summary only covers drafted cards, not basics.
+            let summary = PoolSummary::of(pool);
+            if self.config.include_basics {
+                for colour in Colour::ALL {
+                    let basic = Card::basic_land(colour);
+                    pool.extend(std::iter::repeat_n(basic, self.config.basics_per_colour));
+                }
+            }
+            self.send_to(*id, ServerMessage::Finished(pool.clone()));
+            self.send_to(*id, ServerMessage::PoolSummary(summary));
+        }
+        self.notify(WebhookEvent::DraftFinished { draft: self.id });
+        if let Some(size) = self.config.deck_size {
+            let limit = self.config.deck_deadline;
+            let deadline = limit.map(|limit| Instant::now() + limit);
+            self.phase = Phase::Building(pools, Pools::new(), deadline);
+            self.broadcast(ServerMessage::DeckBuilding(size), None);
+            if let Some(limit) = limit {
+                self.broadcast(ServerMessage::DeckDeadline(limit.as_secs()), None);
+            }
+        } else {
+            self.finish(pools, Pools::new());
+        }
+    }
+
+    fn finish_building_if_done(&mut self) {
+        if let Phase::Building(_, decks, _) = &self.phase
+            && self.clients.iter().all(|c| decks.contains_key(&c.id))
+        {
+            // Seats without a player can't submit decks, so are given one.
+            self.auto_submit_decks();
+        }
+    }
+
+    /// Submit a deck for each seat which hasn't submitted one, players who
+    /// missed the deadline as well as bots and kicked seats, then end
+    /// deckbuilding.
+    fn auto_submit_decks(&mut self) {
+        let Phase::Building(pools, decks, _) = &mut self.phase else {
+            return;
+        };
+
+        let size = self.config.deck_size.unwrap_or_default();
+        let mut submitted = Vec::new();
+        for (&seat, pool) in pools.iter() {
+            if let Entry::Vacant(entry) = decks.entry(seat) {
+                submitted.push((seat, entry.insert(auto_deck(pool, size)).clone()));
+            }
+        }
+
+        for (id, deck) in submitted {
+            self.send_player(id, ServerMessage::DeckAutoSubmitted(deck));
+            self.broadcast_player_update(id);
+        }
+        self.finish_building();
+    }
+
+    /// End deckbuilding, sharing each submitted deck with the table.
+    fn finish_building(&mut self) {
+        if let Phase::Building(pools, decks, _) = &self.phase {
+            let (pools, decks) = (pools.clone(), decks.clone());
+            self.broadcast(ServerMessage::Decks(decks.clone()), None);
+            self.finish(pools, decks);
+        }
+    }
+
+    /// Finish the draft, recording the results, and return to the lobby if it
+    /// is kept open.
+    fn finish(&mut self, pools: Pools, decks: Pools) {
+        let seats = self
+            .pool_seats(&pools)
+            .into_iter()
+            .map(|(seat, name)| SeatResult {
+                seat,
+                name,
+                pool: pools[&seat].clone(),
+                deck: decks.get(&seat).cloned(),
+            })
+            .collect();
+        self.results = Some(DraftResults {
+            draft: self.id,
+            name: self.name.clone(),
+            seats,
+            config: self.config.clone(),
+            started: self.started_at.map(unix_secs),
+            finished: unix_secs(SystemTime::now()),
+        });
+
+        self.phase = Phase::Finished(pools, decks);
+        self.return_to_lobby();
+    }
+
+    /// Reopen the lobby with the original pool once the draft is over, if the
+    /// lobby is kept open. Players who left are dropped and every seat has to
+    /// ready up again.
+    fn return_to_lobby(&mut self) {
+        if !matches!(self.phase, Phase::Finished(..)) {
+            return;
+        }
+        let Some(pool) = self.retained_pool.take() else {
+            return;
+        };
+
+        let left: Vec<Uuid> = self
+            .clients
+            .iter()
+            .filter(|c| c.known_status == ClientStatus::Left)
+            .map(|c| c.id)
+            .collect();
+        for id in left {
+            self.clients.remove(id);
+        }
+        if self
+            .host
+            .is_some_and(|host| self.clients.get(host).is_none())
+        {
+            self.host = self.clients.iter().next().map(|c| c.id);
+        }
+
+        self.phase = Phase::Lobby(HashMap::new(), pool);
+        self.created = Instant::now();
+        self.draft_started = None;
+        self.pack_delivered.clear();
+        self.pick_deadlines.clear();
+        self.picks.clear();
+        self.opened_packs.clear();
+        self.pick_log.clear();
+        self.stop_replays();
+        self.broadcast(ServerMessage::ReturnedToLobby, None);
+        self.broadcast(ServerMessage::PlayerList(self.player_list()), None);
+    }
+}
+
+/// Send a spectator each pick made in the draft, spaced out as they were
+/// made, sped up by the given factor. Stops early if the spectator leaves.
+async fn replay(chan: UnboundedSender<ServerMessage>, picks: Vec<PickEvent>, speed: f32) {
+    let start = Instant::now();
+    for pick in picks {
+        sleep_until(start + Duration::from_millis(pick.at).div_f32(speed)).await;
+        if chan.send(ServerMessage::ReplayPick(pick)).is_err() {
+            return;
+        }
+    }
+    chan.send(ServerMessage::ReplayFinished).ok();
+}
+
+#[cfg(test)]
+mod test {
+    use std::assert_matches::assert_matches;
+
+    use tokio::sync::mpsc::unbounded_channel;
+
+    use super::*;
+    use crate::{cards::Rarity, draft::packs::DraftPool};
+
+    const TIMEOUT: Duration = Duration::from_secs(60);
+
+    fn close_server(handle: ServerHandle) {
+        handle.send(DraftServerRequest::Terminate(String::new()));
+    }
+
+    fn client_send(handle: &ServerHandle, id: Uuid, message: ClientMessage) {
+        handle.send(DraftServerRequest::Message(id, message))
+    }
+
+    async fn receive(chan: &mut UnboundedReceiver<ServerMessage>) -> ServerMessage {
+        match tokio::time::timeout(tokio::time::Duration::from_millis(1), chan.recv()).await {
+            Ok(Some(msg)) => msg,
+            Ok(None) => panic!("Channel was closed, expected to receive a message."),
+            Err(_) => panic!("Didn't receive on channel after 1ms."),
+        }
+    }
+
+    async fn receive_until<P: Fn(ServerMessage) -> bool>(
+        chan: &mut UnboundedReceiver<ServerMessage>,
+        pred: P,
+    ) {
+        while !pred(receive(chan).await) {}
+    }
+
+    /// Receive messages until a pack arrives, returning the pack.
+    async fn receive_pack(chan: &mut UnboundedReceiver<ServerMessage>) -> DraftPack {
+        loop {
+            if let ServerMessage::Pack { pack, .. } = receive(chan).await {
+                return pack;
+            }
+        }
+    }
+
+    fn pick(handle: &ServerHandle, id: Uuid, pack: &DraftPack, index: usize) {
+        client_send(
+            handle,
+            id,
+            ClientMessage::Pick {
+                pack: pack.id,
+                index,
+            },
+        );
+    }
+
+    async fn add_client(handle: &ServerHandle) -> (Uuid, UnboundedReceiver<ServerMessage>) {
+        let user = Uuid::new_v4();
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(user, send));
+        if let ServerMessage::Connected { draft, seat, .. } = receive(&mut recv).await {
+            assert_eq!(draft, handle.id);
+            assert_eq!(seat, user);
+        } else {
+            panic!("Expected to receive connected message first.");
+        };
+        if let ServerMessage::PlayerList(players) = receive(&mut recv).await {
+            assert!(players.iter().any(|p| p.seat == user));
+        } else {
+            panic!("Expected to receive player list second.");
+        };
+        (user, recv)
+    }
+
+    async fn add_spectator(handle: &ServerHandle) -> (Uuid, UnboundedReceiver<ServerMessage>) {
+        let id = Uuid::new_v4();
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Spectate(id, send));
+        assert_matches!(receive(&mut recv).await, ServerMessage::Spectating { .. });
+        assert_matches!(receive(&mut recv).await, ServerMessage::PlayerList(..));
+        (id, recv)
+    }
+
+    #[tokio::test]
+    async fn test_joining_closing_server() {
+        let handle = DraftServer::spawn(
+            Default::default(),
+            DraftPool::new(),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (_user, mut recv) = add_client(&handle).await;
+        close_server(handle);
+        assert_matches!(recv.recv().await.unwrap(), ServerMessage::FatalError(..));
+    }
+
+    #[tokio::test]
+    async fn test_abandoned_lobby() {
+        let timeout = Duration::from_millis(10);
+
+        // Closed if no one joins.
+        let handle = DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            timeout,
+            TIMEOUT,
+            None,
+        );
+        tokio::time::sleep(timeout * 2).await;
+        assert!(!handle.is_open());
+
+        // Kept open while someone is in the lobby, closed once they leave.
+        let handle = DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            timeout,
+            TIMEOUT,
+            None,
+        );
+        let (p1, _chan1) = add_client(&handle).await;
+        tokio::time::sleep(timeout * 2).await;
+        assert!(handle.is_open());
+        client_send(&handle, p1, ClientMessage::Disconnected);
+        tokio::time::sleep(timeout * 2).await;
+        assert!(!handle.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_idle_lobby_expiry() {
+        let expiry = Duration::from_millis(50);
+        let handle = DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            TIMEOUT,
+            expiry,
+            None,
+        );
+        let (p1, mut chan1) = add_client(&handle).await;
+
+        // Closed once the window passes, even while the player is active.
+        for _ in 0..5 {
+            client_send(&handle, p1, ClientMessage::HeartBeat);
+            tokio::time::sleep(expiry / 5).await;
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Ended)).await;
+        assert!(!handle.is_open());
+    }
+
+    #[tokio::test]
+    async fn test_draft() {
+        let pool = DraftPool::sample(1, 1, 1, 1);
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            unique_cards: true,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = DraftServer::spawn(config, pool, None, TIMEOUT, TIMEOUT, None);
+        let (p1, mut chan1) = add_client(&handle).await;
+        let (p2, mut chan2) = add_client(&handle).await;
+        assert_matches!(receive(&mut chan1).await, ServerMessage::PlayerList(..));
+
+        // Once both players are ready
+        client_send(&handle, p1, ClientMessage::ReadyState(true));
+        receive_until(&mut chan2, |m| matches!(m, ServerMessage::PlayerUpdate(..))).await;
+        client_send(&handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+
+        // Client one passes, don't expect any packs at this stage as they are
+        // backed up behind p2.
+        pick(&handle, p1, &pack1, 0);
+        receive_until(&mut chan1, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+
+        // After p2s pick, both players should be sent a new pack.
+        pick(&handle, p2, &pack2, 0);
+        receive_until(&mut chan2, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+        let pack2 = receive_pack(&mut chan2).await;
+        let pack1 = receive_pack(&mut chan1).await;
+
+        pick(&handle, p1, &pack1, 0);
+        receive_until(&mut chan1, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+        pick(&handle, p2, &pack2, 0);
+        receive_until(&mut chan2, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+
+        receive_until(
+            &mut chan1,
+            |m| matches!(m, ServerMessage::Finished(cards) if cards.len() == 2),
+        )
+        .await;
+        receive_until(
+            &mut chan2,
+            |m| matches!(m, ServerMessage::Finished(cards) if cards.len() == 2),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_set_name() {
+        let handle = &DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut _chan1) = add_client(handle).await;
+        let (_p2, mut chan2) = add_client(handle).await;
+
+        client_send(handle, p1, ClientMessage::SetName("name".into()));
+        let ServerMessage::PlayerUpdate(PlayerDetails {
+            seat,
+            name,
+            ready,
+            status,
+        }) = receive(&mut chan2).await
+        else {
+            panic!("Should have received a status update.");
+        };
+        assert_eq!(seat, p1);
+        assert_eq!(name, "name");
+        assert!(!ready);
+        assert_eq!(status, ClientStatus::Ok);
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let ServerMessage::PlayerUpdate(PlayerDetails {
+            seat,
+            name,
+            ready,
+            status,
+        }) = receive(&mut chan2).await
+        else {
+            panic!("Should have received a status update.");
+        };
+        assert_eq!(seat, p1);
+        assert_eq!(name, "name");
+        assert!(ready);
+        assert_eq!(status, ClientStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn test_leave() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 4),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, _chan2) = add_client(handle).await;
+        let (p3, _chan3) = add_client(handle).await;
+        receive_until(
+            &mut chan1,
+            |m| matches!(m, ServerMessage::PlayerList(players) if players.len() == 3),
+        )
+        .await;
+
+        // Leaving the lobby frees the seat.
+        client_send(handle, p3, ClientMessage::Leave);
+        let ServerMessage::PlayerList(players) = receive(&mut chan1).await else {
+            panic!("Should have received the player list.");
+        };
+        assert_eq!(players.len(), 2);
+        assert!(!players.iter().any(|p| p.seat == p3));
+
+        // Leaving mid-draft marks the seat, even once the connection drops.
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        receive_pack(&mut chan1).await;
+        client_send(handle, p2, ClientMessage::Leave);
+        client_send(handle, p2, ClientMessage::Disconnected);
+        let details = loop {
+            if let ServerMessage::PlayerUpdate(details) = receive(&mut chan1).await {
+                break details;
+            }
+        };
+        assert_eq!(details.seat, p2);
+        assert_eq!(details.status, ClientStatus::Left);
+        assert!(chan1.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coalesced_player_updates() {
+        let handle = &DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, _chan1) = add_client(handle).await;
+        let (p2, _chan2) = add_client(handle).await;
+        let (_p3, mut chan3) = add_client(handle).await;
+
+        // Changes which arrive together are sent as one player list.
+        client_send(handle, p1, ClientMessage::SetName("one".into()));
+        client_send(handle, p2, ClientMessage::SetName("two".into()));
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let ServerMessage::PlayerList(players) = receive(&mut chan3).await else {
+            panic!("Should have received a player list.");
+        };
+        assert_eq!(players[0].name, "one");
+        assert!(players[0].ready);
+        assert_eq!(players[1].name, "two");
+        assert!(chan3.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_in_lobby() {
+        let handle = &DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, _chan1) = add_client(handle).await;
+        let (_p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        assert_matches!(receive(&mut chan2).await, ServerMessage::PlayerUpdate(..));
+
+        // Reconnect p1 on a new channel.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(p1, send));
+        assert_matches!(
+            receive(&mut recv).await,
+            ServerMessage::Connected { seat, .. } if seat == p1
+        );
+        let ServerMessage::PlayerList(players) = receive(&mut recv).await else {
+            panic!("Should have received the player list.");
+        };
+        assert_eq!(players.len(), 2);
+        assert!(players.iter().any(|p| p.seat == p1 && p.ready));
+
+        // The rest of the table is sent p1's full details again.
+        let ServerMessage::PlayerUpdate(details) = receive(&mut chan2).await else {
+            panic!("Should have received a status update.");
+        };
+        assert_eq!(details.seat, p1);
+        assert!(details.ready);
+        assert_eq!(details.status, ClientStatus::Ok);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_before_first_pack() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, chan2) = add_client(handle).await;
+
+        // p2's connection drops before the draft starts, so their first pack
+        // can't be delivered.
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        drop(chan2);
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+
+        // On reconnecting, p2 is sent the pack they missed, and can pick.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(p2, send));
+        let ServerMessage::Reconnected {
+            in_progress: true,
+            pack: Some(pack2),
+            ..
+        } = receive(&mut recv).await
+        else {
+            panic!("Should have reconnected with the current pack.");
+        };
+        assert_ne!(pack1.id, pack2.id);
+        assert_eq!(pack2.cards.len(), 2);
+
+        pick(handle, p2, &pack2, 0);
+        receive_until(&mut recv, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_starting_summary() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 4),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, _chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+
+        // The summary of the seated players arrives before the first pack.
+        let (players, summary) = loop {
+            match receive(&mut chan1).await {
+                ServerMessage::Starting { players, summary } => break (players, summary),
+                ServerMessage::Pack { .. } => panic!("Pack arrived before summary."),
+                _ => {}
+            }
+        };
+        assert_eq!(players.len(), 2);
+        assert!(players.iter().all(|p| p.ready));
+        assert_eq!(summary.pack_sizes, vec![2]);
+        assert_matches!(receive(&mut chan1).await, ServerMessage::Pack { .. });
+    }
+
+    #[tokio::test]
+    async fn test_opened_packs() {
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 1,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let opened = || async {
+            let (send, recv) = oneshot::channel();
+            handle.send(DraftServerRequest::OpenedPacks(send));
+            recv.await.unwrap()
+        };
+        assert!(opened().await.is_none());
+
+        // Packs are kept after the draft finishes.
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        for _ in 0..2 {
+            let pack = receive_pack(&mut chan1).await;
+            pick(handle, p1, &pack, 0);
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Finished(..))).await;
+        let packs = opened().await.unwrap();
+        assert_eq!(
+            packs.iter().map(|p| p.round).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert!(packs.iter().all(|p| p.seat == p1));
+    }
+
+    #[tokio::test]
+    async fn test_reclaim_seat() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 4),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, _chan2) = add_client(handle).await;
+
+        async fn request_code(
+            handle: &ServerHandle,
+            seat: Uuid,
+            chan: &mut UnboundedReceiver<ServerMessage>,
+        ) -> String {
+            client_send(handle, seat, ClientMessage::RequestReclaimCode);
+            loop {
+                if let ServerMessage::ReclaimCode(code) = receive(chan).await {
+                    break code;
+                }
+            }
+        }
+
+        // Issuing a new code replaces the old one.
+        let old_code = request_code(handle, p1, &mut chan1).await;
+        let code = request_code(handle, p1, &mut chan1).await;
+        assert_ne!(old_code, code);
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        receive_pack(&mut chan1).await;
+
+        // The first player loses their connection, and their seat ID with it.
+        drop(chan1);
+        client_send(handle, p1, ClientMessage::Disconnected);
+
+        // A new connection can't join the draft, but can reclaim the seat.
+        let conn = Uuid::new_v4();
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(conn, send));
+        assert_matches!(receive(&mut recv).await, ServerMessage::Started);
+        client_send(handle, conn, ClientMessage::Reclaim(old_code));
+        assert_matches!(receive(&mut recv).await, ServerMessage::ReclaimRejected(..));
+
+        // Codes are compared ignoring case and spacing.
+        let typed = code.to_lowercase().replace('-', " ");
+        client_send(handle, conn, ClientMessage::Reclaim(typed));
+        let pack = loop {
+            if let ServerMessage::Reconnected { seat, pack, .. } = receive(&mut recv).await {
+                assert_eq!(seat, p1);
+                break pack.unwrap();
+            }
+        };
+
+        // The connection then picks for the reclaimed seat.
+        pick(handle, conn, &pack, 0);
+        receive_until(&mut recv, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+
+        // A connection guessing codes is refused after a few attempts, even
+        // if it then guesses right.
+        let guesser = Uuid::new_v4();
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(guesser, send));
+        assert_matches!(receive(&mut recv).await, ServerMessage::Started);
+        for _ in 0..MAX_RECLAIM_ATTEMPTS {
+            client_send(handle, guesser, ClientMessage::Reclaim("guess".into()));
+            assert_matches!(receive(&mut recv).await, ServerMessage::ReclaimRejected(..));
+        }
+        client_send(handle, guesser, ClientMessage::Reclaim(code));
+        assert_matches!(receive(&mut recv).await, ServerMessage::ReclaimRejected(..));
+        assert!(recv.recv().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_min_players() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            min_players: 2,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 8),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let status = || async move {
+            let (send, recv) = oneshot::channel();
+            handle.send(DraftServerRequest::Status(send));
+            serde_json::to_value(recv.await.unwrap()).unwrap()
+        };
+
+        // A lone ready player waits for the table to fill.
+        let (p1, mut chan1) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        assert_eq!(status().await["phase"], "lobby");
+
+        let (p2, _chan2) = add_client(handle).await;
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::PlayerList(..))).await;
+        assert_eq!(status().await["phase"], "lobby");
+
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        receive_pack(&mut chan1).await;
+        assert_eq!(status().await["phase"], "draft");
+    }
+
+    #[tokio::test]
+    async fn test_lobby_full() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            max_players: Some(2),
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 8),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, _chan1) = add_client(handle).await;
+        let (_p2, _chan2) = add_client(handle).await;
+
+        // A third player can't join the full lobby.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(Uuid::new_v4(), send));
+        assert_matches!(receive(&mut recv).await, ServerMessage::LobbyFull);
+
+        // But players already seated can reconnect.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(p1, send));
+        assert_matches!(receive(&mut recv).await, ServerMessage::Connected { seat, .. } if seat == p1);
+    }
+
+    #[tokio::test]
+    async fn test_status() {
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 2,
+            use_rarities: false,
+            max_players: Some(2),
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 8),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let status = || async move {
+            let (send, recv) = oneshot::channel();
+            handle.send(DraftServerRequest::Status(send));
+            serde_json::to_value(recv.await.unwrap()).unwrap()
+        };
+
+        let (p1, mut chan1) = add_client(handle).await;
+        assert_eq!(status().await["joinable"], true);
+
+        let (p2, _chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let status1 = status().await;
+        assert_eq!(status1["phase"], "lobby");
+        assert_eq!(status1["players"], 2);
+        assert_eq!(status1["ready"], 1);
+        assert!(status1["round"].is_null());
+        assert_eq!(status1["joinable"], false);
+
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        receive_pack(&mut chan1).await;
+        let status2 = status().await;
+        assert_eq!(status2["phase"], "draft");
+        assert_eq!(status2["players"], 2);
+        assert_eq!(status2["ready"], 2);
+        assert_eq!(status2["round"], 1);
+        assert_eq!(status2["joinable"], false);
+    }
+
+    #[tokio::test]
+    async fn test_pick_log() {
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 4),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let log = |seat| async move {
+            let (send, recv) = oneshot::channel();
+            handle.send(DraftServerRequest::PickLog(seat, send));
+            recv.await.unwrap()
+        };
+        assert!(log(p1).await.is_none());
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let mut picked = Vec::new();
+        for i in 0..4 {
+            let pack = receive_pack(&mut chan1).await;
+            picked.push(pack.cards[0].name().to_string());
+            pick(handle, p1, &pack, 0);
+
+            // Picks are private until the draft finishes.
+            if i == 0 {
+                receive_until(&mut chan1, |m| {
+                    matches!(m, ServerMessage::PickSuccessful(..))
+                })
+                .await;
+                assert!(log(p1).await.is_none());
+            }
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Finished(..))).await;
+
+        // Picks are kept after the draft finishes, with the rest of the pack.
+        assert_matches!(log(Uuid::new_v4()).await, Some(None));
+        let records = serde_json::to_value(log(p1).await.unwrap().unwrap()).unwrap();
+        let records = records.as_array().unwrap();
+        let field = |name: &str| -> Vec<serde_json::Value> {
+            records.iter().map(|r| r[name].clone()).collect()
+        };
+        assert_eq!(field("pack_number"), [1, 1, 2, 2]);
+        assert_eq!(field("pick_number"), [1, 2, 1, 2]);
+        assert_eq!(
+            records
+                .iter()
+                .map(|r| r["alternatives"].as_array().unwrap().len())
+                .collect::<Vec<_>>(),
+            [1, 0, 1, 0]
+        );
+        assert!(records
+            .iter()
+            .zip(&picked)
+            .all(|(r, name)| r["picked"]["name"] == *name));
+    }
+
+    #[tokio::test]
+    async fn test_sample_pack() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+
+        // Sampling doesn't use up the pool, so can be repeated.
+        for _ in 0..2 {
+            let (send, recv) = oneshot::channel();
+            handle.send(DraftServerRequest::SamplePack(send));
+            assert_eq!(recv.await.unwrap().unwrap().unwrap().len(), 2);
+        }
+
+        // Once the draft starts, there's no sample.
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        receive_pack(&mut chan1).await;
+        let (send, recv) = oneshot::channel();
+        handle.send(DraftServerRequest::SamplePack(send));
+        assert!(recv.await.unwrap().is_none());
+    }
+
+    #[test]
+    fn test_clean_lobby_name() {
+        assert_eq!(
+            clean_lobby_name("  Friday\n Cube\u{7}  ").unwrap(),
+            "Friday Cube"
+        );
+        assert!(clean_lobby_name(" \t ").is_err());
+        assert!(clean_lobby_name(&"a".repeat(MAX_LOBBY_NAME_LENGTH)).is_ok());
+        assert!(clean_lobby_name(&"a".repeat(MAX_LOBBY_NAME_LENGTH + 1)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_set_lobby_name() {
+        let handle = &DraftServer::spawn(
+            DraftConfig::default(),
+            DraftPool::new(),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (host, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        assert_matches!(receive(&mut chan1).await, ServerMessage::PlayerList(..));
+
+        // Only the host may rename the lobby.
+        client_send(handle, p2, ClientMessage::SetLobbyName("Not host".into()));
+        client_send(
+            handle,
+            host,
+            ClientMessage::SetLobbyName("Cube night".into()),
+        );
+        assert_matches!(
+            receive(&mut chan2).await,
+            ServerMessage::LobbyName(name) if name == "Cube night"
+        );
+
+        // New connections are told the lobby name.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(Uuid::new_v4(), send));
+        assert_matches!(
+            receive(&mut recv).await,
+            ServerMessage::Connected { name: Some(name), .. } if name == "Cube night"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_follow_player() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, _chan2) = add_client(handle).await;
+        let (spectator, mut spec) = add_spectator(handle).await;
+
+        client_send(handle, spectator, ClientMessage::Follow(p1));
+        assert_matches!(
+            receive(&mut spec).await,
+            ServerMessage::Following { seat, pool, pack: None } if seat == p1 && pool.is_empty()
+        );
+
+        // Spectators can't ready up or pick.
+        client_send(handle, spectator, ClientMessage::ReadyState(true));
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+
+        // The followed player's pack is forwarded to the spectator.
+        let pack = receive_pack(&mut chan1).await;
+        receive_until(&mut spec, |m| matches!(m, ServerMessage::Pack { .. })).await;
+
+        pick(handle, spectator, &pack, 0);
+        pick(handle, p1, &pack, 0);
+        receive_until(&mut spec, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+        receive_until(&mut chan1, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_spectator_view() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack = receive_pack(&mut chan1).await;
+        receive_pack(&mut chan2).await;
+
+        // Joining mid-draft, the spectator sees how many packs each seat has.
+        let (_, mut spec) = add_spectator(handle).await;
+        assert_matches!(
+            receive(&mut spec).await,
+            ServerMessage::SpectatorView(queues) if queues[&p1] == 1 && queues[&p2] == 1
+        );
+
+        pick(handle, p1, &pack, 0);
+        receive_until(&mut spec, |m| {
+            matches!(
+                m,
+                ServerMessage::SpectatorView(queues) if queues[&p1] == 0 && queues[&p2] == 2
+            )
+        })
+        .await;
+    }
+
+    #[test]
+    fn test_build_deck() {
+        let pool = vec![
+            Card::sample(Rarity::Common),
+            Card::sample(Rarity::Uncommon),
+            Card::sample(Rarity::Rare),
+        ];
+        let deck = build_deck(&pool, &[2, 0], 2).unwrap();
+        assert_eq!(deck[0].name(), pool[2].name());
+        assert_eq!(deck[1].name(), pool[0].name());
+
+        assert!(build_deck(&pool, &[0], 2).is_err());
+        assert!(build_deck(&pool, &[0, 3], 2).is_err());
+        assert!(build_deck(&pool, &[1, 1], 2).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deck_building() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            deck_size: Some(1),
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        for _ in 0..2 {
+            let pack1 = receive_pack(&mut chan1).await;
+            let pack2 = receive_pack(&mut chan2).await;
+            pick(handle, p1, &pack1, 0);
+            pick(handle, p2, &pack2, 0);
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::DeckBuilding(1))).await;
+        receive_until(&mut chan2, |m| matches!(m, ServerMessage::DeckBuilding(1))).await;
+
+        // Pools are final once deckbuilding starts.
+        let (send, recv) = oneshot::channel();
+        handle.send(DraftServerRequest::Pools(send));
+        let pools = recv.await.unwrap().unwrap();
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[0].seat, p1);
+        assert!(pools.iter().all(|pool| pool.cards.len() == 2));
+
+        // Decks must be the right size.
+        client_send(handle, p1, ClientMessage::SubmitDeck(vec![0, 1]));
+        assert_matches!(receive(&mut chan1).await, ServerMessage::DeckRejected(..));
+
+        client_send(handle, p1, ClientMessage::SubmitDeck(vec![0]));
+        assert_matches!(
+            receive(&mut chan1).await,
+            ServerMessage::DeckSubmitted(deck) if deck.len() == 1
+        );
+        assert_matches!(
+            receive(&mut chan2).await,
+            ServerMessage::PlayerUpdate(PlayerDetails { seat, ready: true, .. }) if seat == p1
+        );
+
+        // Once every deck is in, everyone is sent all of the decks.
+        client_send(handle, p2, ClientMessage::SubmitDeck(vec![1]));
+        receive_until(
+            &mut chan1,
+            |m| matches!(m, ServerMessage::Decks(decks) if decks.len() == 2),
+        )
+        .await;
+        receive_until(
+            &mut chan2,
+            |m| matches!(m, ServerMessage::Decks(decks) if decks.len() == 2),
+        )
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_deck_deadline() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            deck_size: Some(1),
+            deck_deadline: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        for _ in 0..2 {
+            let pack1 = receive_pack(&mut chan1).await;
+            let pack2 = receive_pack(&mut chan2).await;
+            pick(handle, p1, &pack1, 0);
+            pick(handle, p2, &pack2, 0);
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::DeckDeadline(0))).await;
+        receive_until(&mut chan2, |m| matches!(m, ServerMessage::DeckDeadline(0))).await;
+
+        client_send(handle, p1, ClientMessage::SubmitDeck(vec![0]));
+        assert_matches!(receive(&mut chan1).await, ServerMessage::DeckSubmitted(..));
+
+        // Once the deadline passes, the missing deck is built automatically.
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        receive_until(
+            &mut chan2,
+            |m| matches!(m, ServerMessage::DeckAutoSubmitted(deck) if deck.len() == 1),
+        )
+        .await;
+        receive_until(
+            &mut chan1,
+            |m| matches!(m, ServerMessage::Decks(decks) if decks.len() == 2),
+        )
+        .await;
+    }
+
+    #[test]
+    fn test_auto_deck() {
+        let mut pool = vec![
+            Card::sample(Rarity::Mythic),
+            Card::sample(Rarity::Common),
+            Card::sample(Rarity::Uncommon),
+            Card::sample(Rarity::Common),
+            Card::sample(Rarity::Common),
+        ];
+        pool[0].colours = vec![Colour::B];
+        pool[1].colours = vec![Colour::G];
+        pool[2].colours = vec![Colour::G];
+        pool[3].colours = vec![Colour::R];
+        pool[4].colours = vec![Colour::R];
+
+        // Small pools are used whole.
+        assert_eq!(auto_deck(&pool[..2], 3).len(), 2);
+
+        // Cards in the two main colours come first, by rarity.
+        let deck = auto_deck(&pool, 2);
+        assert_eq!(deck[0].rarity, Rarity::Uncommon);
+        assert_eq!(deck[1].rarity, Rarity::Common);
+    }
+
+    #[tokio::test]
+    async fn test_results() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 1,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 0, 0),
+            Some("League".into()),
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+
+        let results = || async {
+            let (send, recv) = oneshot::channel();
+            handle.send(DraftServerRequest::Results(send));
+            recv.await.unwrap()
+        };
+        assert!(results().await.is_none());
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+        pick(handle, p1, &pack1, 0);
+        pick(handle, p2, &pack2, 0);
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::DraftComplete)).await;
+
+        let results = results().await.unwrap();
+        assert_eq!(results.name.as_deref(), Some("League"));
+        assert_eq!(results.seats.len(), 2);
+        assert_eq!(results.seats[0].seat, p1);
+        assert!(results
+            .seats
+            .iter()
+            .all(|seat| seat.pool.len() == 1 && seat.deck.is_none()));
+        assert!(results
+            .started
+            .is_some_and(|started| started <= results.finished));
+
+        let json = serde_json::to_value(&results).unwrap();
+        assert_eq!(json["config"]["rounds"], 1);
+        assert_eq!(json["config"]["reconnect_grace"], 10000);
+    }
+
+    #[tokio::test]
+    async fn test_pick_timer() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            pick_timer: Some(Duration::from_millis(20)),
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        receive_until(&mut chan2, |m| {
+            matches!(
+                m,
+                ServerMessage::Pack {
+                    time_left: Some(0),
+                    ..
+                }
+            )
+        })
+        .await;
+        let pack1 = receive_pack(&mut chan1).await;
+        pick(handle, p1, &pack1, 0);
+
+        // Once time runs out, a card is picked for the player and the table
+        // is warned.
+        tokio::time::sleep(Duration::from_millis(30)).await;
+        receive_until(&mut chan2, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+        receive_until(&mut chan1, |m| {
+            matches!(
+                m,
+                ServerMessage::PlayerUpdate(PlayerDetails {
+                    seat,
+                    status: ClientStatus::Warning,
+                    ..
+                }) if seat == p2
+            )
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_bots() {
+        let config = DraftConfig {
+            rounds: 3,
+            cards_per_pack: 2,
+            use_rarities: false,
+            bots: 3,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(6, 6, 6, 6),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+
+        // Bots pick as soon as a pack reaches them, so the lone player is
+        // never left waiting.
+        for _ in 0..6 {
+            let pack = receive_pack(&mut chan1).await;
+            pick(handle, p1, &pack, 0);
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::DraftComplete)).await;
+        assert_matches!(receive(&mut chan1).await, ServerMessage::Finished(pool) if pool.len() == 6);
+
+        // The bots' pools are part of the results, after the player's.
+        let (send, recv) = oneshot::channel();
+        handle.send(DraftServerRequest::Results(send));
+        let results = recv.await.unwrap().unwrap();
+        assert_eq!(results.seats[0].seat, p1);
+        assert_eq!(
+            results.seats[1..]
+                .iter()
+                .map(|seat| seat.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Bot 1", "Bot 2", "Bot 3"]
+        );
+        assert!(results.seats.iter().all(|seat| seat.pool.len() == 6));
+    }
+
+    #[tokio::test]
+    async fn test_reap() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let mut servers = ServerPool::new(TIMEOUT, TIMEOUT);
+        let owner = Some("owner".to_string());
+        let ids: Vec<Uuid> = (0..3)
+            .map(|_| {
+                let pool = DraftPool::sample(0, 0, 0, 8);
+                servers.spawn(config.clone(), pool, None, owner.clone())
+            })
+            .collect();
+        assert_eq!(servers.reap(), 0);
+
+        for &id in &ids[..2] {
+            let handle = servers.handle(id).unwrap();
+            handle.send(DraftServerRequest::Terminate("Closed.".to_string()));
+        }
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        assert_eq!(servers.reap(), 2);
+        assert_eq!(servers.servers.len(), 1);
+        assert_eq!(servers.owners.len(), 1);
+        assert!(servers.handle(ids[2]).is_some());
+        assert_eq!(servers.owned_by("owner"), 1);
+    }
+
+    #[tokio::test]
+    async fn test_iter_status() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let mut servers = ServerPool::new(TIMEOUT, TIMEOUT);
+        let first = servers.spawn(config.clone(), DraftPool::sample(0, 0, 0, 8), None, None);
+        let second = servers.spawn(config, DraftPool::sample(0, 0, 0, 8), None, None);
+        add_client(&servers.handle(first).unwrap()).await;
+
+        let closed = servers.handle(second).unwrap();
+        closed.send(DraftServerRequest::Terminate("Closed.".to_string()));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        assert!(!closed.is_open());
+
+        let listings = servers.iter_status().await;
+        assert_eq!(listings.len(), 1);
+        let listing = serde_json::to_value(&listings[0]).unwrap();
+        assert_eq!(listing["id"], first.to_string());
+        assert_eq!(listing["phase"], "lobby");
+        assert_eq!(listing["players"], 1);
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_restore() {
+        let dir = std::env::temp_dir().join(format!("drafttool-{}", Uuid::new_v4()));
+        let snapshots = SnapshotConfig {
+            dir: dir.clone(),
+            interval: Duration::ZERO,
+        };
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 3,
+            use_rarities: false,
+            webhook_url: Some("http://127.0.0.1:9/secret".to_string()),
+            ..Default::default()
+        };
+        let mut servers = ServerPool::new(TIMEOUT, TIMEOUT);
+        assert_eq!(servers.restore(snapshots.clone()), 0);
+        let id = servers.spawn(config, DraftPool::sample(0, 0, 0, 12), None, None);
+        let handle = &servers.handle(id).unwrap();
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, _chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack = receive_pack(&mut chan1).await;
+        pick(handle, p1, &pack, 0);
+        receive_until(&mut chan1, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+
+        // Each seat's pool and current pack, as sent on reconnection.
+        async fn seat_state(handle: &ServerHandle, seat: Uuid) -> serde_json::Value {
+            let (send, mut recv) = unbounded_channel();
+            handle.send(DraftServerRequest::Connect(seat, send));
+            let message = receive(&mut recv).await;
+            assert_matches!(message, ServerMessage::Reconnected { .. });
+            serde_json::to_value(message).unwrap()
+        }
+
+        // Let the server write its snapshot once it is idle.
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let before = [seat_state(handle, p1).await, seat_state(handle, p2).await];
+
+        // The webhook URL may hold a secret, so isn't saved.
+        let saved = std::fs::read_to_string(dir.join(format!("{id}.json"))).unwrap();
+        assert!(!saved.contains("secret"));
+
+        let mut restored = ServerPool::new(TIMEOUT, TIMEOUT);
+        assert_eq!(restored.restore(snapshots), 1);
+        let handle = &restored.handle(id).unwrap();
+        let after = [seat_state(handle, p1).await, seat_state(handle, p2).await];
+        assert_eq!(before, after);
+
+        // The snapshot was taken after the first pick, with the second seat
+        // yet to pick.
+        assert!(before[0]["value"]["pack"].is_null());
+        assert!(before[1]["value"]["pack"].is_object());
+        assert_eq!(before[0]["value"]["pool"].as_array().unwrap().len(), 1);
+        std::fs::remove_dir_all(dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_undo_pick() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 6),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+
+        // Player two has nothing else to pick from once player one's pack is
+        // taken back.
+        pick(handle, p2, &pack2, 0);
+        pick(handle, p1, &pack1, 0);
+        let passed = receive_pack(&mut chan2).await;
+        assert_eq!(passed.id, pack1.id);
+        client_send(handle, p1, ClientMessage::UndoPick);
+        receive_until(
+            &mut chan1,
+            |m| matches!(m, ServerMessage::PickUndone(pool) if pool.is_empty()),
+        )
+        .await;
+        let reclaimed = receive_pack(&mut chan1).await;
+        assert_eq!(reclaimed.id, pack1.id);
+        assert_eq!(reclaimed.cards.len(), 3);
+        receive_until(&mut chan2, |m| matches!(m, ServerMessage::PackWithdrawn)).await;
+
+        // Once player two has picked from the pack, it's too late.
+        pick(handle, p1, &reclaimed, 0);
+        let passed = receive_pack(&mut chan2).await;
+        pick(handle, p2, &passed, 0);
+        receive_until(&mut chan2, |m| {
+            matches!(m, ServerMessage::PickSuccessful(..))
+        })
+        .await;
+        client_send(handle, p1, ClientMessage::UndoPick);
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::UndoRejected(..))).await;
+    }
+
+    #[tokio::test]
+    async fn test_winston() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            use_rarities: false,
+            mode: DraftMode::Winston,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 2, 2, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+
+        // A Winston draft needs at least two players.
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+
+        // Each player takes the first pile on their turn, until the deck and
+        // piles are empty.
+        loop {
+            let state = loop {
+                if let ServerMessage::Winston(state) = receive(&mut chan1).await {
+                    break state;
                 }
-            }
+            };
+            let Some(active) = state.active else {
+                assert_eq!(state.deck_size, 0);
+                assert!(state.pile_sizes.iter().all(|&size| size == 0));
+                break;
+            };
+            client_send(handle, active, ClientMessage::Winston(WinstonAction::Take));
         }
-        false
+
+        let mut cards = 0;
+        for chan in [&mut chan1, &mut chan2] {
+            let pool = loop {
+                if let ServerMessage::Finished(pool) = receive(chan).await {
+                    break pool;
+                }
+            };
+            cards += pool.len();
+        }
+        assert_eq!(cards, 6);
     }
 
-    fn finish_if_done(&mut self) {
-        if let Phase::Draft(draft) = &self.phase {
-            if draft.draft_complete() {
-                let pools = draft.pools().clone();
-                for (id, pool) in &pools {
-                    self.send_to(*id, ServerMessage::Finished(pool.clone()));
+    #[tokio::test]
+    async fn test_grid() {
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 9,
+            use_rarities: false,
+            mode: DraftMode::Grid,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 36),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+
+        // Four grids are dealt from the 36 cards. Seats take turns, with the
+        // other seat taking first in the next grid, and each takes the first
+        // full row of the grid it was last shown.
+        for seat in [p1, p2, p2, p1, p1, p2, p2, p1] {
+            let chan = if seat == p1 { &mut chan1 } else { &mut chan2 };
+            let mut grid = Vec::new();
+            loop {
+                match receive(chan).await {
+                    ServerMessage::Grid(slots) => grid = slots,
+                    ServerMessage::GridTurn => break,
+                    _ => {}
                 }
-                self.phase = Phase::Finished(pools);
             }
+            let row = grid
+                .chunks(3)
+                .position(|row| row.iter().all(Option::is_some));
+            client_send(
+                handle,
+                seat,
+                ClientMessage::Grid(GridSelection::Row(row.unwrap())),
+            );
         }
-    }
-}
 
-#[cfg(test)]
-mod test {
-    use std::assert_matches::assert_matches;
+        for chan in [&mut chan1, &mut chan2] {
+            let pool = loop {
+                if let ServerMessage::Finished(pool) = receive(chan).await {
+                    break pool;
+                }
+            };
+            assert_eq!(pool.len(), 12);
+        }
+    }
 
-    use tokio::sync::mpsc::unbounded_channel;
+    #[tokio::test]
+    async fn test_include_basics() {
+        for include_basics in [false, true] {
+            let config = DraftConfig {
+                rounds: 1,
+                cards_per_pack: 2,
+                use_rarities: false,
+                include_basics,
+                basics_per_colour: 3,
+                ..Default::default()
+            };
+            let handle = &DraftServer::spawn(
+                config,
+                DraftPool::sample(0, 0, 0, 2),
+                None,
+                TIMEOUT,
+                TIMEOUT,
+                None,
+            );
+            let (p1, mut chan1) = add_client(handle).await;
+            client_send(handle, p1, ClientMessage::ReadyState(true));
+            for _ in 0..2 {
+                let pack = receive_pack(&mut chan1).await;
+                pick(handle, p1, &pack, 0);
+            }
 
-    use super::*;
-    use crate::draft::packs::DraftPool;
+            let pool = loop {
+                if let ServerMessage::Finished(pool) = receive(&mut chan1).await {
+                    break pool;
+                }
+            };
+            let basics = pool
+                .iter()
+                .filter(|card| card.rarity == Rarity::Special)
+                .count();
+            if include_basics {
+                assert_eq!(pool.len(), 17);
+                assert_eq!(basics, 15);
+                assert!(pool.iter().any(|card| card.name() == "Island"));
+            } else {
+                assert_eq!(pool.len(), 2);
+                assert_eq!(basics, 0);
+            }
 
-    fn close_server(handle: ServerHandle) {
-        handle.send(DraftServerRequest::Terminate(String::new()));
+            // Basics don't count towards the colours of the pool.
+            assert_matches!(
+                receive(&mut chan1).await,
+                ServerMessage::PoolSummary(summary) if summary.colourless == 2
+            );
+        }
     }
 
-    fn client_send(handle: &ServerHandle, id: Uuid, message: ClientMessage) {
-        handle.send(DraftServerRequest::Message(id, message))
-    }
+    #[tokio::test]
+    async fn test_rochester() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 3,
+            use_rarities: false,
+            mode: DraftMode::Rochester,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 6),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
 
-    async fn receive(chan: &mut UnboundedReceiver<ServerMessage>) -> ServerMessage {
-        match tokio::time::timeout(tokio::time::Duration::from_millis(1), chan.recv()).await {
-            Ok(Some(msg)) => msg,
-            Ok(None) => panic!("Channel was closed, expected to receive a message."),
-            Err(_) => panic!("Didn't receive on channel after 1ms."),
+        // Both seats see the first pack, opened by the first seat, which
+        // picks first.
+        let pack = receive_pack(&mut chan2).await;
+        assert_matches!(receive(&mut chan2).await, ServerMessage::WaitingFor(seat) if seat == p1);
+        pick(handle, p2, &pack, 0);
+        receive_until(&mut chan2, |m| match m {
+            ServerMessage::PickSuccessful(..) => panic!("Pick made out of turn."),
+            ServerMessage::WaitingFor(seat) => seat == p1,
+            _ => false,
+        })
+        .await;
+
+        // Turns snake back from the last seat, which picks twice.
+        for seat in [p1, p2, p2, p2, p1, p1] {
+            let chan = if seat == p1 { &mut chan1 } else { &mut chan2 };
+            let mut pack = None;
+            loop {
+                match receive(chan).await {
+                    ServerMessage::Pack { pack: open, .. } => pack = Some(open),
+                    ServerMessage::YourTurn => break,
+                    ServerMessage::WaitingFor(other) => assert_ne!(other, seat),
+                    _ => {}
+                }
+            }
+            pick(handle, seat, &pack.unwrap(), 0);
         }
-    }
 
-    async fn receive_until<P: Fn(ServerMessage) -> bool>(
-        chan: &mut UnboundedReceiver<ServerMessage>,
-        pred: P,
-    ) {
-        while !pred(receive(chan).await) {}
+        for chan in [&mut chan1, &mut chan2] {
+            let pool = loop {
+                if let ServerMessage::Finished(pool) = receive(chan).await {
+                    break pool;
+                }
+            };
+            assert_eq!(pool.len(), 3);
+        }
     }
 
-    async fn add_client(handle: &ServerHandle) -> (Uuid, UnboundedReceiver<ServerMessage>) {
-        let user = Uuid::new_v4();
-        let (send, mut recv) = unbounded_channel();
-        handle.send(DraftServerRequest::Connect(user, send));
-        if let ServerMessage::Connected { draft, seat } = receive(&mut recv).await {
-            assert_eq!(draft, handle.id);
-            assert_eq!(seat, user);
-        } else {
-            panic!("Expected to receive connected message first.");
-        };
-        if let ServerMessage::PlayerList(players) = receive(&mut recv).await {
-            assert!(players.iter().any(|p| p.seat == user));
-        } else {
-            panic!("Expected to receive player list second.");
+    #[tokio::test]
+    async fn test_reveal_bombs() {
+        let config = DraftConfig {
+            rounds: 2,
+            cards_per_pack: 1,
+            use_rarities: false,
+            reveal_bombs: true,
+            ..Default::default()
         };
-        (user, recv)
-    }
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 1, 1, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
 
-    #[tokio::test]
-    async fn test_joining_closing_server() {
-        let handle = DraftServer::spawn(Default::default(), DraftPool::new());
-        let (_user, mut recv) = add_client(&handle).await;
-        close_server(handle);
-        assert_matches!(recv.recv().await.unwrap(), ServerMessage::FatalError(..));
+        // Each round, the best card in every pack is revealed before picking.
+        for _ in 0..2 {
+            let mut reveals = Vec::new();
+            let pack = loop {
+                match receive(&mut chan1).await {
+                    ServerMessage::Revealed(r) => reveals = r,
+                    ServerMessage::Pack { pack, .. } => break pack,
+                    _ => {}
+                }
+            };
+            assert_eq!(reveals.len(), 2);
+            let reveal = reveals.iter().find(|r| r.seat == p1).unwrap();
+            assert_eq!(reveal.card.name(), pack.cards[0].name());
+
+            pick(handle, p1, &pack, 0);
+            pick(handle, p2, &receive_pack(&mut chan2).await, 0);
+        }
     }
 
     #[tokio::test]
-    async fn test_draft() {
-        let pool = DraftPool::sample(1, 1, 1, 1);
+    async fn test_min_pick_delay() {
+        let delay = Duration::from_millis(20);
         let config = DraftConfig {
             rounds: 1,
-            cards_per_pack: 2,
-            unique_cards: true,
+            cards_per_pack: 1,
             use_rarities: false,
+            min_pick_delay: delay,
             ..Default::default()
         };
-        let handle = DraftServer::spawn(config, pool);
-        let (p1, mut chan1) = add_client(&handle).await;
-        let (p2, mut chan2) = add_client(&handle).await;
-        assert_matches!(receive(&mut chan1).await, ServerMessage::PlayerList(..));
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let pack = receive_pack(&mut chan1).await;
 
-        // Once both players are ready
-        client_send(&handle, p1, ClientMessage::ReadyState(true));
-        receive_until(&mut chan2, |m| matches!(m, ServerMessage::PlayerUpdate(..))).await;
-        client_send(&handle, p2, ClientMessage::ReadyState(true));
-        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Pack(..))).await;
-        receive_until(&mut chan2, |m| matches!(m, ServerMessage::Pack(..))).await;
+        // An instant pick is rejected, and the pack sent again.
+        pick(handle, p1, &pack, 0);
+        let resent = receive_pack(&mut chan1).await;
+        assert_eq!(resent.id, pack.id);
 
-        // Client one passes, don't expect any packs at this stage as they are
-        // backed up behind p2.
-        client_send(&handle, p1, ClientMessage::Pick(0));
+        tokio::time::sleep(delay).await;
+        pick(handle, p1, &pack, 0);
         receive_until(&mut chan1, |m| {
             matches!(m, ServerMessage::PickSuccessful(..))
         })
         .await;
+    }
 
-        // After p2s pick, both players should be sent a new pack.
-        client_send(&handle, p2, ClientMessage::Pick(0));
+    #[tokio::test]
+    async fn test_replay() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        for _ in 0..2 {
+            let pack1 = receive_pack(&mut chan1).await;
+            let pack2 = receive_pack(&mut chan2).await;
+            pick(handle, p1, &pack1, 0);
+            pick(handle, p2, &pack2, 0);
+        }
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Finished(..))).await;
+
+        // Every pick is replayed, in order, then the end of the replay.
+        let (spectator, mut chan) = add_spectator(handle).await;
+        client_send(handle, spectator, ClientMessage::Replay(f32::INFINITY));
+        let mut picks = Vec::new();
+        loop {
+            match tokio::time::timeout(TIMEOUT, chan.recv()).await {
+                Ok(Some(ServerMessage::ReplayPick(pick))) => picks.push(pick),
+                Ok(Some(ServerMessage::ReplayFinished)) => break,
+                Ok(Some(_)) => {}
+                _ => panic!("Replay didn't finish."),
+            }
+        }
+        assert_eq!(picks.len(), 4);
+        assert!(picks.windows(2).all(|w| w[0].at <= w[1].at));
+        assert_eq!(picks.iter().filter(|pick| pick.seat == p1).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_replay_restarts() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 1,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        let pack = receive_pack(&mut chan1).await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        pick(handle, p1, &pack, 0);
+        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Finished(..))).await;
+
+        // A slow replay is replaced by the next, so only one stream arrives.
+        let (spectator, mut chan) = add_spectator(handle).await;
+        client_send(handle, spectator, ClientMessage::Replay(MIN_REPLAY_SPEED));
+        client_send(handle, spectator, ClientMessage::Replay(f32::INFINITY));
+        let mut messages = Vec::new();
+        while let Ok(Some(message)) =
+            tokio::time::timeout(Duration::from_millis(400), chan.recv()).await
+        {
+            if matches!(
+                message,
+                ServerMessage::ReplayPick(..) | ServerMessage::ReplayFinished
+            ) {
+                messages.push(message);
+            }
+        }
+        assert_eq!(messages.len(), 2);
+        assert_matches!(messages[0], ServerMessage::ReplayPick(..));
+        assert_matches!(messages[1], ServerMessage::ReplayFinished);
+    }
+
+    #[tokio::test]
+    async fn test_peek_next() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 4),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+        pick(handle, p2, &pack2, 0);
         receive_until(&mut chan2, |m| {
             matches!(m, ServerMessage::PickSuccessful(..))
         })
         .await;
-        receive_until(&mut chan2, |m| matches!(m, ServerMessage::Pack(..))).await;
-        receive_until(&mut chan1, |m| matches!(m, ServerMessage::Pack(..))).await;
 
-        client_send(&handle, p1, ClientMessage::Pick(0));
+        // p1 can see the pack passed by p2, twice over as nothing changes.
+        for _ in 0..2 {
+            client_send(handle, p1, ClientMessage::PeekNext);
+            let peeked = loop {
+                if let ServerMessage::PeekPack(pack) = receive(&mut chan1).await {
+                    break pack;
+                }
+            };
+            assert_eq!(peeked.id, pack2.id);
+            assert_eq!(peeked.cards.len(), 1);
+        }
+
+        // Picks from the peeked pack are rejected, the current pack is resent.
+        pick(handle, p1, &pack2, 0);
+        assert_eq!(receive_pack(&mut chan1).await.id, pack1.id);
+        pick(handle, p1, &pack1, 0);
         receive_until(&mut chan1, |m| {
             matches!(m, ServerMessage::PickSuccessful(..))
         })
         .await;
-        client_send(&handle, p2, ClientMessage::Pick(0));
+        assert_eq!(receive_pack(&mut chan1).await.id, pack2.id);
+    }
+
+    #[tokio::test]
+    async fn test_repeated_pick() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+
+        // Once p2 passes, p1 has a second pack queued behind their first.
+        pick(handle, p2, &pack2, 0);
         receive_until(&mut chan2, |m| {
             matches!(m, ServerMessage::PickSuccessful(..))
         })
         .await;
 
+        // A repeated pick must not take a card from the queued pack. The
+        // first pick is followed by the next pack, and the second is rejected
+        // with the next pack resent.
+        pick(handle, p1, &pack1, 0);
+        pick(handle, p1, &pack1, 0);
+        let mut picks = 0;
+        let mut packs = Vec::new();
+        while packs.len() < 2 {
+            match receive(&mut chan1).await {
+                ServerMessage::PickSuccessful(..) => picks += 1,
+                ServerMessage::Pack { pack, .. } => packs.push(pack),
+                _ => {}
+            }
+        }
+        assert_eq!(picks, 1);
+        assert!(packs
+            .iter()
+            .all(|pack| pack.id != pack1.id && pack.cards.len() == 1));
+    }
+
+    #[tokio::test]
+    async fn test_kick() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 2,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(1, 1, 1, 1),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+
+        // Only the host may kick.
+        client_send(handle, p2, ClientMessage::Kick(p1));
+        client_send(handle, p1, ClientMessage::Kick(p2));
+        receive_until(&mut chan2, |m| matches!(m, ServerMessage::Kicked)).await;
+
+        // A pick arriving from the kicked seat is dropped, and the pack it
+        // was holding is picked from on its behalf and passed on.
+        pick(handle, p2, &pack2, 0);
+        pick(handle, p1, &pack1, 0);
+        let pack = receive_pack(&mut chan1).await;
+        assert_eq!(pack.id, pack2.id);
+        assert_eq!(pack.cards.len(), 1);
+        pick(handle, p1, &pack, 0);
         receive_until(
             &mut chan1,
             |m| matches!(m, ServerMessage::Finished(cards) if cards.len() == 2),
         )
         .await;
-        receive_until(
-            &mut chan2,
-            |m| matches!(m, ServerMessage::Finished(cards) if cards.len() == 2),
-        )
-        .await;
+
+        // The kicked seat's pool is still exported, under its name.
+        let (send, recv) = oneshot::channel();
+        handle.send(DraftServerRequest::Pools(send));
+        let pools = recv.await.unwrap().unwrap();
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[1].seat, p2);
+        assert_ne!(pools[1].name, "Absent player");
+        assert_eq!(pools[1].cards.len(), 2);
+
+        // Reconnecting doesn't restore the seat.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(p2, send));
+        assert_matches!(receive(&mut recv).await, ServerMessage::Kicked);
     }
 
     #[tokio::test]
-    async fn test_set_name() {
-        let handle = &DraftServer::spawn(DraftConfig::default(), DraftPool::new());
-        let (p1, mut _chan1) = add_client(handle).await;
-        let (_p2, mut chan2) = add_client(handle).await;
+    async fn test_keep_lobby() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 1,
+            unique_cards: true,
+            use_rarities: false,
+            keep_lobby: true,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
 
-        client_send(handle, p1, ClientMessage::SetName("name".into()));
-        let ServerMessage::PlayerUpdate(PlayerDetails {
-            seat,
-            name,
-            ready,
-            status,
-        }) = receive(&mut chan2).await
-        else {
-            panic!("Should have received a status update.");
+        for _ in 0..2 {
+            client_send(handle, p1, ClientMessage::ReadyState(true));
+            client_send(handle, p2, ClientMessage::ReadyState(true));
+            let pack1 = receive_pack(&mut chan1).await;
+            let pack2 = receive_pack(&mut chan2).await;
+            pick(handle, p1, &pack1, 0);
+            pick(handle, p2, &pack2, 0);
+            receive_until(&mut chan1, |m| matches!(m, ServerMessage::Finished(..))).await;
+
+            // Once the draft is over, the table is returned to the lobby,
+            // and can draft again from the same pool.
+            receive_until(&mut chan1, |m| matches!(m, ServerMessage::ReturnedToLobby)).await;
+            receive_until(&mut chan2, |m| matches!(m, ServerMessage::ReturnedToLobby)).await;
+            assert_matches!(
+                receive(&mut chan1).await,
+                ServerMessage::PlayerList(players) if players.len() == 2 && !players[0].ready
+            );
+        }
+    }
+
+    #[test]
+    fn test_pool_summary() {
+        let card = |colours: &[Colour]| {
+            let mut card = Card::sample(Rarity::Common);
+            card.colours = colours.to_vec();
+            card
         };
-        assert_eq!(seat, p1);
-        assert_eq!(name, "name");
-        assert!(!ready);
-        assert_eq!(status, ClientStatus::Ok);
+        let pool = [
+            card(&[Colour::W]),
+            card(&[Colour::W, Colour::U]),
+            card(&[Colour::G]),
+            card(&[]),
+            card(&[]),
+        ];
+        let summary = PoolSummary::of(&pool);
+        assert_eq!(
+            summary.colours,
+            HashMap::from([(Colour::W, 2), (Colour::U, 1), (Colour::G, 1)])
+        );
+        assert_eq!(summary.colourless, 2);
+        assert_eq!(PoolSummary::of(&[]), PoolSummary::default());
+    }
 
+    #[tokio::test]
+    async fn test_reconnect_grace() {
+        let grace = Duration::from_millis(20);
+        let config = DraftConfig {
+            reconnect_grace: grace,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(config, DraftPool::new(), None, TIMEOUT, TIMEOUT, None);
+        let (p1, _chan1) = add_client(handle).await;
+        let (_p2, mut chan2) = add_client(handle).await;
         client_send(handle, p1, ClientMessage::ReadyState(true));
-        let ServerMessage::PlayerUpdate(PlayerDetails {
-            seat,
-            name,
-            ready,
-            status,
-        }) = receive(&mut chan2).await
-        else {
+        assert_matches!(receive(&mut chan2).await, ServerMessage::PlayerUpdate(..));
+
+        // Disconnecting marks the seat as away, rather than freeing it.
+        client_send(handle, p1, ClientMessage::Disconnected);
+        let ServerMessage::PlayerUpdate(details) = receive(&mut chan2).await else {
             panic!("Should have received a status update.");
         };
-        assert_eq!(seat, p1);
-        assert_eq!(name, "name");
-        assert!(ready);
-        assert_eq!(status, ClientStatus::Ok);
+        assert_eq!(details.seat, p1);
+        assert_eq!(details.status, ClientStatus::Error);
+
+        // Reconnecting within the window restores the seat and ready state.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(p1, send));
+        assert_matches!(
+            receive(&mut recv).await,
+            ServerMessage::Connected { seat, .. } if seat == p1
+        );
+        assert_matches!(
+            receive(&mut recv).await,
+            ServerMessage::PlayerList(players)
+                if players.iter().any(|p| p.seat == p1 && p.ready)
+        );
+
+        // Once the window passes, the seat is freed.
+        client_send(handle, p1, ClientMessage::Disconnected);
+        tokio::time::sleep(grace * 2).await;
+        receive_until(
+            &mut chan2,
+            |m| matches!(m, ServerMessage::PlayerList(players) if players.len() == 1),
+        )
+        .await;
+
+        // Reconnecting afterwards takes a new seat, which isn't ready.
+        let (send, mut recv) = unbounded_channel();
+        handle.send(DraftServerRequest::Connect(p1, send));
+        receive_until(&mut recv, |m| {
+            matches!(
+                m,
+                ServerMessage::PlayerList(players)
+                    if players.iter().any(|p| p.seat == p1 && !p.ready)
+            )
+        })
+        .await;
+    }
+
+    #[tokio::test]
+    async fn test_draft_complete() {
+        let config = DraftConfig {
+            rounds: 1,
+            cards_per_pack: 1,
+            use_rarities: false,
+            ..Default::default()
+        };
+        let handle = &DraftServer::spawn(
+            config,
+            DraftPool::sample(0, 0, 0, 2),
+            None,
+            TIMEOUT,
+            TIMEOUT,
+            None,
+        );
+        let (p1, mut chan1) = add_client(handle).await;
+        let (p2, mut chan2) = add_client(handle).await;
+        let (_, mut spectator) = add_spectator(handle).await;
+        client_send(handle, p1, ClientMessage::ReadyState(true));
+        client_send(handle, p2, ClientMessage::ReadyState(true));
+        let pack1 = receive_pack(&mut chan1).await;
+        let pack2 = receive_pack(&mut chan2).await;
+        pick(handle, p1, &pack1, 0);
+        pick(handle, p2, &pack2, 0);
+
+        // The whole table learns the draft is over, then each player is sent
+        // their own pool.
+        for chan in [&mut chan1, &mut chan2] {
+            receive_until(chan, |m| matches!(m, ServerMessage::DraftComplete)).await;
+            assert_matches!(
+                receive(chan).await,
+                ServerMessage::Finished(cards) if cards.len() == 1
+            );
+        }
+        receive_until(&mut spectator, |m| {
+            matches!(m, ServerMessage::DraftComplete)
+        })
+        .await;
     }
 }
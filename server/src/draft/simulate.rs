@@ -0,0 +1,177 @@
+use uuid::Uuid;
+
+use crate::cards::{card_entry, cockatrice::decode_xml_cards, Card, CardDatabase};
+
+use super::{
+    game::{auto_pick, Draft},
+    handlers::build_pool,
+    packs::make_packs,
+    DraftConfig,
+};
+
+/// Description of a draft to simulate, read from a JSON file. Laid out as a
+/// JSON launch request, with the number of players at the table.
+#[derive(serde::Deserialize)]
+pub struct SimulateJson {
+    #[serde(default)]
+    config: DraftConfig,
+
+    /// Names of the cards to include in packs.
+    list: Vec<String>,
+
+    /// Names of cards to remove from the list.
+    #[serde(default)]
+    exclude: Vec<String>,
+
+    /// Card database for custom cards, as Cockatrice XML.
+    card_database: Option<String>,
+
+    /// Number of seats at the table. Defaults to the minimum for the draft.
+    players: Option<usize>,
+}
+
+impl SimulateJson {
+    /// Whether cards must be looked up in the Scryfall database, as no card
+    /// database was provided.
+    pub fn needs_carddb(&self) -> bool {
+        self.card_database.is_none()
+    }
+}
+
+/// Outcome of a simulated draft.
+#[derive(Debug, serde::Serialize)]
+pub struct SimulateResult {
+    /// Seed used for the draft, so that it can be repeated.
+    seed: u64,
+
+    /// Final pool of each seat, in seating order.
+    pools: Vec<Vec<Card>>,
+}
+
+/// Run a draft with every pick made automatically, using the configured
+/// auto pick strategy.
+pub fn simulate(input: SimulateJson, carddb: &CardDatabase) -> Result<SimulateResult, String> {
+    let cards = match input.card_database {
+        Some(xml) => Some(
+            decode_xml_cards(xml.into())
+                .map_err(|e| format!("Failed to load card database: {e}"))?
+                .cards,
+        ),
+        None => None,
+    };
+    let mut pool = build_pool(
+        carddb,
+        cards.as_ref(),
+        input.list.iter().map(String::as_str),
+        usize::MAX,
+    )
+    .map_err(|e| e.to_string())?;
+    for name in &input.exclude {
+        pool.remove(card_entry(name).1);
+    }
+
+    let config = input.config;
+    let players = input.players.unwrap_or(config.min_players).max(1);
+    let packs = make_packs(players, &config, pool).map_err(|e| e.to_string())?;
+    let seats: Vec<Uuid> = (0..players).map(|_| Uuid::new_v4()).collect();
+    let seed = config.seed.unwrap_or_else(rand::random);
+    let mut draft = Draft::new(
+        seats.clone(),
+        config.rounds,
+        packs,
+        config.pass_model,
+        config.first_direction,
+        seed,
+    );
+    draft.begin();
+
+    while !draft.draft_complete() {
+        let mut picked = false;
+        for &seat in &seats {
+            if let Some(pack) = draft.current_pack(seat) {
+                let pool = draft.drafted_cards(seat).map(Vec::as_slice);
+                let index = auto_pick(
+                    &pack.cards,
+                    pool.unwrap_or_default(),
+                    config.auto_pick,
+                    &mut draft.pack_rng(&pack),
+                );
+                draft.handle_pick(seat, index).map_err(|e| e.to_string())?;
+                picked = true;
+            }
+        }
+
+        if !picked {
+            return Err("Draft stalled with no packs to pick.".to_string());
+        }
+    }
+
+    let pools = seats
+        .iter()
+        .map(|&seat| draft.drafted_cards(seat).cloned().unwrap_or_default())
+        .collect();
+    Ok(SimulateResult { seed, pools })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cards::CardDatabase;
+
+    use super::{simulate, SimulateJson};
+
+    #[test]
+    fn test_simulate() {
+        let mut xml = String::from("<cockatrice_carddatabase version=\"3\"><cards>");
+        let mut list = Vec::new();
+        for i in 0..24 {
+            let rarity = if i % 4 == 0 { "Rare" } else { "Common" };
+            xml.push_str(&format!(
+                concat!(
+                    "<card><name>Card {i}</name>",
+                    "<set rarity=\"{rarity}\" picURL=\"https://example.com/{i}.jpg\">TST</set>",
+                    "<manacost>1</manacost><cmc>1</cmc><type>Creature</type>",
+                    "<text></text></card>"
+                ),
+                i = i,
+                rarity = rarity,
+            ));
+            list.push(format!("Card {i}"));
+        }
+        xml.push_str("</cards></cockatrice_carddatabase>");
+
+        let input: SimulateJson = serde_json::from_value(serde_json::json!({
+            "config": {
+                "rounds": 2,
+                "cards_per_pack": 3,
+                "rares": 1,
+                "uncommons": 0,
+                "commons": 2,
+                "seed": 7,
+            },
+            "list": list,
+            "card_database": xml,
+            "players": 4,
+        }))
+        .unwrap();
+        assert!(!input.needs_carddb());
+
+        let result = simulate(input, &CardDatabase::new()).unwrap();
+        let output: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&result).unwrap()).unwrap();
+        assert_eq!(output["seed"], 7);
+        let pools = output["pools"].as_array().unwrap();
+        assert_eq!(pools.len(), 4);
+        assert!(pools.iter().all(|pool| pool.as_array().unwrap().len() == 6));
+    }
+
+    #[test]
+    fn test_simulate_unknown_card() {
+        let input: SimulateJson = serde_json::from_value(serde_json::json!({
+            "list": ["Not a card"],
+            "players": 2,
+        }))
+        .unwrap();
+        assert!(input.needs_carddb());
+        assert!(simulate(input, &CardDatabase::new()).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use std::{
+    net::{IpAddr, SocketAddr},
+    sync::Arc,
+};
+
+use tokio::sync::mpsc::{error::TrySendError, Receiver, Sender};
+use uuid::Uuid;
+
+use crate::{cards::Card, err, Res};
+
+/// Maximum number of events waiting to be delivered. Further events are
+/// dropped until the queue drains, so a slow webhook never blocks the draft.
+const QUEUE_SIZE: usize = 64;
+
+/// Number of times delivery of an event is attempted before giving up.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Time to wait for the webhook to respond to a request.
+const TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(tag = "event")]
+pub enum WebhookEvent {
+    DraftStarted {
+        draft: Uuid,
+        players: Vec<Uuid>,
+    },
+    Pick {
+        draft: Uuid,
+        seat: Uuid,
+        card: Box<Card>,
+    },
+    DraftFinished {
+        draft: Uuid,
+    },
+}
+
+/// Whether the address is on the server's own machine or network, rather
+/// than the public internet: loopback, private, link-local and the like.
+fn is_internal(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(ip) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || ip
+                    .to_ipv4_mapped()
+                    .is_some_and(|ip| is_internal(IpAddr::V4(ip)))
+        }
+    }
+}
+
+/// Check that a webhook URL is an absolute HTTP(S) URL. Unless internal
+/// addresses are allowed, the host mustn't be one, so that launchers can't
+/// have the server send requests to its own network.
+pub fn validate_url(url: &str, allow_internal: bool) -> Res<()> {
+    let url = match reqwest::Url::parse(url) {
+        Ok(url) if url.scheme() == "http" || url.scheme() == "https" => url,
+        Ok(_) => return err("Webhook URL must use http or https."),
+        Err(e) => return err(format!("Invalid webhook URL: {e}")),
+    };
+
+    if allow_internal {
+        return Ok(());
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    let internal = match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(ip) => is_internal(ip),
+        Err(_) => host == "localhost" || host.ends_with(".localhost"),
+    };
+    if internal {
+        err("Webhook URL must be a public address.")
+    } else {
+        Ok(())
+    }
+}
+
+/// Resolves hosts as usual, but drops internal addresses, so that a public
+/// domain name can't be pointed at the server's own network.
+struct PublicResolver;
+
+impl reqwest::dns::Resolve for PublicResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| !is_internal(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address.", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as reqwest::dns::Addrs)
+        })
+    }
+}
+
+/// Handle to a task which POSTs draft events as JSON to a URL.
+pub struct Webhook {
+    queue: Sender<WebhookEvent>,
+}
+
+impl Webhook {
+    /// Start delivering events to the URL. Unless internal addresses are
+    /// allowed, hosts which resolve only to internal addresses are refused.
+    pub fn spawn(url: String, allow_internal: bool) -> Self {
+        let (send, recv) = tokio::sync::mpsc::channel(QUEUE_SIZE);
+        tokio::spawn(deliver(url, allow_internal, recv));
+        Self { queue: send }
+    }
+
+    /// Queue an event for delivery. Never blocks.
+    pub fn send(&self, event: WebhookEvent) {
+        if let Err(TrySendError::Full(_)) = self.queue.try_send(event) {
+            tracing::debug!("Webhook queue full, dropping event.");
+        }
+    }
+}
+
+async fn deliver(url: String, allow_internal: bool, mut queue: Receiver<WebhookEvent>) {
+    // Redirects aren't followed, as they could lead to an internal address.
+    let mut builder = reqwest::Client::builder()
+        .timeout(TIMEOUT)
+        .redirect(reqwest::redirect::Policy::none());
+    if !allow_internal {
+        builder = builder.dns_resolver(Arc::new(PublicResolver));
+    }
+    let client = match builder.build() {
+        Ok(client) => client,
+        Err(e) => {
+            tracing::debug!("Failed to create webhook client: {e}");
+            return;
+        }
+    };
+
+    while let Some(event) = queue.recv().await {
+        let body = match serde_json::ser::to_vec(&event) {
+            Ok(body) => body,
+            Err(e) => {
+                tracing::debug!("Failed to encode webhook event: {e}");
+                continue;
+            }
+        };
+
+        for attempt in 1..=MAX_ATTEMPTS {
+            let resp = client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .body(body.clone())
+                .send()
+                .await;
+            match resp {
+                Ok(resp) if resp.status().is_success() => break,
+                Ok(resp) => tracing::debug!(
+                    "Webhook responded {} (attempt {attempt}/{MAX_ATTEMPTS}).",
+                    resp.status()
+                ),
+                Err(e) => {
+                    tracing::debug!(
+                        "Webhook request failed (attempt {attempt}/{MAX_ATTEMPTS}): {e}"
+                    )
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use axum::{extract::State, routing::post, Router};
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
+    use uuid::Uuid;
+
+    use super::{validate_url, Webhook, WebhookEvent};
+
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com/hook", false).is_ok());
+        assert!(validate_url("http://93.184.215.14/hook", false).is_ok());
+        assert!(validate_url("ftp://example.com", false).is_err());
+        assert!(validate_url("example.com/hook", false).is_err());
+
+        // Internal addresses are only allowed if the operator allows them.
+        for url in [
+            "http://localhost:8080",
+            "http://127.0.0.1/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://10.1.2.3/",
+            "http://192.168.0.1/",
+            "http://[::1]/",
+            "http://[fd00::1]/",
+            "http://[::ffff:127.0.0.1]/",
+        ] {
+            assert!(validate_url(url, false).is_err(), "{url}");
+            assert!(validate_url(url, true).is_ok(), "{url}");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_public_resolver() {
+        use reqwest::dns::Resolve;
+
+        let name = "localhost".parse().unwrap();
+        assert!(super::PublicResolver.resolve(name).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_deliver_events() {
+        async fn hook(State(chan): State<UnboundedSender<String>>, body: String) {
+            chan.send(body).ok();
+        }
+
+        let (send, mut recv) = unbounded_channel();
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let app = Router::new().route("/", post(hook)).with_state(send);
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let webhook = Webhook::spawn(format!("http://{addr}/"), true);
+        let draft = Uuid::new_v4();
+        webhook.send(WebhookEvent::DraftStarted {
+            draft,
+            players: Vec::new(),
+        });
+        webhook.send(WebhookEvent::DraftFinished { draft });
+
+        let started: serde_json::Value = serde_json::from_str(&recv.recv().await.unwrap()).unwrap();
+        assert_eq!(started["event"], "DraftStarted");
+        assert_eq!(started["draft"], draft.to_string());
+        let finished: serde_json::Value =
+            serde_json::from_str(&recv.recv().await.unwrap()).unwrap();
+        assert_eq!(finished["event"], "DraftFinished");
+    }
+}
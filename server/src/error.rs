@@ -0,0 +1,211 @@
+use std::fmt::Display;
+
+use crate::cards::Rarity;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DraftError {
+    /// The pool has no cards left at all.
+    PoolEmpty,
+
+    /// The pool has no cards of the given rarity, or suitable replacements.
+    RarityUnavailable(Rarity),
+
+    /// The pool ran out while generating packs. Needed is the number of
+    /// additional cards that would have been required, of the given rarity if
+    /// any, otherwise in total.
+    PoolInsufficient {
+        rarity: Option<Rarity>,
+        needed: usize,
+        packs: usize,
+        players: usize,
+    },
+
+    /// The pool is short of cards in several ways, each described by one of
+    /// the errors.
+    PoolIncomplete(Vec<DraftError>),
+
+    /// No card list was given to build the pool from.
+    NoCardList,
+
+    /// A line of the card list didn't match any known card.
+    CardNotFound(String),
+
+    /// The card list had more cards than the server allows, the maximum.
+    CardListTooLarge(usize),
+
+    /// Cards excluded from the pool which weren't in it.
+    ExcludedNotFound(Vec<String>),
+
+    /// An uploaded card database couldn't be read, for the given reason.
+    InvalidCardDatabase(String),
+
+    /// The pick index was outside of the player's current pack.
+    InvalidPickIndex,
+
+    /// The player has no pack to pick from.
+    NoCurrentPack,
+
+    /// The player isn't part of the draft.
+    PlayerNotInDraft,
+
+    /// The draft configuration was rejected, for the given reason.
+    InvalidConfig(String),
+
+    /// Something went wrong in the server, not caused by the request.
+    Internal(String),
+
+    /// Any other error, described by the message.
+    Other(String),
+}
+
+impl DraftError {
+    /// Error for a pool with the given shortfalls, the shortfall itself if
+    /// there is only one.
+    pub fn incomplete(mut shortfalls: Vec<DraftError>) -> Self {
+        if shortfalls.len() == 1 {
+            shortfalls.remove(0)
+        } else {
+            DraftError::PoolIncomplete(shortfalls)
+        }
+    }
+
+    /// Name of the kind of error, for clients to branch on.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DraftError::PoolEmpty => "pool_empty",
+            DraftError::RarityUnavailable(..) => "rarity_unavailable",
+            DraftError::PoolInsufficient { .. } => "pool_insufficient",
+            DraftError::PoolIncomplete(..) => "pool_incomplete",
+            DraftError::NoCardList => "no_card_list",
+            DraftError::CardNotFound(..) => "card_not_found",
+            DraftError::CardListTooLarge(..) => "card_list_too_large",
+            DraftError::ExcludedNotFound(..) => "excluded_not_found",
+            DraftError::InvalidCardDatabase(..) => "invalid_card_database",
+            DraftError::InvalidPickIndex => "invalid_pick_index",
+            DraftError::NoCurrentPack => "no_current_pack",
+            DraftError::PlayerNotInDraft => "player_not_in_draft",
+            DraftError::InvalidConfig(..) => "invalid_config",
+            DraftError::Internal(..) => "internal",
+            DraftError::Other(..) => "other",
+        }
+    }
+}
+
+impl Display for DraftError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DraftError::PoolEmpty => write!(f, "Insufficient cards in pool."),
+            DraftError::RarityUnavailable(rarity) => write!(f, "Insufficient {rarity:?}s in pool."),
+            DraftError::PoolInsufficient {
+                rarity,
+                needed,
+                packs,
+                players,
+            } => {
+                let kind = match rarity {
+                    Some(rarity) => format!("{rarity:?}s").to_lowercase(),
+                    None => "cards".to_string(),
+                };
+                write!(
+                    f,
+                    "Insufficient cards in pool, needed {needed} more {kind} to complete {packs} packs for {players} players."
+                )
+            }
+            DraftError::PoolIncomplete(shortfalls) => {
+                let messages: Vec<String> = shortfalls.iter().map(ToString::to_string).collect();
+                write!(f, "{}", messages.join(" "))
+            }
+            DraftError::NoCardList => write!(f, "No card list provided for draft."),
+            DraftError::CardNotFound(line) => {
+                write!(f, "Card not found in custom list or database: {line}")
+            }
+            DraftError::CardListTooLarge(max) => write!(
+                f,
+                "Card list contains more than the maximum allowed number of cards ({max})."
+            ),
+            DraftError::ExcludedNotFound(names) => write!(
+                f,
+                "Excluded cards not found in card list: {}",
+                names.join(", ")
+            ),
+            DraftError::InvalidCardDatabase(e) => write!(f, "Failed to load card database: {e}"),
+            DraftError::InvalidPickIndex => write!(f, "Invalid pick index."),
+            DraftError::NoCurrentPack => write!(f, "No current pack."),
+            DraftError::PlayerNotInDraft => write!(f, "Player not in draft."),
+            DraftError::InvalidConfig(message)
+            | DraftError::Internal(message)
+            | DraftError::Other(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for DraftError {}
+
+/// Serialised with the kind of error, a human-readable message and any
+/// details, e.g. `{"kind": "rarity_unavailable", "message": "...", "rarity":
+/// "Rare"}`.
+impl serde::Serialize for DraftError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeMap;
+
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("kind", self.kind())?;
+        map.serialize_entry("message", &self.to_string())?;
+        match self {
+            DraftError::RarityUnavailable(rarity) => map.serialize_entry("rarity", rarity)?,
+            DraftError::PoolInsufficient {
+                rarity,
+                needed,
+                packs,
+                players,
+            } => {
+                map.serialize_entry("rarity", rarity)?;
+                map.serialize_entry("needed", needed)?;
+                map.serialize_entry("packs", packs)?;
+                map.serialize_entry("players", players)?;
+            }
+            DraftError::PoolIncomplete(shortfalls) => {
+                map.serialize_entry("shortfalls", shortfalls)?
+            }
+            DraftError::CardNotFound(line) => map.serialize_entry("card", line)?,
+            DraftError::CardListTooLarge(max) => map.serialize_entry("max", max)?,
+            DraftError::ExcludedNotFound(names) => map.serialize_entry("cards", names)?,
+            _ => {}
+        }
+        map.end()
+    }
+}
+
+pub type Res<T> = Result<T, DraftError>;
+
+pub fn err<T, S: ToString>(message: S) -> Res<T> {
+    Err(DraftError::Other(message.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::cards::Rarity;
+
+    use super::DraftError;
+
+    #[test]
+    fn test_serialise_error() {
+        let json = serde_json::to_value(DraftError::PoolInsufficient {
+            rarity: Some(Rarity::Rare),
+            needed: 2,
+            packs: 3,
+            players: 3,
+        })
+        .unwrap();
+        assert_eq!(json["kind"], "pool_insufficient");
+        assert_eq!(json["rarity"], "Rare");
+        assert_eq!(json["needed"], 2);
+        assert!(json["message"].as_str().unwrap().contains("2 more rares"));
+
+        let json = serde_json::to_value(DraftError::InvalidConfig("Bad.".into())).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "invalid_config", "message": "Bad." })
+        );
+    }
+}
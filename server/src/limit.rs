@@ -0,0 +1,117 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// Tokens held by a single client, as of the last time they were counted.
+#[derive(Clone, Copy, Debug)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token bucket rate limiter, keyed by client IP address. Each client may
+/// make a burst of up to `capacity` requests, after which tokens are refilled
+/// at a steady rate over the period.
+pub struct RateLimiter {
+    capacity: f64,
+    period: Duration,
+    buckets: Mutex<HashMap<IpAddr, Bucket>>,
+}
+
+impl RateLimiter {
+    /// Allow each client this many requests per period. Zero disables the
+    /// limit.
+    pub fn new(capacity: u32, period: Duration) -> Self {
+        Self {
+            capacity: capacity as f64,
+            period,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Take a token for a request from the client, if they have one left.
+    /// Returns whether the request is allowed.
+    pub fn try_acquire(&self, ip: IpAddr) -> bool {
+        self.try_acquire_at(ip, Instant::now())
+    }
+
+    fn try_acquire_at(&self, ip: IpAddr, now: Instant) -> bool {
+        if self.capacity == 0.0 {
+            return true;
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Forget clients whose buckets have refilled, so that the map doesn't
+        // grow without bound.
+        buckets.retain(|_, bucket| self.tokens(bucket, now) < self.capacity);
+
+        let bucket = buckets.entry(ip).or_insert(Bucket {
+            tokens: self.capacity,
+            updated: now,
+        });
+        bucket.tokens = self.tokens(bucket, now);
+        bucket.updated = now;
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Tokens in the bucket at the given time, including those refilled since
+    /// it was last counted.
+    fn tokens(&self, bucket: &Bucket, now: Instant) -> f64 {
+        let elapsed = now.saturating_duration_since(bucket.updated);
+        let refilled = elapsed.as_secs_f64() / self.period.as_secs_f64() * self.capacity;
+        (bucket.tokens + refilled).min(self.capacity)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::{Duration, Instant};
+
+    use super::RateLimiter;
+
+    #[test]
+    fn test_rate_limiter() {
+        let limiter = RateLimiter::new(5, Duration::from_secs(60));
+        let a = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1));
+        let b = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2));
+        let start = Instant::now();
+
+        // A burst of up to the capacity is allowed, then requests are
+        // rejected until tokens are refilled.
+        for _ in 0..5 {
+            assert!(limiter.try_acquire_at(a, start));
+        }
+        assert!(!limiter.try_acquire_at(a, start));
+
+        // Each client has their own bucket.
+        assert!(limiter.try_acquire_at(b, start));
+
+        // One token is refilled every 12 seconds.
+        assert!(!limiter.try_acquire_at(a, start + Duration::from_secs(6)));
+        assert!(limiter.try_acquire_at(a, start + Duration::from_secs(12)));
+        assert!(!limiter.try_acquire_at(a, start + Duration::from_secs(13)));
+
+        // Full buckets are forgotten.
+        assert!(limiter.try_acquire_at(a, start + Duration::from_secs(120)));
+        assert_eq!(limiter.buckets.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let limiter = RateLimiter::new(0, Duration::from_secs(60));
+        let ip = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        for _ in 0..100 {
+            assert!(limiter.try_acquire(ip));
+        }
+    }
+}
@@ -1,17 +1,25 @@
 #![feature(assert_matches)]
 #![feature(let_chains)]
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
 use axum::{
-    extract::{Multipart, Path, State, WebSocketUpgrade},
-    http::{Response, StatusCode},
+    extract::{
+        multipart::MultipartRejection, ConnectInfo, FromRequest, Multipart, Path, Request, State,
+        WebSocketUpgrade,
+    },
+    http::{header, HeaderMap, Response, StatusCode},
     response::IntoResponse,
     routing::{get, post},
-    Router,
+    Json, Router,
 };
-use cards::CardDatabase;
-use draft::server::ServerPool;
+use cards::{cubes::Cubes, CardDatabase};
+use draft::server::{ConnectRequest, DraftServerRequest, ServerPool, SnapshotConfig};
 use tokio::{net::TcpListener, sync::RwLock};
 use tower_http::{
     services::{ServeDir, ServeFile},
@@ -21,17 +29,19 @@ use uuid::Uuid;
 
 mod cards;
 mod draft;
+mod error;
+mod limit;
 
-type Res<T> = Result<T, String>;
-
-fn err<T, S: ToString>(message: S) -> Res<T> {
-    Err(message.to_string())
-}
+use error::{err, DraftError, Res};
 
 #[derive(serde::Serialize)]
 struct Resp {
     message: String,
     success: bool,
+
+    /// Structured description of the failure, where there is one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<DraftError>,
 }
 
 impl Resp {
@@ -40,28 +50,34 @@ impl Resp {
             Self {
                 message: message.to_string(),
                 success: status == StatusCode::OK,
+                error: None,
             },
             status,
         )
     }
 
-    fn e500<S: ToString>(message: S) -> Response<String> {
-        Self::basic(message, StatusCode::INTERNAL_SERVER_ERROR)
+    /// Reject a request because of the error, describing it in the body.
+    fn error(error: DraftError, status: StatusCode) -> Response<String> {
+        Self::json(
+            Self {
+                message: error.to_string(),
+                success: false,
+                error: Some(error),
+            },
+            status,
+        )
     }
 
-    fn e422<S: ToString>(message: S) -> Response<String> {
-        Self::basic(message, StatusCode::UNPROCESSABLE_ENTITY)
+    /// Reject a launch because of a problem with the draft configuration.
+    fn invalid<S: ToString>(message: S) -> Response<String> {
+        Self::error(
+            DraftError::InvalidConfig(message.to_string()),
+            StatusCode::UNPROCESSABLE_ENTITY,
+        )
     }
 
-    fn redirect<S: ToString>(uri: String, message: S) -> Response<String> {
-        match Response::builder()
-            .header("Location", uri)
-            .status(StatusCode::SEE_OTHER)
-            .body(message.to_string())
-        {
-            Ok(resp) => resp,
-            Err(e) => Self::e500(format!("Failed to build redirect response: {e}")),
-        }
+    fn e500<S: ToString>(message: S) -> Response<String> {
+        Self::basic(message, StatusCode::INTERNAL_SERVER_ERROR)
     }
 
     fn json<S: serde::Serialize>(body: S, status: StatusCode) -> Response<String> {
@@ -83,11 +99,18 @@ impl Resp {
 async fn websocket_handler(
     lobby: Uuid,
     seat: Uuid,
-    servers: Servers,
+    connect: ConnectRequest,
+    state: Arc<AppState>,
     sock: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    if let Some(server) = servers.read().await.handle(lobby) {
-        sock.on_upgrade(move |ws| draft::handlers::handle_websocket_connection(ws, server, seat))
+    if let Some(server) = state.servers.read().await.handle(lobby) {
+        let config = state.config.clone();
+        // Limit the size of messages at the protocol level, so that oversized
+        // messages are rejected before being buffered.
+        sock.max_message_size(config.max_message_size)
+            .on_upgrade(move |ws| {
+                draft::handlers::handle_websocket_connection(ws, server, seat, connect, config)
+            })
     } else {
         // Server already closed. Just tell the client the draft has ended.
         sock.on_upgrade(move |mut ws| async move {
@@ -103,21 +126,316 @@ async fn join_table_handler(
     State(state): State<Arc<AppState>>,
     upgrade: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    websocket_handler(lobby, Uuid::new_v4(), state.servers.clone(), upgrade).await
+    websocket_handler(
+        lobby,
+        Uuid::new_v4(),
+        DraftServerRequest::Connect,
+        state,
+        upgrade,
+    )
+    .await
 }
 async fn resume_seat_handler(
     Path((lobby, seat)): Path<(Uuid, Uuid)>,
     State(state): State<Arc<AppState>>,
     upgrade: WebSocketUpgrade,
 ) -> impl IntoResponse {
-    websocket_handler(lobby, seat, state.servers.clone(), upgrade).await
+    websocket_handler(lobby, seat, DraftServerRequest::Connect, state, upgrade).await
+}
+
+async fn spectate_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+    upgrade: WebSocketUpgrade,
+) -> impl IntoResponse {
+    websocket_handler(
+        lobby,
+        Uuid::new_v4(),
+        DraftServerRequest::Spectate,
+        state,
+        upgrade,
+    )
+    .await
+}
+
+/// Name of the cookie holding the token which identifies a user.
+const USER_TOKEN_COOKIE: &str = "drafttool_token";
+
+/// Read the user's token from the request cookies, if present and valid.
+fn user_token(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|cookies| cookies.split(';'))
+        .filter_map(|cookie| cookie.trim().split_once('='))
+        .find(|(name, _)| *name == USER_TOKEN_COOKIE)
+        .map(|(_, token)| token.to_string())
+        .filter(|token| {
+            !token.is_empty()
+                && token.len() <= 64
+                && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
+
+/// Whether the request body is JSON, judging by its content type.
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"))
+}
+
+/// Reject a launch if the client has launched too many lobbies recently.
+fn launch_limited(state: &AppState, addr: SocketAddr) -> Option<Response<String>> {
+    if state.launches.try_acquire(addr.ip()) {
+        None
+    } else {
+        Some(Resp::basic(
+            "Too many lobbies launched recently, try again later.",
+            StatusCode::TOO_MANY_REQUESTS,
+        ))
+    }
 }
 
 async fn launch_handler(
     State(state): State<Arc<AppState>>,
-    data: Multipart,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    request: Request,
 ) -> axum::http::Response<String> {
-    draft::handlers::handle_launch_request(state.carddb.clone(), state.servers.clone(), data).await
+    if let Some(resp) = launch_limited(&state, addr) {
+        return resp;
+    }
+
+    if is_json(&headers) {
+        return match Json::from_request(request, &()).await {
+            Ok(Json(body)) => {
+                draft::handlers::handle_json_launch_request(
+                    state.carddb(),
+                    state.servers.clone(),
+                    state.config.clone(),
+                    body,
+                    user_token(&headers),
+                )
+                .await
+            }
+            Err(e) => Resp::basic(e.body_text(), e.status()),
+        };
+    }
+
+    let data = match Multipart::from_request(request, &()).await {
+        Ok(data) => data,
+        Err(e) => return Resp::basic(e.body_text(), StatusCode::BAD_REQUEST),
+    };
+
+    draft::handlers::handle_launch_request(
+        state.carddb(),
+        state.servers.clone(),
+        state.config.clone(),
+        data,
+        user_token(&headers),
+    )
+    .await
+}
+
+async fn preview_handler(
+    State(state): State<Arc<AppState>>,
+    data: Result<Multipart, MultipartRejection>,
+) -> Response<String> {
+    let data = match data {
+        Ok(data) => data,
+        Err(e) => return Resp::basic(e.body_text(), StatusCode::BAD_REQUEST),
+    };
+
+    draft::handlers::handle_preview_request(state.carddb(), state.config.clone(), data).await
+}
+
+#[derive(serde::Serialize)]
+struct CubeSummary<'a> {
+    name: &'a str,
+    cards: usize,
+}
+
+async fn list_cubes_handler(State(state): State<Arc<AppState>>) -> Response<String> {
+    let mut cubes: Vec<CubeSummary> = state
+        .cubes
+        .get()
+        .into_iter()
+        .flatten()
+        .map(|(name, cards)| CubeSummary {
+            name,
+            cards: cards.len(),
+        })
+        .collect();
+    cubes.sort_by_key(|cube| cube.name);
+    Resp::json(cubes, StatusCode::OK)
+}
+
+async fn cube_launch_handler(
+    Path(name): Path<String>,
+    State(state): State<Arc<AppState>>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    data: Result<Multipart, MultipartRejection>,
+) -> Response<String> {
+    if let Some(resp) = launch_limited(&state, addr) {
+        return resp;
+    }
+
+    let data = match data {
+        Ok(data) => data,
+        Err(e) => return Resp::basic(e.body_text(), StatusCode::BAD_REQUEST),
+    };
+
+    let Some(cubes) = state.cubes.get() else {
+        return draft::handlers::loading_response();
+    };
+    let Some(cube) = cubes.get(&name) else {
+        return Resp::basic(format!("No cube named {name}."), StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_cube_launch_request(
+        cube,
+        state.servers.clone(),
+        state.config.clone(),
+        data,
+        user_token(&headers),
+    )
+    .await
+}
+
+async fn pools_csv_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_pools_csv_request(server).await
+}
+
+async fn pool_cod_handler(
+    Path((lobby, seat)): Path<(Uuid, Uuid)>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_pool_cod_request(server, seat).await
+}
+
+async fn pool_cockatrice_handler(
+    Path((lobby, seat)): Path<(Uuid, Uuid)>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_pool_cockatrice_request(server, seat).await
+}
+
+async fn pool_arena_handler(
+    Path((lobby, seat)): Path<(Uuid, Uuid)>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_pool_arena_request(server, seat).await
+}
+
+async fn pick_log_handler(
+    Path((lobby, seat)): Path<(Uuid, Uuid)>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_pick_log_request(server, seat).await
+}
+
+async fn pool_svg_handler(
+    Path((lobby, seat)): Path<(Uuid, Uuid)>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_pool_svg_request(server, seat).await
+}
+
+async fn lobby_status_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_lobby_status_request(server).await
+}
+
+async fn lobbies_handler(State(state): State<Arc<AppState>>) -> Response<String> {
+    draft::handlers::handle_lobbies_request(state.servers.clone()).await
+}
+
+async fn rarity_counts_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_rarity_counts_request(server).await
+}
+
+async fn results_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_results_request(server).await
+}
+
+async fn opened_packs_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_opened_packs_request(server).await
+}
+
+async fn sample_pack_handler(
+    Path(lobby): Path<Uuid>,
+    State(state): State<Arc<AppState>>,
+) -> Response<String> {
+    let Some(server) = state.servers.read().await.handle(lobby) else {
+        return Resp::basic("Lobby not found.", StatusCode::NOT_FOUND);
+    };
+
+    draft::handlers::handle_sample_pack_request(server).await
+}
+
+async fn health_handler(State(state): State<Arc<AppState>>) -> Response<String> {
+    if state.carddb.get().is_some() {
+        Resp::basic("Ready.", StatusCode::OK)
+    } else {
+        Resp::basic("Loading cards.", StatusCode::SERVICE_UNAVAILABLE)
+    }
 }
 
 async fn load_card_database(data: &std::path::Path) -> Result<CardDatabase, String> {
@@ -136,15 +454,231 @@ async fn load_card_database(data: &std::path::Path) -> Result<CardDatabase, Stri
 
 type Servers = Arc<RwLock<ServerPool>>;
 
+/// How often closed lobbies are removed from the server pool.
+const REAP_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Periodically remove closed lobbies from the pool, so that it doesn't grow
+/// without bound while no new lobbies are launched.
+async fn reap_lobbies(servers: Servers) {
+    let mut interval = tokio::time::interval(REAP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let reaped = servers.write().await.reap();
+        if reaped > 0 {
+            tracing::debug!("Removed {reaped} closed lobbies.");
+        }
+    }
+}
+
+/// Operator configuration for the server. Each value may be overridden with
+/// an environment variable.
+#[derive(Clone, Debug)]
+struct ServerConfig {
+    /// Largest websocket message accepted from a client, in bytes.
+    max_message_size: usize,
+
+    /// Most websocket messages a client may send in one second.
+    max_message_rate: u32,
+
+    /// Most packs each player may open in a draft.
+    max_rounds: usize,
+
+    /// Most cards allowed in a single pack.
+    max_cards_per_pack: usize,
+
+    /// Most cards allowed in the pool for a draft, after resolving the list.
+    max_pool_size: usize,
+
+    /// Most cards across all packs generated for the minimum number of
+    /// players.
+    max_total_cards: usize,
+
+    /// How long a lobby may go without any connected players before it is
+    /// closed. Set in seconds.
+    lobby_timeout: Duration,
+
+    /// How long a lobby may stay open without the draft starting, even with
+    /// players connected. Set in seconds.
+    lobby_idle_expiry: Duration,
+
+    /// Most lobbies a single user may have open at once.
+    max_lobbies_per_user: usize,
+
+    /// Most lobbies a single IP address may launch in a minute, in a burst.
+    /// Launches are then allowed at a steady rate. Zero disables the limit.
+    max_launches_per_minute: u32,
+
+    /// Directory lobbies are saved to, so that they survive a restart.
+    /// Defaults to `lobbies` in the data directory, set empty to disable.
+    snapshot_dir: Option<PathBuf>,
+
+    /// Longest a change to a lobby may go unsaved. Set in seconds.
+    snapshot_interval: Duration,
+
+    /// Whether lobbies may send webhook events to loopback, private and
+    /// link-local addresses. Off by default, so that anyone launching a
+    /// lobby can't use the server to reach its own network.
+    allow_internal_webhooks: bool,
+}
+
+impl ServerConfig {
+    fn from_env(data: &str) -> Self {
+        fn var<T: std::str::FromStr>(key: &str, default: T) -> T {
+            match std::env::var(key) {
+                Ok(s) => s
+                    .parse()
+                    .unwrap_or_else(|_| panic!("Invalid value for {key}: {s}")),
+                Err(_) => default,
+            }
+        }
+
+        let default = Self::default();
+        let snapshot_dir = var(
+            "DRAFTTOOL_SNAPSHOT_DIR",
+            PathBuf::from(data).join("lobbies"),
+        );
+        Self {
+            max_message_size: var("DRAFTTOOL_MAX_MESSAGE_SIZE", default.max_message_size),
+            max_message_rate: var("DRAFTTOOL_MAX_MESSAGE_RATE", default.max_message_rate),
+            max_rounds: var("DRAFTTOOL_MAX_ROUNDS", default.max_rounds),
+            max_cards_per_pack: var("DRAFTTOOL_MAX_CARDS_PER_PACK", default.max_cards_per_pack),
+            max_pool_size: var("DRAFTTOOL_MAX_POOL_SIZE", default.max_pool_size),
+            max_total_cards: var("DRAFTTOOL_MAX_TOTAL_CARDS", default.max_total_cards),
+            lobby_timeout: Duration::from_secs(var(
+                "DRAFTTOOL_LOBBY_TIMEOUT",
+                default.lobby_timeout.as_secs(),
+            )),
+            lobby_idle_expiry: Duration::from_secs(var(
+                "DRAFTTOOL_LOBBY_IDLE_EXPIRY",
+                default.lobby_idle_expiry.as_secs(),
+            )),
+            max_lobbies_per_user: var(
+                "DRAFTTOOL_MAX_LOBBIES_PER_USER",
+                default.max_lobbies_per_user,
+            ),
+            max_launches_per_minute: var(
+                "DRAFTTOOL_MAX_LAUNCHES_PER_MINUTE",
+                default.max_launches_per_minute,
+            ),
+            snapshot_dir: (!snapshot_dir.as_os_str().is_empty()).then_some(snapshot_dir),
+            snapshot_interval: Duration::from_secs(var(
+                "DRAFTTOOL_SNAPSHOT_INTERVAL",
+                default.snapshot_interval.as_secs(),
+            )),
+            allow_internal_webhooks: var(
+                "DRAFTTOOL_ALLOW_INTERNAL_WEBHOOKS",
+                default.allow_internal_webhooks,
+            ),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_message_size: 64 * 1024,
+            max_message_rate: 20,
+            max_rounds: 10,
+            max_cards_per_pack: 60,
+            max_pool_size: 20_000,
+            max_total_cards: 50_000,
+            lobby_timeout: Duration::from_secs(10 * 60),
+            lobby_idle_expiry: Duration::from_secs(2 * 60 * 60),
+            max_lobbies_per_user: 5,
+            max_launches_per_minute: 5,
+            snapshot_dir: None,
+            snapshot_interval: Duration::from_secs(5),
+            allow_internal_webhooks: false,
+        }
+    }
+}
+
 struct AppState {
-    carddb: Arc<CardDatabase>,
+    /// Card database and cubes, set once loading finishes. Cards are loaded
+    /// in the background, so that the server is up while downloading them.
+    carddb: OnceLock<Arc<CardDatabase>>,
+    cubes: OnceLock<Cubes>,
     servers: Servers,
+    config: ServerConfig,
+
+    /// Limits how often each client may launch lobbies.
+    launches: limit::RateLimiter,
+}
+
+impl AppState {
+    /// The card database, once loading finishes.
+    fn carddb(&self) -> Option<Arc<CardDatabase>> {
+        self.carddb.get().cloned()
+    }
+}
+
+/// Populate the card database and cubes. The server can't run without cards,
+/// so exits if they can't be loaded.
+async fn load_cards(state: Arc<AppState>, data: PathBuf) {
+    let card_db = match load_card_database(&data).await {
+        Ok(db) => db,
+        Err(e) => {
+            tracing::error!("Failed to load scryfall card list: {e}");
+            std::process::exit(1);
+        }
+    };
+    let cubes = cards::cubes::load_cubes(&data.join("cubes"), &card_db).await;
+    state.cubes.set(cubes).ok();
+    state.carddb.set(Arc::new(card_db)).ok();
+    tracing::info!("Card database ready.");
+}
+
+/// Run a draft with every pick made automatically, printing the seed and
+/// each seat's pool as JSON, rather than starting the server.
+async fn simulate() {
+    const USAGE: &str = "Usage: server --simulate <draft json path> [data path]";
+
+    let path = std::env::args().nth(2).expect(USAGE);
+    let input: draft::simulate::SimulateJson = match std::fs::read(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|data| serde_json::from_slice(&data).map_err(|e| e.to_string()))
+    {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("Failed to read {path}: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    // Only load the Scryfall cards if the draft doesn't provide its own.
+    let carddb = if input.needs_carddb() {
+        let data = PathBuf::from(std::env::args().nth(3).expect(USAGE));
+        match load_card_database(&data).await {
+            Ok(db) => db,
+            Err(e) => {
+                eprintln!("Failed to load scryfall card list: {e}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        CardDatabase::new()
+    };
+
+    match draft::simulate::simulate(input, &carddb)
+        .and_then(|result| serde_json::to_string(&result).map_err(|e| e.to_string()))
+    {
+        Ok(json) => println!("{json}"),
+        Err(e) => {
+            eprintln!("Simulation failed: {e}");
+            std::process::exit(1);
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() {
     const USAGE: &str = "Usage: server <static path> <data path> <port>";
 
+    if std::env::args().nth(1).as_deref() == Some("--simulate") {
+        simulate().await;
+        return;
+    }
+
     let content = PathBuf::from(std::env::args().nth(1).expect(USAGE));
     let data = std::env::args().nth(2).expect(USAGE);
     let port = std::env::args()
@@ -159,21 +693,55 @@ async fn main() {
         .with_max_level(tracing::Level::DEBUG)
         .init();
 
-    let card_db = match load_card_database(&PathBuf::from(data)).await {
-        Ok(db) => db,
-        Err(e) => panic!("Failed to load scryfall card list: {e}"),
-    };
+    let config = ServerConfig::from_env(&data);
+
+    // Lobbies are saved as they change, so that they survive a restart.
+    let mut servers = ServerPool::new(config.lobby_timeout, config.lobby_idle_expiry);
+    if let Some(dir) = &config.snapshot_dir {
+        let restored = servers.restore(SnapshotConfig {
+            dir: dir.clone(),
+            interval: config.snapshot_interval,
+        });
+        tracing::info!("Restored {restored} lobbies.");
+    }
+
+    let state = Arc::new(AppState {
+        carddb: OnceLock::new(),
+        cubes: OnceLock::new(),
+        servers: Arc::new(RwLock::new(servers)),
+        launches: limit::RateLimiter::new(config.max_launches_per_minute, Duration::from_secs(60)),
+        config,
+    });
+    tokio::spawn(load_cards(state.clone(), PathBuf::from(data)));
+    tokio::spawn(reap_lobbies(state.servers.clone()));
 
     let app = Router::new()
         .fallback_service(ServeDir::new(&content).append_index_html_on_directories(true))
+        .route("/ws/:lobby/spectate", get(spectate_handler))
         .route("/ws/:lobby/:seat", get(resume_seat_handler))
         .route("/ws/:lobby", get(join_table_handler))
+        .route("/api/health", get(health_handler))
         .route("/api/start", post(launch_handler))
+        .route("/api/preview", post(preview_handler))
+        .route("/api/cubes", get(list_cubes_handler))
+        .route("/api/cubes/:name/start", post(cube_launch_handler))
+        .route("/api/lobbies", get(lobbies_handler))
+        .route("/api/lobby/:id", get(lobby_status_handler))
+        .route("/api/lobby/:id/pools.csv", get(pools_csv_handler))
+        .route("/api/lobby/:id/results.json", get(results_handler))
+        .route("/api/lobby/:id/rarities", get(rarity_counts_handler))
+        .route("/api/lobby/:id/sample-pack", get(sample_pack_handler))
+        .route("/api/lobby/:id/packs", get(opened_packs_handler))
+        .route("/api/draftlog/:lobby/:seat", get(pick_log_handler))
+        .route("/api/pool/:lobby/:seat/cod", get(pool_cod_handler))
+        .route(
+            "/api/pool/:lobby/:seat/cockatrice",
+            get(pool_cockatrice_handler),
+        )
+        .route("/api/pool/:lobby/:seat/arena", get(pool_arena_handler))
+        .route("/api/pool/:lobby/:seat/svg", get(pool_svg_handler))
         .route_service("/lobby/:id", ServeFile::new(content.join("draft.html")))
-        .with_state(Arc::new(AppState {
-            carddb: Arc::new(card_db),
-            servers: Arc::new(RwLock::new(ServerPool::new())),
-        }))
+        .with_state(state)
         .layer(TraceLayer::new_for_http());
 
     let listener = TcpListener::bind(format!("0.0.0.0:{port}"))
@@ -181,6 +749,8 @@ async fn main() {
         .unwrap_or_else(|_| panic!("Failed to open port {port}"));
 
     tracing::debug!("Starting to listen on :{port}");
+    // Client addresses are needed to rate limit launches.
+    let app = app.into_make_service_with_connect_info::<SocketAddr>();
     if let Err(e) = axum::serve(listener, app).await {
         eprintln!("Closed due to error: {e}");
     }